@@ -0,0 +1,157 @@
+use crate::data_types::NumberLike;
+
+/// A summary statistic that decompressors can compute over numbers
+/// without writing them all out.
+///
+/// Min and max compare numbers in the same order pco uses for latents.
+/// For floats, this means -0.0 < +0.0 and NaNs with the sign bit set are
+/// less than -inf, while other NaNs are greater than +inf.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Agg {
+  /// The count of numbers.
+  Count,
+  /// The sum of numbers, accumulated as `f64`.
+  Sum,
+  /// The smallest number.
+  Min,
+  /// The largest number.
+  Max,
+}
+
+/// The result of computing an [`Agg`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AggValue<T> {
+  /// The count of numbers.
+  Count(usize),
+  /// Since this is accumulated as `f64`, it may be rounded for large
+  /// integers.
+  Sum(f64),
+  /// This is `None` if there were no numbers.
+  Min(Option<T>),
+  /// This is `None` if there were no numbers.
+  Max(Option<T>),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Aggregator<T: NumberLike> {
+  agg: Agg,
+  count: usize,
+  sum: f64,
+  extremum: Option<T>,
+}
+
+impl<T: NumberLike> Aggregator<T> {
+  pub fn new(agg: Agg) -> Self {
+    Self {
+      agg,
+      count: 0,
+      sum: 0.0,
+      extremum: None,
+    }
+  }
+
+  fn update_extremum(&mut self, x: T) {
+    let replace = match (self.agg, self.extremum) {
+      (_, None) => true,
+      (Agg::Min, Some(y)) => x.to_latent_ordered() < y.to_latent_ordered(),
+      (Agg::Max, Some(y)) => x.to_latent_ordered() > y.to_latent_ordered(),
+      _ => false,
+    };
+    if replace {
+      self.extremum = Some(x);
+    }
+  }
+
+  pub fn update(&mut self, nums: &[T]) {
+    self.count += nums.len();
+    match self.agg {
+      Agg::Count => (),
+      Agg::Sum => {
+        for &x in nums {
+          self.sum += x.to_f64_lossy();
+        }
+      }
+      Agg::Min | Agg::Max => {
+        for &x in nums {
+          self.update_extremum(x);
+        }
+      }
+    }
+  }
+
+  // equivalent to `update` with `n` copies of `x`
+  pub fn update_constant(&mut self, x: T, n: usize) {
+    if n == 0 {
+      return;
+    }
+
+    self.count += n;
+    match self.agg {
+      Agg::Count => (),
+      Agg::Sum => self.sum += x.to_f64_lossy() * n as f64,
+      Agg::Min | Agg::Max => self.update_extremum(x),
+    }
+  }
+
+  // combines a value computed over other numbers with the same agg
+  pub fn update_value(&mut self, value: AggValue<T>) {
+    match value {
+      AggValue::Count(count) => self.count += count,
+      AggValue::Sum(sum) => self.sum += sum,
+      AggValue::Min(Some(x)) | AggValue::Max(Some(x)) => self.update_extremum(x),
+      AggValue::Min(None) | AggValue::Max(None) => (),
+    }
+  }
+
+  pub fn finish(self) -> AggValue<T> {
+    match self.agg {
+      Agg::Count => AggValue::Count(self.count),
+      Agg::Sum => AggValue::Sum(self.sum),
+      Agg::Min => AggValue::Min(self.extremum),
+      Agg::Max => AggValue::Max(self.extremum),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_aggregator() {
+    let nums = [3_i32, -7, 5];
+    let aggregate = |agg: Agg| {
+      let mut aggregator = Aggregator::new(agg);
+      aggregator.update(&nums);
+      aggregator.update_constant(-2, 2);
+      aggregator.update(&[]);
+      aggregator.finish()
+    };
+    assert_eq!(aggregate(Agg::Count), AggValue::Count(5));
+    assert_eq!(aggregate(Agg::Sum), AggValue::Sum(-3.0));
+    assert_eq!(aggregate(Agg::Min), AggValue::Min(Some(-7)));
+    assert_eq!(aggregate(Agg::Max), AggValue::Max(Some(5)));
+
+    assert_eq!(
+      Aggregator::<f32>::new(Agg::Max).finish(),
+      AggValue::Max(None)
+    );
+
+    for agg in [Agg::Count, Agg::Sum, Agg::Min, Agg::Max] {
+      let mut aggregator = Aggregator::new(agg);
+      aggregator.update_value(aggregate(agg));
+      aggregator.update_value(Aggregator::<i32>::new(agg).finish());
+      assert_eq!(aggregator.finish(), aggregate(agg));
+    }
+  }
+
+  #[test]
+  fn test_aggregator_float_order() {
+    let mut aggregator = Aggregator::new(Agg::Min);
+    aggregator.update(&[0.0_f32, -0.0, 1.0]);
+    match aggregator.finish() {
+      AggValue::Min(Some(x)) => assert!(x == 0.0 && x.is_sign_negative()),
+      other => panic!("unexpected {:?}", other),
+    }
+  }
+}
@@ -2,6 +2,7 @@ use crate::chunk_config::ChunkConfig;
 use crate::data_types::NumberLike;
 use crate::errors::PcoResult;
 use crate::wrapped::FileCompressor;
+use crate::{LpcSpec, RunLengthSpec};
 
 /// Automatically makes an educated guess for the best compression
 /// delta encoding order, based on `nums` and `compression_level`.
@@ -16,6 +17,11 @@ pub fn auto_delta_encoding_order<T: NumberLike>(
   let fc = FileCompressor::default();
   let chunk_config = ChunkConfig {
     compression_level,
+    // in run length mode, the delta encoding order would only apply to the
+    // run values, not the numbers themselves
+    run_length_spec: RunLengthSpec::Disabled,
+    // linear prediction mode replaces delta encoding entirely
+    lpc_spec: LpcSpec::Disabled,
     ..Default::default()
   };
   let cc = fc.chunk_compressor(nums, &chunk_config)?;
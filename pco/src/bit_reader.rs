@@ -198,6 +198,7 @@ pub struct BitReaderBuilder<R: BetterBufRead> {
   reached_eof: bool,
   bytes_into_eof_buffer: usize,
   bits_past_byte: Bitlen,
+  bits_consumed: usize,
 }
 
 impl<R: BetterBufRead> BitReaderBuilder<R> {
@@ -209,6 +210,7 @@ impl<R: BetterBufRead> BitReaderBuilder<R> {
       reached_eof: false,
       bytes_into_eof_buffer: 0,
       bits_past_byte,
+      bits_consumed: 0,
     }
   }
 
@@ -250,7 +252,13 @@ impl<R: BetterBufRead> BitReaderBuilder<R> {
     self.inner
   }
 
+  // the count of bits read (or skipped) since this was created
+  pub fn bits_consumed(&self) -> usize {
+    self.bits_consumed
+  }
+
   fn update(&mut self, final_bit_idx: usize) {
+    self.bits_consumed += final_bit_idx - self.bits_past_byte as usize;
     let bytes_consumed = final_bit_idx / 8;
     self.inner.consume(bytes_consumed);
     if self.reached_eof {
@@ -269,6 +277,29 @@ impl<R: BetterBufRead> BitReaderBuilder<R> {
     self.update(final_bit_idx);
     Ok(res)
   }
+
+  pub fn skip_bits(&mut self, n_bits: usize) -> PcoResult<()> {
+    let mut n_remaining = n_bits;
+    while n_remaining > 0 {
+      let n_skipped = self.with_reader(|reader| {
+        let bit_idx = reader.bit_idx();
+        let n_available = reader.unpadded_bit_size.saturating_sub(bit_idx);
+        if n_available == 0 {
+          return Err(PcoError::insufficient_data(format!(
+            "[BitReaderBuilder] unable to skip {} more bits",
+            n_remaining,
+          )));
+        }
+        let n_skipped = min(n_remaining, n_available);
+        let new_bit_idx = bit_idx + n_skipped;
+        reader.stale_byte_idx = new_bit_idx / 8;
+        reader.bits_past_byte = new_bit_idx as Bitlen % 8;
+        Ok(n_skipped)
+      })?;
+      n_remaining -= n_skipped;
+    }
+    Ok(())
+  }
 }
 
 pub fn ensure_buf_read_capacity<R: BetterBufRead>(src: &mut R, required: usize) {
@@ -42,5 +42,6 @@ mod tests {
   fn test_bits_to_encode_offset_bits() {
     assert_eq!(bits_to_encode_offset_bits::<u32>(), 6);
     assert_eq!(bits_to_encode_offset_bits::<u64>(), 7);
+    assert_eq!(bits_to_encode_offset_bits::<u128>(), 8);
   }
 }
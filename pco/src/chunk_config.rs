@@ -1,4 +1,4 @@
-use crate::constants::DEFAULT_MAX_PAGE_N;
+use crate::constants::{DEFAULT_MAX_CHUNK_N, DEFAULT_MAX_PAGE_N, MAX_CHUNK_ENTRIES, MAX_ENTRIES};
 use crate::errors::{PcoError, PcoResult};
 use crate::DEFAULT_COMPRESSION_LEVEL;
 
@@ -24,6 +24,33 @@ pub enum IntMultSpec {
   Provided(u64),
 }
 
+/// Configures whether lagged delta encoding is enabled.
+///
+/// Examples where this helps:
+/// * metering data with a daily period (lag 96 for 15-minute samples)
+/// * interleaved multi-channel data, where the lag is the number of channels
+///
+/// With a lag of `k`, each order of delta encoding takes differences
+/// `x[i] - x[i - k]` instead of `x[i] - x[i - 1]`.
+/// When this is enabled, pco looks for a good lag on a sample of each chunk
+/// and only uses it if it appears to compress better than ordinary delta
+/// encoding.
+/// This configuration may hurt compression speed slightly even when it isn't
+/// helpful.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeltaLagSpec {
+  Disabled,
+  #[default]
+  Enabled,
+  /// If you know your data has a period of `lag`, you can provide it here to
+  /// ensure it gets used and save compression time.
+  /// This may be at most 4096.
+  ///
+  /// If `delta_encoding_order` is `None`, first order delta encoding will be
+  /// used with this lag.
+  Provided(usize),
+}
+
 /// Configures whether float multiplier detection is enabled.
 ///
 /// Examples where this helps:
@@ -43,7 +70,156 @@ pub enum FloatMultSpec {
   /// If you know all your floats are roughly multiples of `base`, you can
   /// provide `base` here to ensure it gets used and save compression time.
   Provided(f64),
-  // TODO support a LossyEnabled mode that always drops the ULPs latent var
+  /// Like `Enabled`, but if a `base` is found, the ULPs adjustment is dropped
+  /// and each float decompresses to exactly its nearest multiple of `base`.
+  /// This is lossy and saves the bits that would have gone to the
+  /// adjustments.
+  /// If no `base` is found, compression falls back to lossless classic mode.
+  LossyEnabled,
+  /// Like `Provided`, but lossy in the same way as `LossyEnabled`.
+  /// Each float decompresses to `base` times the rounded value of
+  /// `x / base`.
+  LossyProvided(f64),
+}
+
+/// Configures whether decimal mode is enabled.
+///
+/// Examples where this helps:
+/// * prices or measurements parsed from decimal strings, e.g. 12.37
+/// * decimals with varying precision, e.g. 0.5, 12.37, and 0.005
+///
+/// Decimal mode finds a power of ten exponent `e` and compresses each float
+/// as the integer `round(x * 10^e)`, which decompresses exactly via
+/// `int / 10^e`.
+/// Exceptions that don't round trip this way get stored as ULPs adjustments.
+/// It is only considered for float data types when `float_mult_spec` is
+/// `Enabled` or `Disabled`, and it is only used instead of float mult mode
+/// if float mult mode doesn't find a coarser base.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DecimalSpec {
+  Disabled,
+  #[default]
+  Enabled,
+}
+
+/// Configures whether dictionary mode is enabled.
+///
+/// Examples where this helps:
+/// * status codes or bucket ids
+/// * prices on a tick grid with few distinct levels
+///
+/// Dictionary mode stores the sorted distinct values of a chunk in its
+/// metadata and compresses each number as an index into them.
+/// It is only considered when the chunk would otherwise use classic mode and
+/// has few distinct values, and it is only used if it appears to compress
+/// better than classic mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DictionarySpec {
+  Disabled,
+  #[default]
+  Enabled,
+}
+
+/// Configures whether run length mode is enabled.
+///
+/// Examples where this helps:
+/// * flags or states that change rarely
+/// * sensor readings that get repeated until they update
+///
+/// Run length mode compresses each run of repeated numbers as a pair of
+/// the number and the run length.
+/// It is only considered when the chunk would otherwise use classic mode and
+/// has long runs on average, and it is only used if it appears to compress
+/// better than classic mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RunLengthSpec {
+  Disabled,
+  #[default]
+  Enabled,
+}
+
+/// Configures whether linear prediction mode is enabled.
+///
+/// Examples where this helps:
+/// * audio and other oscillating signals
+/// * smooth sensor readings with momentum
+///
+/// Linear prediction mode fits a small set of fixed-point predictor
+/// coefficients to the chunk and compresses each number as its residual from
+/// the prediction.
+/// It is only considered when the chunk would otherwise use classic mode and
+/// `delta_encoding_order` is not specified, and it is only used if the fitted
+/// predictor appears substantially better than delta encoding.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LpcSpec {
+  Disabled,
+  #[default]
+  Enabled,
+}
+
+/// Configures whether rare outliers may be stored as exceptions.
+///
+/// Examples where this helps:
+/// * sensor readings with occasional sentinel values like -9999
+/// * IDs or counts with a few `u64::MAX` placeholders
+///
+/// Each exception is stored in its page as a (position, value) pair, and the
+/// rest of the chunk is compressed as if the exception were a copy of the
+/// previous number, so a few extreme values don't widen every bin.
+/// It is only used if the chunk has a small number of outliers and it
+/// appears to compress better than without exceptions.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ExceptionSpec {
+  Disabled,
+  #[default]
+  Enabled,
+}
+
+/// Configures whether chunk metadata stores the exact minimum and maximum of
+/// the chunk's numbers.
+///
+/// Wrapping formats can use these bounds to skip chunks that can't contain
+/// values of interest without decompressing them.
+/// Even when they aren't stored,
+/// [`ChunkMeta::value_bounds`][crate::ChunkMeta::value_bounds] can sometimes
+/// derive bounds from the rest of the metadata.
+/// Storing them costs 2 numbers' worth of space per chunk.
+/// Bounds are not stored when [`LossySpec`] is used, since quantization may
+/// move numbers slightly outside the original range.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ValueBoundsSpec {
+  #[default]
+  Disabled,
+  Enabled,
+}
+
+/// Configures whether floats may be quantized before compression, trading
+/// precision for compression ratio.
+///
+/// Each float is quantized such that it decompresses to a value within the
+/// specified tolerance of the original.
+/// Any float that can't be quantized within tolerance (e.g. NaN) is kept
+/// as-is.
+/// This has no effect on integer data types.
+/// When enabled, lossy float mult specs are treated as their lossless
+/// counterparts so that the tolerance still holds.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LossySpec {
+  /// Compress losslessly.
+  #[default]
+  None,
+  /// Guarantee each float `x` decompresses to `y` such that
+  /// `|y - x| <= tolerance`.
+  ///
+  /// Floats get rounded to the nearest multiple of `2 * tolerance`, so this
+  /// works especially well in combination with float mult mode.
+  AbsError(f64),
+  /// Guarantee each float `x` decompresses to `y` such that
+  /// `|y - x| <= tolerance * |x|`.
+  ///
+  /// Floats get their mantissas rounded to the fewest bits that satisfy the
+  /// tolerance.
+  RelError(f64),
 }
 
 /// All configurations available for a compressor.
@@ -90,6 +266,13 @@ pub struct ChunkConfig {
   /// chunks,
   /// [`auto_delta_encoding_order`][crate::auto_delta_encoding_order] can help.
   pub delta_encoding_order: Option<usize>,
+  /// Lagged delta encoding improves compression ratio in cases where the
+  /// numbers are periodic, so each number is closest to the one `lag`
+  /// numbers before it
+  /// (default: `Enabled`).
+  ///
+  /// See [`DeltaLagSpec`][crate::DeltaLagSpec] for more detail.
+  pub delta_lag_spec: DeltaLagSpec,
   /// Integer multiplier mode improves compression ratio in cases where many
   /// numbers are congruent modulo an integer `base`
   /// (default: `Enabled`).
@@ -103,11 +286,76 @@ pub struct ChunkConfig {
   ///
   /// See [`FloatMultSpec`][crate::FloatMultSpec] for more detail.
   pub float_mult_spec: FloatMultSpec,
+  /// Decimal mode improves compression ratio in cases where the data type
+  /// is a float and most numbers are exactly decimals with a few digits
+  /// (default: `Enabled`).
+  ///
+  /// See [`DecimalSpec`][crate::DecimalSpec] for more detail.
+  pub decimal_spec: DecimalSpec,
+  /// `lossy_spec` allows floats to be quantized to within an absolute or
+  /// relative error tolerance before compression
+  /// (default: `None`, compressing losslessly).
+  ///
+  /// See [`LossySpec`][crate::LossySpec] for more detail.
+  pub lossy_spec: LossySpec,
+  /// Dictionary mode improves compression ratio in cases where a chunk has
+  /// few distinct values spread over a wide range
+  /// (default: `Enabled`).
+  ///
+  /// See [`DictionarySpec`][crate::DictionarySpec] for more detail.
+  pub dictionary_spec: DictionarySpec,
+  /// Run length mode improves compression ratio in cases where a chunk has
+  /// long runs of repeated numbers
+  /// (default: `Enabled`).
+  ///
+  /// See [`RunLengthSpec`][crate::RunLengthSpec] for more detail.
+  pub run_length_spec: RunLengthSpec,
+  /// Linear prediction mode improves compression ratio in cases where each
+  /// number is well predicted by a linear combination of the previous few
+  /// (default: `Enabled`).
+  ///
+  /// See [`LpcSpec`][crate::LpcSpec] for more detail.
+  pub lpc_spec: LpcSpec,
+  /// Exceptions improve compression ratio in cases where a few extreme
+  /// outliers would otherwise inflate the bins for the whole chunk
+  /// (default: `Enabled`).
+  ///
+  /// See [`ExceptionSpec`][crate::ExceptionSpec] for more detail.
+  pub exception_spec: ExceptionSpec,
+  /// `seek_index_interval` adds a seek index to each page with an entry
+  /// every this many batches, letting decompressors skip ahead within a page
+  /// without decoding the batches in between
+  /// (default: `None`, no seek index).
+  ///
+  /// Each entry stores the decoder state at its batch, so smaller intervals
+  /// allow finer seeking but take more space.
+  /// This ranges from 1 to 2^16 inclusive and has no effect in run length
+  /// mode.
+  /// The seek index is not counted by the size bounds in
+  /// [`wrapped::guarantee`][crate::wrapped::guarantee].
+  pub seek_index_interval: Option<usize>,
+  /// `value_bounds_spec` stores the chunk's minimum and maximum number in
+  /// its metadata (default: `Disabled`).
+  ///
+  /// See [`ValueBoundsSpec`][crate::ValueBoundsSpec] for more detail.
+  pub value_bounds_spec: ValueBoundsSpec,
   /// `paging_spec` specifies how the chunk should be split into pages
   /// (default: equal pages up to 2^18 numbers each).
   ///
+  /// In the standalone format, each page but the last in a chunk must hold a
+  /// multiple of 256 numbers, so `EqualPagesUpTo` rounds page sizes to whole
+  /// batches there.
   /// See [`PagingSpec`][crate::PagingSpec] for more information.
   pub paging_spec: PagingSpec,
+  /// `chunking_spec` specifies how standalone functions like
+  /// [`simple_compress`][crate::standalone::simple_compress] split numbers
+  /// into chunks (default: equal chunks up to 2^18 numbers each).
+  ///
+  /// Every chunk pays for its own metadata, so larger chunks with several
+  /// pages each can compress better when the numbers follow one
+  /// distribution.
+  /// This has no effect when compressing one chunk at a time.
+  pub chunking_spec: PagingSpec,
 }
 
 impl Default for ChunkConfig {
@@ -115,9 +363,19 @@ impl Default for ChunkConfig {
     Self {
       compression_level: DEFAULT_COMPRESSION_LEVEL,
       delta_encoding_order: None,
+      delta_lag_spec: DeltaLagSpec::Enabled,
       int_mult_spec: IntMultSpec::Enabled,
       float_mult_spec: FloatMultSpec::Enabled,
+      decimal_spec: DecimalSpec::Enabled,
+      lossy_spec: LossySpec::None,
+      dictionary_spec: DictionarySpec::Enabled,
+      run_length_spec: RunLengthSpec::Enabled,
+      lpc_spec: LpcSpec::Enabled,
+      exception_spec: ExceptionSpec::Enabled,
+      seek_index_interval: None,
+      value_bounds_spec: ValueBoundsSpec::Disabled,
       paging_spec: PagingSpec::EqualPagesUpTo(DEFAULT_MAX_PAGE_N),
+      chunking_spec: PagingSpec::EqualPagesUpTo(DEFAULT_MAX_CHUNK_N),
     }
   }
 }
@@ -135,6 +393,12 @@ impl ChunkConfig {
     self
   }
 
+  /// Sets [`delta_lag_spec`][ChunkConfig::delta_lag_spec].
+  pub fn with_delta_lag_spec(mut self, delta_lag_spec: DeltaLagSpec) -> Self {
+    self.delta_lag_spec = delta_lag_spec;
+    self
+  }
+
   /// Sets [`int_mult_spec`][ChunkConfig::int_mult_spec].
   pub fn with_int_mult_spec(mut self, int_mult_spec: IntMultSpec) -> Self {
     self.int_mult_spec = int_mult_spec;
@@ -147,14 +411,70 @@ impl ChunkConfig {
     self
   }
 
+  /// Sets [`decimal_spec`][ChunkConfig::decimal_spec].
+  pub fn with_decimal_spec(mut self, decimal_spec: DecimalSpec) -> Self {
+    self.decimal_spec = decimal_spec;
+    self
+  }
+
+  /// Sets [`lossy_spec`][ChunkConfig::lossy_spec].
+  pub fn with_lossy_spec(mut self, lossy_spec: LossySpec) -> Self {
+    self.lossy_spec = lossy_spec;
+    self
+  }
+
+  /// Sets [`dictionary_spec`][ChunkConfig::dictionary_spec].
+  pub fn with_dictionary_spec(mut self, dictionary_spec: DictionarySpec) -> Self {
+    self.dictionary_spec = dictionary_spec;
+    self
+  }
+
+  /// Sets [`run_length_spec`][ChunkConfig::run_length_spec].
+  pub fn with_run_length_spec(mut self, run_length_spec: RunLengthSpec) -> Self {
+    self.run_length_spec = run_length_spec;
+    self
+  }
+
+  /// Sets [`lpc_spec`][ChunkConfig::lpc_spec].
+  pub fn with_lpc_spec(mut self, lpc_spec: LpcSpec) -> Self {
+    self.lpc_spec = lpc_spec;
+    self
+  }
+
+  /// Sets [`exception_spec`][ChunkConfig::exception_spec].
+  pub fn with_exception_spec(mut self, exception_spec: ExceptionSpec) -> Self {
+    self.exception_spec = exception_spec;
+    self
+  }
+
+  /// Sets [`seek_index_interval`][ChunkConfig::seek_index_interval].
+  pub fn with_seek_index_interval(mut self, interval: Option<usize>) -> Self {
+    self.seek_index_interval = interval;
+    self
+  }
+
+  /// Sets [`value_bounds_spec`][ChunkConfig::value_bounds_spec].
+  pub fn with_value_bounds_spec(mut self, value_bounds_spec: ValueBoundsSpec) -> Self {
+    self.value_bounds_spec = value_bounds_spec;
+    self
+  }
+
   /// Sets [`paging_spec`][ChunkConfig::paging_spec].
   pub fn with_paging_spec(mut self, paging_spec: PagingSpec) -> Self {
     self.paging_spec = paging_spec;
     self
   }
+
+  /// Sets [`chunking_spec`][ChunkConfig::chunking_spec].
+  pub fn with_chunking_spec(mut self, chunking_spec: PagingSpec) -> Self {
+    self.chunking_spec = chunking_spec;
+    self
+  }
 }
 
-/// `PagingSpec` specifies how a chunk is split into pages.
+/// `PagingSpec` specifies how a chunk is split into pages, or, as a
+/// [`chunking_spec`][ChunkConfig::chunking_spec], how numbers are split into
+/// chunks.
 #[derive(Clone, Debug)]
 #[non_exhaustive]
 pub enum PagingSpec {
@@ -178,7 +498,15 @@ impl Default for PagingSpec {
 
 impl PagingSpec {
   pub(crate) fn n_per_page(&self, n: usize) -> PcoResult<Vec<usize>> {
-    let n_per_page = match self {
+    self.split(n, MAX_ENTRIES, "data page")
+  }
+
+  pub(crate) fn n_per_chunk(&self, n: usize) -> PcoResult<Vec<usize>> {
+    self.split(n, MAX_CHUNK_ENTRIES, "chunk")
+  }
+
+  fn split(&self, n: usize, max_part_n: usize, part_name: &str) -> PcoResult<Vec<usize>> {
+    let n_per_part = match self {
       // You might think it would be beneficial to do either of these:
       // * greedily fill pages since compressed chunk size seems like a concave
       //   function of chunk_n
@@ -199,10 +527,10 @@ impl PagingSpec {
         }
         res
       }
-      PagingSpec::Exact(n_per_page) => n_per_page.to_vec(),
+      PagingSpec::Exact(n_per_part) => n_per_part.to_vec(),
     };
 
-    let summed_n: usize = n_per_page.iter().sum();
+    let summed_n: usize = n_per_part.iter().sum();
     if summed_n != n {
       return Err(PcoError::invalid_argument(format!(
         "paging spec suggests {} numbers but {} were given",
@@ -210,14 +538,21 @@ impl PagingSpec {
       )));
     }
 
-    for &page_n in &n_per_page {
-      if page_n == 0 {
-        return Err(PcoError::invalid_argument(
-          "cannot write data page of 0 numbers",
-        ));
+    for &part_n in &n_per_part {
+      if part_n == 0 {
+        return Err(PcoError::invalid_argument(format!(
+          "cannot write {} of 0 numbers",
+          part_name,
+        )));
+      }
+      if part_n > max_part_n {
+        return Err(PcoError::invalid_argument(format!(
+          "count may not exceed {} per {} (was {})",
+          max_part_n, part_name, part_n,
+        )));
       }
     }
 
-    Ok(n_per_page)
+    Ok(n_per_part)
   }
 }
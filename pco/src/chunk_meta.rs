@@ -8,7 +8,7 @@ use crate::bit_reader::BitReaderBuilder;
 use crate::bit_writer::BitWriter;
 use crate::bits::bits_to_encode_offset_bits;
 use crate::constants::*;
-use crate::data_types::Latent;
+use crate::data_types::{Latent, NumberLike};
 use crate::errors::{PcoError, PcoResult};
 use crate::format_version::FormatVersion;
 use crate::Mode;
@@ -157,6 +157,21 @@ impl<L: Latent> ChunkLatentVarMeta<L> {
   }
 }
 
+/// A range of numbers and the approximate fraction of a chunk's numbers
+/// within it, as estimated by
+/// [`ChunkMeta::approx_histogram`][crate::ChunkMeta::approx_histogram].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct HistogramBin<T> {
+  /// The lower bound (inclusive) of the range.
+  pub lower: T,
+  /// The upper bound (inclusive) of the range.
+  pub upper: T,
+  /// The estimated fraction of the chunk's numbers in the range, between 0
+  /// and 1.
+  pub fraction: f64,
+}
+
 /// The metadata of a pco chunk.
 #[derive(Clone, Debug, PartialEq)]
 #[non_exhaustive]
@@ -168,6 +183,39 @@ pub struct ChunkMeta<L: Latent> {
   ///
   /// See [`ChunkConfig`][crate::ChunkConfig] for more details.
   pub delta_encoding_order: usize,
+  /// How far apart (in numbers) the latents are that delta encoding takes
+  /// differences between.
+  /// This is 1 for ordinary delta encoding, and always 1 when
+  /// `delta_encoding_order` is 0.
+  ///
+  /// See [`DeltaLagSpec`][crate::DeltaLagSpec] for more details.
+  pub delta_lag: usize,
+  /// The sorted, distinct latents that the primary latent variable indexes
+  /// into when `mode` is [`Dictionary`][crate::Mode::Dictionary].
+  ///
+  /// This is empty for all other modes.
+  pub dictionary: Vec<L>,
+  /// The fixed-point coefficients of the linear predictor when `mode` is
+  /// [`Lpc`][crate::Mode::Lpc], each with 12 fractional bits.
+  /// The coefficient at index `m` multiplies the first difference `m + 1`
+  /// steps back.
+  ///
+  /// This is empty for all other modes.
+  pub lpc_coefficients: Vec<i16>,
+  /// Whether each page stores exceptions: outliers that are kept out of the
+  /// latent variables and patched in as (position, value) pairs after
+  /// decompression.
+  pub has_exceptions: bool,
+  /// The count of batches between entries of each page's seek index, if
+  /// pages have one.
+  ///
+  /// See [`ChunkConfig`][crate::ChunkConfig] for more details.
+  pub seek_index_interval: Option<usize>,
+  /// The exact minimum and maximum latents of the chunk's numbers, if
+  /// stored.
+  ///
+  /// See [`ValueBoundsSpec`][crate::ValueBoundsSpec] for more details.
+  pub stored_value_bounds: Option<(L, L)>,
   /// Metadata about the interleaved streams needed by `pco` to
   /// compress/decompress the inputs
   /// according to the formula used by `mode`.
@@ -192,15 +240,122 @@ unsafe fn write_bins<L: Latent, W: Write>(
   Ok(())
 }
 
+unsafe fn parse_dictionary<L: Latent, R: BetterBufRead>(
+  reader_builder: &mut BitReaderBuilder<R>,
+) -> PcoResult<Vec<L>> {
+  let size =
+    reader_builder.with_reader(|reader| Ok(reader.read_usize(BITS_TO_ENCODE_DICTIONARY_SIZE)))?;
+  if size == 0 || size > MAX_DICTIONARY_SIZE {
+    return Err(PcoError::corruption(format!(
+      "dictionary size ({}) should be between 1 and {}",
+      size, MAX_DICTIONARY_SIZE,
+    )));
+  }
+
+  let mut dictionary = Vec::with_capacity(size);
+  while dictionary.len() < size {
+    let batch_size = min(size - dictionary.len(), FULL_BIN_BATCH_SIZE);
+    reader_builder.with_reader(|reader| {
+      for _ in 0..batch_size {
+        dictionary.push(reader.read_uint::<L>(L::BITS));
+      }
+      Ok(())
+    })?;
+  }
+
+  if dictionary.windows(2).any(|pair| pair[0] >= pair[1]) {
+    return Err(PcoError::corruption(
+      "dictionary entries should be strictly increasing",
+    ));
+  }
+
+  Ok(dictionary)
+}
+
+unsafe fn write_dictionary<L: Latent, W: Write>(
+  dictionary: &[L],
+  writer: &mut BitWriter<W>,
+) -> PcoResult<()> {
+  writer.write_usize(
+    dictionary.len(),
+    BITS_TO_ENCODE_DICTIONARY_SIZE,
+  );
+  for entry_batch in dictionary.chunks(FULL_BIN_BATCH_SIZE) {
+    for &entry in entry_batch {
+      writer.write_uint(entry, L::BITS);
+    }
+    writer.flush()?;
+  }
+  Ok(())
+}
+
+unsafe fn parse_value_bounds<L: Latent, R: BetterBufRead>(
+  reader_builder: &mut BitReaderBuilder<R>,
+) -> PcoResult<Option<(L, L)>> {
+  let bounds = reader_builder.with_reader(|reader| {
+    if reader.read_usize(1) == 0 {
+      return Ok(None);
+    }
+
+    let lower = reader.read_uint::<L>(L::BITS);
+    let upper = reader.read_uint::<L>(L::BITS);
+    Ok(Some((lower, upper)))
+  })?;
+
+  if let Some((lower, upper)) = bounds {
+    if lower > upper {
+      return Err(PcoError::corruption(format!(
+        "value bounds should be ordered but were {} and {}",
+        lower, upper,
+      )));
+    }
+  }
+
+  Ok(bounds)
+}
+
+unsafe fn write_value_bounds<L: Latent, W: Write>(
+  bounds: Option<(L, L)>,
+  writer: &mut BitWriter<W>,
+) -> PcoResult<()> {
+  writer.write_usize(bounds.is_some() as usize, 1);
+  if let Some((lower, upper)) = bounds {
+    writer.write_uint(lower, L::BITS);
+    writer.write_uint(upper, L::BITS);
+  }
+  writer.flush()
+}
+
+// the largest latent a bin can decode to, saturating instead of wrapping
+fn bin_upper<L: Latent>(bin: &Bin<L>) -> L {
+  let max_offset = if bin.offset_bits >= L::BITS {
+    L::MAX
+  } else {
+    (L::ONE << bin.offset_bits) - L::ONE
+  };
+  if bin.lower > L::MAX - max_offset {
+    L::MAX
+  } else {
+    bin.lower + max_offset
+  }
+}
+
 impl<L: Latent> ChunkMeta<L> {
   pub(crate) fn new(
     mode: Mode<L>,
     delta_encoding_order: usize,
+    delta_lag: usize,
     per_latent_var: Vec<ChunkLatentVarMeta<L>>,
   ) -> Self {
     ChunkMeta {
       mode,
       delta_encoding_order,
+      delta_lag,
+      dictionary: Vec::new(),
+      lpc_coefficients: Vec::new(),
+      has_exceptions: false,
+      seek_index_interval: None,
+      stored_value_bounds: None,
       per_latent_var,
     }
   }
@@ -210,6 +365,34 @@ impl<L: Latent> ChunkMeta<L> {
       Mode::Classic => 0,
       Mode::IntMult(_) => L::BITS,
       Mode::FloatMult(_) => L::BITS,
+      Mode::LossyFloatMult(_) => L::BITS,
+      Mode::Dictionary => 0,
+      Mode::RunLength => 0,
+      Mode::Decimal(_) => BITS_TO_ENCODE_DECIMAL_EXPONENT,
+      Mode::Lpc => {
+        BITS_TO_ENCODE_LPC_ORDER + self.lpc_coefficients.len() as Bitlen * LPC_COEFFICIENT_BITS
+      }
+    };
+    let bits_for_delta_lag = if self.delta_encoding_order > 0 {
+      BITS_TO_ENCODE_DELTA_LAG
+    } else {
+      0
+    };
+    let bits_for_seek_index_interval = if self.seek_index_interval.is_some() {
+      BITS_TO_ENCODE_SEEK_INTERVAL
+    } else {
+      0
+    };
+    let bits_for_value_bounds = if self.stored_value_bounds.is_some() {
+      2 * L::BITS
+    } else {
+      0
+    };
+    let bits_for_dictionary = match self.mode {
+      Mode::Dictionary => {
+        BITS_TO_ENCODE_DICTIONARY_SIZE as usize + self.dictionary.len() * L::BITS as usize
+      }
+      _ => 0,
     };
     let bits_for_latent_vars: usize = self
       .per_latent_var
@@ -219,56 +402,150 @@ impl<L: Latent> ChunkMeta<L> {
     let n_bits = BITS_TO_ENCODE_MODE as usize
       + extra_bits_for_mode as usize
       + BITS_TO_ENCODE_DELTA_ENCODING_ORDER as usize
+      + bits_for_delta_lag as usize
+      + 1 // has_exceptions
+      + 1 // has seek index
+      + bits_for_seek_index_interval as usize
+      + 1 // has value bounds
+      + bits_for_value_bounds as usize
+      + bits_for_dictionary
       + bits_for_latent_vars;
     n_bits.div_ceil(8)
   }
 
-  pub(crate) fn exact_page_meta_size(&self) -> usize {
+  pub(crate) fn exact_page_meta_size(&self, n_exceptions: usize, n_latents: usize) -> usize {
     let bit_size: usize = self
       .per_latent_var
       .iter()
       .enumerate()
       .map(|(latent_var_idx, latent_var)| {
-        let delta_order = self
-          .mode
-          .delta_order_for_latent_var(latent_var_idx, self.delta_encoding_order);
-        latent_var.ans_size_log as usize * ANS_INTERLEAVING + L::BITS as usize * delta_order
+        latent_var.ans_size_log as usize * ANS_INTERLEAVING
+          + L::BITS as usize * self.n_delta_moments_for_latent_var(latent_var_idx)
       })
       .sum();
-    bit_size.div_ceil(8)
+    let n_runs_bit_size = match self.mode {
+      Mode::RunLength => BITS_TO_ENCODE_N_RUNS as usize,
+      _ => 0,
+    };
+    let exceptions_bit_size = if self.has_exceptions {
+      BITS_TO_ENCODE_N_EXCEPTIONS as usize
+        + n_exceptions * (BITS_TO_ENCODE_EXCEPTION_IDX + L::BITS) as usize
+    } else {
+      0
+    };
+    let seek_index_bit_size =
+      self.n_seek_points(n_latents) * (BITS_TO_ENCODE_SEEK_BIT_IDX as usize + bit_size);
+    (n_runs_bit_size + exceptions_bit_size + bit_size + seek_index_bit_size).div_ceil(8)
+  }
+
+  // the count of seek index entries in a page with this many latents; there
+  // is never an entry for the first batch since it starts at the page body
+  pub(crate) fn n_seek_points(&self, n_latents: usize) -> usize {
+    match self.seek_index_interval {
+      Some(interval) => n_latents.div_ceil(FULL_BATCH_N).saturating_sub(1) / interval,
+      None => 0,
+    }
   }
 
   pub(crate) unsafe fn parse_from<R: BetterBufRead>(
     reader_builder: &mut BitReaderBuilder<R>,
     version: &FormatVersion,
   ) -> PcoResult<Self> {
-    let (mode, delta_encoding_order) = reader_builder.with_reader(|reader| {
-      let mode = match reader.read_usize(BITS_TO_ENCODE_MODE) {
-        0 => Ok(Mode::Classic),
-        1 => {
-          if version.used_old_gcds() {
-            return Err(PcoError::compatibility(
-              "unable to decompress data from v0.0.0 of pco with different GCD encoding",
-            ));
+    let mut lpc_coefficients = Vec::new();
+    let (mode, delta_encoding_order, delta_lag, has_exceptions, seek_index_interval) =
+      reader_builder.with_reader(|reader| {
+        let mode = match reader.read_usize(BITS_TO_ENCODE_MODE) {
+          0 => Ok(Mode::Classic),
+          1 => {
+            if version.used_old_gcds() {
+              return Err(PcoError::compatibility(
+                "unable to decompress data from v0.0.0 of pco with different GCD encoding",
+              ));
+            }
+
+            let base = reader.read_uint::<L>(L::BITS);
+            Ok(Mode::IntMult(base))
+          }
+          2 => {
+            let base_latent = reader.read_uint::<L>(L::BITS);
+            Ok(Mode::FloatMult(base_latent))
+          }
+          3 if version.supports_lossy_float_mult() => {
+            let base_latent = reader.read_uint::<L>(L::BITS);
+            Ok(Mode::LossyFloatMult(base_latent))
+          }
+          4 if version.supports_dictionary() => Ok(Mode::Dictionary),
+          5 if version.supports_run_length() => Ok(Mode::RunLength),
+          6 if version.supports_lpc() => {
+            let order = reader.read_usize(BITS_TO_ENCODE_LPC_ORDER) + 1;
+            for _ in 0..order {
+              let coefficient = reader.read_usize(LPC_COEFFICIENT_BITS) as u16 as i16;
+              lpc_coefficients.push(coefficient);
+            }
+            Ok(Mode::Lpc)
           }
+          7 if version.supports_decimal() => {
+            let exponent = reader.read_bitlen(BITS_TO_ENCODE_DECIMAL_EXPONENT);
+            if exponent > MAX_DECIMAL_EXPONENT {
+              return Err(PcoError::corruption(format!(
+                "decimal exponent {} exceeds max of {}",
+                exponent, MAX_DECIMAL_EXPONENT,
+              )));
+            }
+            Ok(Mode::Decimal(exponent))
+          }
+          value => Err(PcoError::corruption(format!(
+            "unknown mode value {}",
+            value
+          ))),
+        }?;
 
-          let base = reader.read_uint::<L>(L::BITS);
-          Ok(Mode::IntMult(base))
-        }
-        2 => {
-          let base_latent = reader.read_uint::<L>(L::BITS);
-          Ok(Mode::FloatMult(base_latent))
-        }
-        value => Err(PcoError::corruption(format!(
-          "unknown mode value {}",
-          value
-        ))),
-      }?;
+        let delta_encoding_order = reader.read_usize(BITS_TO_ENCODE_DELTA_ENCODING_ORDER);
+        let delta_lag = if delta_encoding_order > 0 && version.supports_delta_lag() {
+          reader.read_usize(BITS_TO_ENCODE_DELTA_LAG) + 1
+        } else {
+          1
+        };
 
-      let delta_encoding_order = reader.read_usize(BITS_TO_ENCODE_DELTA_ENCODING_ORDER);
+        let has_exceptions = version.supports_exceptions() && reader.read_usize(1) == 1;
+        let seek_index_interval = if version.supports_seek_index() && reader.read_usize(1) == 1 {
+          Some(reader.read_usize(BITS_TO_ENCODE_SEEK_INTERVAL) + 1)
+        } else {
+          None
+        };
 
-      Ok((mode, delta_encoding_order))
-    })?;
+        Ok((
+          mode,
+          delta_encoding_order,
+          delta_lag,
+          has_exceptions,
+          seek_index_interval,
+        ))
+      })?;
+
+    if matches!(mode, Mode::Lpc) && delta_encoding_order > 0 {
+      return Err(PcoError::corruption(format!(
+        "linear prediction mode should not have delta encoding order {}",
+        delta_encoding_order,
+      )));
+    }
+
+    if matches!(mode, Mode::RunLength) && seek_index_interval.is_some() {
+      return Err(PcoError::corruption(
+        "run length mode should not have a seek index",
+      ));
+    }
+
+    let stored_value_bounds = if version.supports_value_bounds() {
+      parse_value_bounds(reader_builder)?
+    } else {
+      None
+    };
+
+    let dictionary = match mode {
+      Mode::Dictionary if version.supports_dictionary() => parse_dictionary(reader_builder)?,
+      _ => Vec::new(),
+    };
 
     let n_latent_vars = mode.n_latent_vars();
 
@@ -287,6 +564,12 @@ impl<L: Latent> ChunkMeta<L> {
     Ok(Self {
       mode,
       delta_encoding_order,
+      delta_lag,
+      dictionary,
+      lpc_coefficients,
+      has_exceptions,
+      seek_index_interval,
+      stored_value_bounds,
       per_latent_var,
     })
   }
@@ -296,24 +579,61 @@ impl<L: Latent> ChunkMeta<L> {
       Mode::Classic => 0,
       Mode::IntMult(_) => 1,
       Mode::FloatMult { .. } => 2,
+      Mode::LossyFloatMult(_) => 3,
+      Mode::Dictionary => 4,
+      Mode::RunLength => 5,
+      Mode::Lpc => 6,
+      Mode::Decimal(_) => 7,
     };
     writer.write_usize(mode_value, BITS_TO_ENCODE_MODE);
     match self.mode {
-      Mode::Classic => (),
+      Mode::Classic | Mode::Dictionary | Mode::RunLength => (),
       Mode::IntMult(base) => {
         writer.write_uint(base, L::BITS);
       }
-      Mode::FloatMult(base_latent) => {
+      Mode::FloatMult(base_latent) | Mode::LossyFloatMult(base_latent) => {
         writer.write_uint(base_latent, L::BITS);
       }
+      Mode::Decimal(exponent) => {
+        writer.write_bitlen(exponent, BITS_TO_ENCODE_DECIMAL_EXPONENT);
+      }
+      Mode::Lpc => {
+        writer.write_usize(
+          self.lpc_coefficients.len() - 1,
+          BITS_TO_ENCODE_LPC_ORDER,
+        );
+        for &coefficient in &self.lpc_coefficients {
+          writer.write_usize(
+            coefficient as u16 as usize,
+            LPC_COEFFICIENT_BITS,
+          );
+        }
+      }
     };
 
     writer.write_usize(
       self.delta_encoding_order,
       BITS_TO_ENCODE_DELTA_ENCODING_ORDER,
     );
+    if self.delta_encoding_order > 0 {
+      writer.write_usize(self.delta_lag - 1, BITS_TO_ENCODE_DELTA_LAG);
+    }
+    writer.write_usize(self.has_exceptions as usize, 1);
+    writer.write_usize(
+      self.seek_index_interval.is_some() as usize,
+      1,
+    );
+    if let Some(interval) = self.seek_index_interval {
+      writer.write_usize(interval - 1, BITS_TO_ENCODE_SEEK_INTERVAL);
+    }
     writer.flush()?;
 
+    write_value_bounds(self.stored_value_bounds, writer)?;
+
+    if let Mode::Dictionary = self.mode {
+      write_dictionary(&self.dictionary, writer)?;
+    }
+
     for latents in &self.per_latent_var {
       latents.write_to(writer)?;
     }
@@ -328,12 +648,181 @@ impl<L: Latent> ChunkMeta<L> {
       .mode
       .delta_order_for_latent_var(latent_idx, self.delta_encoding_order)
   }
+
+  pub(crate) fn n_delta_moments_for_latent_var(&self, latent_idx: usize) -> usize {
+    match (self.mode, latent_idx) {
+      // each page starts with enough latents to make the first prediction
+      (Mode::Lpc, 0) => self.lpc_coefficients.len() + 1,
+      _ => self.delta_order_for_latent_var(latent_idx) * self.delta_lag,
+    }
+  }
+
+  // Without delta encoding or exceptions, every classic latent lies in a
+  // primary bin and every dictionary latent is a dictionary entry.
+  // Other modes combine latent variables in ways that don't bound cheaply.
+  fn derived_value_bounds(&self) -> Option<(L, L)> {
+    if self.delta_encoding_order > 0 || self.has_exceptions {
+      return None;
+    }
+
+    match self.mode {
+      Mode::Classic => {
+        let bins = &self.per_latent_var.first()?.bins;
+        let lower = bins.iter().map(|bin| bin.lower).min()?;
+        let upper = bins.iter().map(bin_upper).max()?;
+        Some((lower, upper))
+      }
+      Mode::Dictionary => Some((
+        *self.dictionary.first()?,
+        *self.dictionary.last()?,
+      )),
+      _ => None,
+    }
+  }
+
+  // The primary latent variable's bin ranges and their estimated fractions
+  // of the chunk, sorted by lower bound.
+  // These only describe the distribution of numbers when each number is
+  // determined by a single primary latent.
+  fn approx_primary_bins(&self) -> Option<Vec<(L, L, f64)>> {
+    if self.delta_encoding_order > 0
+      || self.has_exceptions
+      || !matches!(self.mode, Mode::Classic | Mode::Dictionary)
+    {
+      return None;
+    }
+
+    let bins = &self.per_latent_var.first()?.bins;
+    let total_weight = bins.iter().map(|bin| bin.weight as f64).sum::<f64>();
+    if total_weight == 0.0 {
+      return None;
+    }
+
+    let mut res = bins
+      .iter()
+      .map(|bin| {
+        (
+          bin.lower,
+          bin_upper(bin),
+          bin.weight as f64 / total_weight,
+        )
+      })
+      .collect::<Vec<_>>();
+    res.sort_by_key(|&(lower, _, _)| lower);
+    Some(res)
+  }
+
+  fn primary_to_latent(&self, primary: L) -> L {
+    match self.mode {
+      Mode::Dictionary => {
+        let idx = min(
+          primary.to_u64(),
+          self.dictionary.len() as u64 - 1,
+        );
+        self.dictionary[idx as usize]
+      }
+      _ => primary,
+    }
+  }
+
+  /// Returns an approximate histogram of the chunk's numbers, estimated
+  /// from its bins without decompressing any pages.
+  ///
+  /// This is only possible for classic and dictionary mode chunks without
+  /// delta encoding or exceptions, and returns `None` for others.
+  ///
+  /// Each histogram bin's fraction is its tANS weight divided by the table
+  /// size `2^ans_size_log`.
+  /// Compression derives these weights from the exact count of numbers in
+  /// each bin, but quantizes them so that every bin gets a weight of at
+  /// least 1.
+  /// As a result, summed over all bins, the absolute errors of the fractions
+  /// are at most `3 * n_bins / 2^ans_size_log`.
+  /// Histogram bins are sorted, but may be wider than the numbers they
+  /// contain.
+  pub fn approx_histogram<T: NumberLike<L = L>>(&self) -> Option<Vec<HistogramBin<T>>> {
+    let res = self
+      .approx_primary_bins()?
+      .into_iter()
+      .map(|(lower, upper, fraction)| HistogramBin {
+        lower: T::from_latent_ordered(self.primary_to_latent(lower)),
+        upper: T::from_latent_ordered(self.primary_to_latent(upper)),
+        fraction,
+      })
+      .collect();
+    Some(res)
+  }
+
+  /// Returns an approximate `q`-quantile of the chunk's numbers, estimated
+  /// from its bins without decompressing any pages.
+  ///
+  /// This is possible for the same chunks as
+  /// [`approx_histogram`][ChunkMeta::approx_histogram], and returns `None`
+  /// for others.
+  /// Numbers are assumed to be spread evenly within each bin, so the true
+  /// fraction of numbers up to the returned one may differ from `q` by the
+  /// histogram's error bound plus the fraction in the returned number's bin.
+  ///
+  /// Will return an invalid argument error if `q` is not between 0 and 1.
+  pub fn approx_quantile<T: NumberLike<L = L>>(&self, q: f64) -> PcoResult<Option<T>> {
+    if !(0.0..=1.0).contains(&q) {
+      return Err(PcoError::invalid_argument(format!(
+        "quantile must be between 0 and 1 (was {})",
+        q
+      )));
+    }
+
+    let Some(bins) = self.approx_primary_bins() else {
+      return Ok(None);
+    };
+    let mut remaining = q;
+    for (bin_idx, &(lower, upper, fraction)) in bins.iter().enumerate() {
+      if remaining > fraction && bin_idx < bins.len() - 1 {
+        remaining -= fraction;
+        continue;
+      }
+
+      let within = if fraction > 0.0 {
+        (remaining / fraction).min(1.0)
+      } else {
+        0.0
+      };
+      let span = (upper - lower).to_u64();
+      let offset = min((within * span as f64).round() as u64, span);
+      let primary = lower + L::from_u64(offset);
+      return Ok(Some(T::from_latent_ordered(
+        self.primary_to_latent(primary),
+      )));
+    }
+    unreachable!("approx primary bins should be nonempty")
+  }
+
+  /// Returns a lower and upper bound (inclusive) on the chunk's numbers, if
+  /// known.
+  ///
+  /// If the chunk stored its value bounds, these are its exact minimum and
+  /// maximum.
+  /// Otherwise, this tries to derive looser bounds from the rest of the
+  /// metadata, which is only possible for some modes and returns `None` for
+  /// the others.
+  /// Bounds follow the order of `T`'s latents, so e.g. for floats, negative
+  /// NaNs precede -inf and positive NaNs follow +inf.
+  pub fn value_bounds<T: NumberLike<L = L>>(&self) -> Option<(T, T)> {
+    let (lower, upper) = self
+      .stored_value_bounds
+      .or_else(|| self.derived_value_bounds())?;
+    Some((
+      T::from_latent_ordered(lower),
+      T::from_latent_ordered(upper),
+    ))
+  }
 }
 
 #[cfg(test)]
 mod tests {
   use crate::delta::DeltaMoments;
-  use crate::page_meta::{PageLatentVarMeta, PageMeta};
+  use crate::exception_utils::Exception;
+  use crate::page_meta::{PageLatentVarMeta, PageMeta, SeekPoint};
 
   use super::*;
 
@@ -346,20 +835,41 @@ mod tests {
     assert_eq!(meta.exact_size(), dst.len());
 
     // page meta size
+    let n_latents = 1000;
+    let per_var = (0..meta.per_latent_var.len())
+      .map(|latent_var_idx| PageLatentVarMeta {
+        delta_moments: DeltaMoments {
+          moments: vec![L::ZERO; meta.n_delta_moments_for_latent_var(latent_var_idx)],
+        },
+        ans_final_state_idxs: [0; ANS_INTERLEAVING],
+      })
+      .collect::<Vec<_>>();
     let mut dst = Vec::new();
     let mut writer = BitWriter::new(&mut dst, buffer_size);
     let page_meta = PageMeta {
-      per_var: (0..meta.per_latent_var.len())
-        .map(|latent_var_idx| {
-          let delta_order = meta
-            .mode
-            .delta_order_for_latent_var(latent_var_idx, meta.delta_encoding_order);
-          PageLatentVarMeta {
-            delta_moments: DeltaMoments {
-              moments: vec![L::ZERO; delta_order],
-            },
-            ans_final_state_idxs: [0; ANS_INTERLEAVING],
-          }
+      n_runs: match meta.mode {
+        Mode::RunLength => Some(1),
+        _ => None,
+      },
+      exceptions: if meta.has_exceptions {
+        Some(vec![
+          Exception {
+            idx: 3,
+            latent: L::MAX,
+          },
+          Exception {
+            idx: 700,
+            latent: L::ZERO,
+          },
+        ])
+      } else {
+        None
+      },
+      per_var: per_var.clone(),
+      seek_points: (0..meta.n_seek_points(n_latents))
+        .map(|seek_idx| SeekPoint {
+          bit_idx: seek_idx * 12345,
+          per_var: per_var.clone(),
         })
         .collect(),
     };
@@ -370,10 +880,14 @@ mod tests {
           .iter()
           .map(|var_meta| var_meta.ans_size_log),
         &mut writer,
-      )
+      )?
     };
     writer.flush()?;
-    assert_eq!(meta.exact_page_meta_size(), dst.len());
+    let n_exceptions = page_meta.exceptions.as_ref().map_or(0, Vec::len);
+    assert_eq!(
+      meta.exact_page_meta_size(n_exceptions, n_latents),
+      dst.len()
+    );
     Ok(())
   }
 
@@ -382,6 +896,12 @@ mod tests {
     let meta = ChunkMeta::<u32> {
       mode: Mode::Classic,
       delta_encoding_order: 5,
+      delta_lag: 1,
+      dictionary: vec![],
+      lpc_coefficients: vec![],
+      has_exceptions: false,
+      seek_index_interval: None,
+      stored_value_bounds: None,
       per_latent_var: vec![ChunkLatentVarMeta {
         ans_size_log: 0,
         bins: vec![],
@@ -396,6 +916,12 @@ mod tests {
     let meta = ChunkMeta::<u64> {
       mode: Mode::Classic,
       delta_encoding_order: 0,
+      delta_lag: 1,
+      dictionary: vec![],
+      lpc_coefficients: vec![],
+      has_exceptions: false,
+      seek_index_interval: None,
+      stored_value_bounds: None,
       per_latent_var: vec![ChunkLatentVarMeta {
         ans_size_log: 0,
         bins: vec![Bin {
@@ -414,6 +940,12 @@ mod tests {
     let meta = ChunkMeta::<u32> {
       mode: Mode::FloatMult(777_u32),
       delta_encoding_order: 3,
+      delta_lag: 1,
+      dictionary: vec![],
+      lpc_coefficients: vec![],
+      has_exceptions: false,
+      seek_index_interval: None,
+      stored_value_bounds: None,
       per_latent_var: vec![
         ChunkLatentVarMeta {
           ans_size_log: 7,
@@ -450,4 +982,408 @@ mod tests {
 
     check_exact_sizes(&meta)
   }
+
+  #[test]
+  fn exact_size_lossy_float_mult() -> PcoResult<()> {
+    let meta = ChunkMeta::<u64> {
+      mode: Mode::LossyFloatMult(777_u64),
+      delta_encoding_order: 1,
+      delta_lag: 1,
+      dictionary: vec![],
+      lpc_coefficients: vec![],
+      has_exceptions: false,
+      seek_index_interval: None,
+      stored_value_bounds: None,
+      per_latent_var: vec![ChunkLatentVarMeta {
+        ans_size_log: 2,
+        bins: vec![
+          Bin {
+            weight: 3,
+            lower: 0,
+            offset_bits: 4,
+          },
+          Bin {
+            weight: 1,
+            lower: 16,
+            offset_bits: 0,
+          },
+        ],
+      }],
+    };
+
+    check_exact_sizes(&meta)
+  }
+
+  #[test]
+  fn exact_size_dictionary() -> PcoResult<()> {
+    let meta = ChunkMeta::<u32> {
+      mode: Mode::Dictionary,
+      delta_encoding_order: 0,
+      delta_lag: 1,
+      dictionary: (0..300).map(|i| i * 1000).collect(),
+      lpc_coefficients: vec![],
+      has_exceptions: false,
+      seek_index_interval: None,
+      stored_value_bounds: None,
+      per_latent_var: vec![ChunkLatentVarMeta {
+        ans_size_log: 1,
+        bins: vec![
+          Bin {
+            weight: 1,
+            lower: 0,
+            offset_bits: 7,
+          },
+          Bin {
+            weight: 1,
+            lower: 128,
+            offset_bits: 8,
+          },
+        ],
+      }],
+    };
+
+    check_exact_sizes(&meta)
+  }
+
+  #[test]
+  fn exact_size_run_length() -> PcoResult<()> {
+    let meta = ChunkMeta::<u32> {
+      mode: Mode::RunLength,
+      delta_encoding_order: 1,
+      delta_lag: 1,
+      dictionary: vec![],
+      lpc_coefficients: vec![],
+      has_exceptions: false,
+      seek_index_interval: None,
+      stored_value_bounds: None,
+      per_latent_var: vec![
+        ChunkLatentVarMeta {
+          ans_size_log: 0,
+          bins: vec![Bin {
+            weight: 1,
+            lower: 0,
+            offset_bits: 1,
+          }],
+        },
+        ChunkLatentVarMeta {
+          ans_size_log: 3,
+          bins: vec![
+            Bin {
+              weight: 5,
+              lower: 0,
+              offset_bits: 4,
+            },
+            Bin {
+              weight: 3,
+              lower: 16,
+              offset_bits: 9,
+            },
+          ],
+        },
+      ],
+    };
+
+    check_exact_sizes(&meta)
+  }
+
+  #[test]
+  fn exact_size_delta_lag() -> PcoResult<()> {
+    let meta = ChunkMeta::<u64> {
+      mode: Mode::Classic,
+      delta_encoding_order: 2,
+      delta_lag: 700,
+      dictionary: vec![],
+      lpc_coefficients: vec![],
+      has_exceptions: false,
+      seek_index_interval: None,
+      stored_value_bounds: None,
+      per_latent_var: vec![ChunkLatentVarMeta {
+        ans_size_log: 1,
+        bins: vec![
+          Bin {
+            weight: 1,
+            lower: 0,
+            offset_bits: 3,
+          },
+          Bin {
+            weight: 1,
+            lower: 8,
+            offset_bits: 11,
+          },
+        ],
+      }],
+    };
+
+    check_exact_sizes(&meta)
+  }
+
+  #[test]
+  fn exact_size_lpc() -> PcoResult<()> {
+    let meta = ChunkMeta::<u16> {
+      mode: Mode::Lpc,
+      delta_encoding_order: 0,
+      delta_lag: 1,
+      dictionary: vec![],
+      lpc_coefficients: vec![7000, -4096, i16::MIN],
+      has_exceptions: false,
+      seek_index_interval: None,
+      stored_value_bounds: None,
+      per_latent_var: vec![ChunkLatentVarMeta {
+        ans_size_log: 2,
+        bins: vec![
+          Bin {
+            weight: 3,
+            lower: 0,
+            offset_bits: 5,
+          },
+          Bin {
+            weight: 1,
+            lower: 32,
+            offset_bits: 2,
+          },
+        ],
+      }],
+    };
+
+    check_exact_sizes(&meta)
+  }
+
+  #[test]
+  fn exact_size_decimal() -> PcoResult<()> {
+    let meta = ChunkMeta::<u64> {
+      mode: Mode::Decimal(3),
+      delta_encoding_order: 1,
+      delta_lag: 1,
+      dictionary: vec![],
+      lpc_coefficients: vec![],
+      has_exceptions: false,
+      seek_index_interval: None,
+      stored_value_bounds: None,
+      per_latent_var: vec![
+        ChunkLatentVarMeta {
+          ans_size_log: 0,
+          bins: vec![Bin {
+            weight: 1,
+            lower: 0,
+            offset_bits: 10,
+          }],
+        },
+        ChunkLatentVarMeta {
+          ans_size_log: 1,
+          bins: vec![
+            Bin {
+              weight: 1,
+              lower: u64::MID,
+              offset_bits: 0,
+            },
+            Bin {
+              weight: 1,
+              lower: 0,
+              offset_bits: 64,
+            },
+          ],
+        },
+      ],
+    };
+
+    check_exact_sizes(&meta)
+  }
+
+  #[test]
+  fn exact_size_exceptions() -> PcoResult<()> {
+    let meta = ChunkMeta::<u32> {
+      mode: Mode::Classic,
+      delta_encoding_order: 1,
+      delta_lag: 1,
+      dictionary: vec![],
+      lpc_coefficients: vec![],
+      has_exceptions: true,
+      seek_index_interval: None,
+      stored_value_bounds: None,
+      per_latent_var: vec![ChunkLatentVarMeta {
+        ans_size_log: 0,
+        bins: vec![Bin {
+          weight: 1,
+          lower: 0,
+          offset_bits: 6,
+        }],
+      }],
+    };
+
+    check_exact_sizes(&meta)
+  }
+
+  #[test]
+  fn exact_size_seek_index() -> PcoResult<()> {
+    let meta = ChunkMeta::<u64> {
+      mode: Mode::FloatMult(777_u64),
+      delta_encoding_order: 2,
+      delta_lag: 3,
+      dictionary: vec![],
+      lpc_coefficients: vec![],
+      has_exceptions: false,
+      seek_index_interval: Some(1),
+      stored_value_bounds: None,
+      per_latent_var: vec![
+        ChunkLatentVarMeta {
+          ans_size_log: 7,
+          bins: vec![Bin {
+            weight: 11,
+            lower: 0,
+            offset_bits: 0,
+          }],
+        },
+        ChunkLatentVarMeta {
+          ans_size_log: 3,
+          bins: vec![Bin {
+            weight: 3,
+            lower: 0,
+            offset_bits: 5,
+          }],
+        },
+      ],
+    };
+
+    assert_eq!(meta.n_seek_points(1000), 3);
+    check_exact_sizes(&meta)
+  }
+
+  #[test]
+  fn exact_size_value_bounds() -> PcoResult<()> {
+    let meta = ChunkMeta::<u16> {
+      mode: Mode::Classic,
+      delta_encoding_order: 1,
+      delta_lag: 1,
+      dictionary: vec![],
+      lpc_coefficients: vec![],
+      has_exceptions: false,
+      seek_index_interval: None,
+      stored_value_bounds: Some((3, 60000)),
+      per_latent_var: vec![ChunkLatentVarMeta {
+        ans_size_log: 0,
+        bins: vec![Bin {
+          weight: 1,
+          lower: 0,
+          offset_bits: 6,
+        }],
+      }],
+    };
+
+    check_exact_sizes(&meta)
+  }
+
+  #[test]
+  fn value_bounds() {
+    let mut meta = ChunkMeta::<u32> {
+      mode: Mode::Classic,
+      delta_encoding_order: 0,
+      delta_lag: 1,
+      dictionary: vec![],
+      lpc_coefficients: vec![],
+      has_exceptions: false,
+      seek_index_interval: None,
+      stored_value_bounds: None,
+      per_latent_var: vec![ChunkLatentVarMeta {
+        ans_size_log: 1,
+        bins: vec![
+          Bin {
+            weight: 1,
+            lower: 100,
+            offset_bits: 3,
+          },
+          Bin {
+            weight: 1,
+            lower: u32::MAX - 5,
+            offset_bits: 4,
+          },
+        ],
+      }],
+    };
+    // the second bin's upper bound saturates
+    assert_eq!(
+      meta.value_bounds::<u32>(),
+      Some((100, u32::MAX))
+    );
+
+    meta.has_exceptions = true;
+    assert_eq!(meta.value_bounds::<u32>(), None);
+
+    meta.stored_value_bounds = Some((101, 200));
+    assert_eq!(meta.value_bounds::<u32>(), Some((101, 200)));
+    assert_eq!(
+      meta.value_bounds::<i32>(),
+      Some((i32::MIN + 101, i32::MIN + 200))
+    );
+
+    meta.stored_value_bounds = None;
+    meta.has_exceptions = false;
+    meta.mode = Mode::Dictionary;
+    meta.dictionary = vec![5, 9, 1000];
+    assert_eq!(meta.value_bounds::<u32>(), Some((5, 1000)));
+  }
+
+  #[test]
+  fn approx_histogram_and_quantiles() -> PcoResult<()> {
+    let mut meta = ChunkMeta::<u32> {
+      mode: Mode::Classic,
+      delta_encoding_order: 0,
+      delta_lag: 1,
+      dictionary: vec![],
+      lpc_coefficients: vec![],
+      has_exceptions: false,
+      seek_index_interval: None,
+      stored_value_bounds: None,
+      per_latent_var: vec![ChunkLatentVarMeta {
+        ans_size_log: 2,
+        bins: vec![
+          Bin {
+            weight: 3,
+            lower: 100,
+            offset_bits: 2,
+          },
+          Bin {
+            weight: 1,
+            lower: 0,
+            offset_bits: 0,
+          },
+        ],
+      }],
+    };
+
+    let histogram = meta.approx_histogram::<u32>().unwrap();
+    assert_eq!(
+      histogram,
+      vec![
+        HistogramBin {
+          lower: 0,
+          upper: 0,
+          fraction: 0.25,
+        },
+        HistogramBin {
+          lower: 100,
+          upper: 103,
+          fraction: 0.75,
+        },
+      ]
+    );
+    assert_eq!(meta.approx_quantile::<u32>(0.0)?, Some(0));
+    assert_eq!(meta.approx_quantile::<u32>(0.25)?, Some(0));
+    assert_eq!(meta.approx_quantile::<u32>(0.5)?, Some(101));
+    assert_eq!(meta.approx_quantile::<u32>(1.0)?, Some(103));
+    assert!(meta.approx_quantile::<u32>(1.1).is_err());
+    assert!(meta.approx_quantile::<u32>(f64::NAN).is_err());
+
+    // dictionary bins map through the dictionary
+    meta.mode = Mode::Dictionary;
+    meta.dictionary = (0..102).map(|i| i * 10).collect();
+    let histogram = meta.approx_histogram::<u32>().unwrap();
+    assert_eq!(histogram[1].lower, 1000);
+    assert_eq!(histogram[1].upper, 1010);
+    assert_eq!(meta.approx_quantile::<u32>(1.0)?, Some(1010));
+
+    meta.delta_encoding_order = 1;
+    assert_eq!(meta.approx_histogram::<u32>(), None);
+    assert_eq!(meta.approx_quantile::<u32>(0.5)?, None);
+    Ok(())
+  }
 }
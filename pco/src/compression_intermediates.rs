@@ -5,6 +5,8 @@ use crate::data_types::Latent;
 #[derive(Clone, Debug)]
 pub struct PageInfo {
   pub page_n: usize,
+  // differs from page_n in run length mode, where each latent is a run
+  pub n_latents: usize,
   pub start_idx: usize,
   pub end_idx_per_var: Vec<usize>,
 }
@@ -18,10 +20,12 @@ pub struct DissectedPageVar<L: Latent> {
   pub offset_bits: Vec<Bitlen>,
 
   pub ans_final_states: [AnsState; ANS_INTERLEAVING],
+  // the ANS states at the start of each seek index entry's batch
+  pub seek_ans_states: Vec<[AnsState; ANS_INTERLEAVING]>,
 }
 
 #[derive(Clone, Debug)]
 pub struct DissectedPage<L: Latent> {
-  pub page_n: usize,
+  pub n_latents: usize,
   pub per_var: Vec<DissectedPageVar<L>>, // one per latent variable
 }
@@ -8,13 +8,22 @@ pub(crate) type Bitlen = u32;
 pub(crate) type Weight = u32;
 
 // compatibility
-pub const CURRENT_FORMAT_VERSION: u8 = 1;
+pub const CURRENT_FORMAT_VERSION: u8 = 10;
 
 // bit lengths
 pub const BITS_TO_ENCODE_ANS_SIZE_LOG: Bitlen = 4;
+pub const BITS_TO_ENCODE_DECIMAL_EXPONENT: Bitlen = 5;
 pub const BITS_TO_ENCODE_DELTA_ENCODING_ORDER: Bitlen = 3;
+pub const BITS_TO_ENCODE_DELTA_LAG: Bitlen = 12;
+pub const BITS_TO_ENCODE_DICTIONARY_SIZE: Bitlen = 15;
+pub const BITS_TO_ENCODE_EXCEPTION_IDX: Bitlen = 24;
+pub const BITS_TO_ENCODE_LPC_ORDER: Bitlen = 3;
 pub const BITS_TO_ENCODE_MODE: Bitlen = 4;
 pub const BITS_TO_ENCODE_N_BINS: Bitlen = 15;
+pub const BITS_TO_ENCODE_N_EXCEPTIONS: Bitlen = 24;
+pub const BITS_TO_ENCODE_N_RUNS: Bitlen = 24;
+pub const BITS_TO_ENCODE_SEEK_BIT_IDX: Bitlen = 36;
+pub const BITS_TO_ENCODE_SEEK_INTERVAL: Bitlen = 16;
 
 // padding
 pub const HEADER_PADDING: usize = 1;
@@ -34,8 +43,18 @@ pub const MAX_ANS_BITS: Bitlen = 14;
 pub const MAX_ANS_BYTES: usize = MAX_ANS_BITS.div_ceil(8) as usize;
 pub const LIMITED_UNOPTIMIZED_BINS_LOG: Bitlen = 6;
 pub const MAX_COMPRESSION_LEVEL: usize = 12;
+// the largest power of ten exactly representable as an f64
+pub const MAX_DECIMAL_EXPONENT: u32 = 22;
 pub const MAX_DELTA_ENCODING_ORDER: usize = 7;
+pub const MAX_DELTA_LAG: usize = 1 << 12;
+pub const MAX_DICTIONARY_SIZE: usize = 1 << 14;
+pub const MAX_LPC_ORDER: usize = 8;
+// the maximum count of numbers per page
 pub const MAX_ENTRIES: usize = 1 << 24;
+// the maximum count of numbers per chunk; bin counts are accumulated as
+// Weights, so this must stay below 2^32
+pub const MAX_CHUNK_ENTRIES: usize = 1 << 31;
+pub const MAX_SEEK_INTERVAL: usize = 1 << 16;
 pub const MAX_SUPPORTED_PRECISION: Bitlen = 128;
 pub const MAX_SUPPORTED_PRECISION_BYTES: usize = (MAX_SUPPORTED_PRECISION / 8) as usize;
 pub const MULT_REQUIRED_BITS_SAVED_PER_NUM: f64 = 0.5;
@@ -45,6 +64,8 @@ pub const CLASSIC_MEMORIZABLE_BINS_LOG: Bitlen = 8;
 pub const DEFAULT_COMPRESSION_LEVEL: usize = 8;
 // if you modify default page size, update docs for PagingSpec
 pub const DEFAULT_MAX_PAGE_N: usize = 1 << 18;
+// if you modify default chunk size, update docs for ChunkConfig
+pub const DEFAULT_MAX_CHUNK_N: usize = 1 << 18;
 
 // important parts of the format specification
 pub const ANS_INTERLEAVING: usize = 4;
@@ -53,6 +74,9 @@ pub const ANS_INTERLEAVING: usize = 4;
 /// Only the final batch in each page may have fewer numbers than this.
 pub const FULL_BATCH_N: usize = 256;
 pub const FULL_BIN_BATCH_SIZE: usize = 128;
+// linear predictor coefficients are fixed-point i16s with this many fractional bits
+pub const LPC_COEFFICIENT_BITS: Bitlen = 16;
+pub const LPC_PRECISION_BITS: Bitlen = 12;
 
 #[cfg(test)]
 mod tests {
@@ -66,6 +90,14 @@ mod tests {
     assert!(n_bits >= bits_to_encode(max_number));
   }
 
+  #[test]
+  fn test_bits_to_encode_decimal_exponent() {
+    assert_can_encode(
+      BITS_TO_ENCODE_DECIMAL_EXPONENT,
+      MAX_DECIMAL_EXPONENT as usize,
+    );
+  }
+
   #[test]
   fn test_bits_to_encode_delta_encoding_order() {
     assert_can_encode(
@@ -74,6 +106,62 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_bits_to_encode_delta_lag() {
+    // we encode 1 less than the lag
+    assert_can_encode(BITS_TO_ENCODE_DELTA_LAG, MAX_DELTA_LAG - 1);
+  }
+
+  #[test]
+  fn test_bits_to_encode_dictionary_size() {
+    assert_can_encode(
+      BITS_TO_ENCODE_DICTIONARY_SIZE,
+      MAX_DICTIONARY_SIZE,
+    );
+  }
+
+  #[test]
+  fn test_bits_to_encode_exceptions() {
+    // exceptions are indices into a page, and there are never as many of
+    // them as numbers in the page
+    assert_can_encode(BITS_TO_ENCODE_EXCEPTION_IDX, MAX_ENTRIES - 1);
+    assert_can_encode(BITS_TO_ENCODE_N_EXCEPTIONS, MAX_ENTRIES - 1);
+  }
+
+  #[test]
+  fn test_bits_to_encode_lpc_order() {
+    // we encode 1 less than the order
+    assert_can_encode(BITS_TO_ENCODE_LPC_ORDER, MAX_LPC_ORDER - 1);
+  }
+
+  #[test]
+  fn test_bits_to_encode_n_runs() {
+    // we encode 1 less than the count of runs, which is at most MAX_ENTRIES
+    assert_can_encode(BITS_TO_ENCODE_N_RUNS, MAX_ENTRIES - 1);
+  }
+
+  #[test]
+  fn test_bits_to_encode_seek_index() {
+    // a seek index entry's bit position is within a page body, where each
+    // number has at most 2 latent variables
+    let max_bits_per_num = 2 * (MAX_SUPPORTED_PRECISION + MAX_ANS_BITS) as usize;
+    assert_can_encode(
+      BITS_TO_ENCODE_SEEK_BIT_IDX,
+      MAX_ENTRIES * max_bits_per_num,
+    );
+    // we encode 1 less than the interval
+    assert_can_encode(
+      BITS_TO_ENCODE_SEEK_INTERVAL,
+      MAX_SEEK_INTERVAL - 1,
+    );
+  }
+
+  #[test]
+  fn test_chunk_bin_counts_fit_in_weight() {
+    // a bin's count is at most the count of numbers in the chunk
+    assert_can_encode(Weight::BITS, MAX_CHUNK_ENTRIES);
+  }
+
   #[test]
   fn test_ans_interleaving_fits_in_u64() {
     assert!(ANS_INTERLEAVING * MAX_ANS_BITS as usize <= 57);
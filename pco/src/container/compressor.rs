@@ -0,0 +1,118 @@
+use std::io::Write;
+
+use crate::container::constants::*;
+use crate::container::ColumnMeta;
+use crate::data_types::NumberLike;
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone;
+use crate::standalone::footer::{write_bytes, write_varint_aligned};
+use crate::ChunkConfig;
+
+/// Top-level entry point for compressing container files, which hold
+/// several named columns of possibly different data types.
+///
+/// Each column is written as a complete standalone file, and a directory of
+/// columns at the end of the container lets decompressors read only the
+/// columns they need.
+///
+/// Example:
+/// ```
+/// use pco::ChunkConfig;
+/// use pco::container::{TableCompressor, TableDecompressor};
+/// # use pco::errors::PcoResult;
+///
+/// # fn main() -> PcoResult<()> {
+/// let config = ChunkConfig::default();
+/// let mut table_compressor = TableCompressor::new(Vec::new())?;
+/// table_compressor.write_column("id", &[1_i64, 2, 3], &config)?;
+/// table_compressor.write_column("price", &[9.5_f32, 7.25, 8.0], &config)?;
+/// let compressed = table_compressor.finish()?;
+///
+/// let table_decompressor = TableDecompressor::new(&compressed)?;
+/// let prices = table_decompressor.decompress_column::<f32>("price")?;
+/// assert_eq!(prices, vec![9.5, 7.25, 8.0]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct TableCompressor<W: Write> {
+  dst: W,
+  columns: Vec<ColumnMeta>,
+  // the byte offset at which the next column will start
+  offset: usize,
+}
+
+impl<W: Write> TableCompressor<W> {
+  /// Writes a short header to the destination and returns a
+  /// `TableCompressor` for writing columns to it.
+  ///
+  /// Will return an error if the provided `Write` errors.
+  pub fn new(mut dst: W) -> PcoResult<Self> {
+    dst.write_all(&MAGIC_HEADER)?;
+    dst.write_all(&[CURRENT_CONTAINER_VERSION])?;
+    Ok(Self {
+      dst,
+      columns: Vec::new(),
+      offset: HEADER_SIZE,
+    })
+  }
+
+  /// Compresses the numbers and writes them as a new column with the given
+  /// name.
+  ///
+  /// Like [`simple_compress`][crate::standalone::simple_compress], this uses
+  /// the config's `chunking_spec` to decide where to split chunks.
+  ///
+  /// Will return an error if the table already has a column with this name,
+  /// if the config is invalid, or if the provided `Write` errors.
+  pub fn write_column<T: NumberLike>(
+    &mut self,
+    name: &str,
+    nums: &[T],
+    config: &ChunkConfig,
+  ) -> PcoResult<()> {
+    if self.columns.iter().any(|column| column.name == name) {
+      return Err(PcoError::invalid_argument(format!(
+        "table already has a column named {:?}",
+        name,
+      )));
+    }
+
+    let compressed = standalone::simple_compress(nums, config)?;
+    self.dst.write_all(&compressed)?;
+    let start = self.offset;
+    self.offset += compressed.len();
+    self.columns.push(ColumnMeta {
+      name: name.to_string(),
+      dtype_byte: T::DTYPE_BYTE,
+      n: nums.len(),
+      start,
+      end: self.offset,
+    });
+    Ok(())
+  }
+
+  /// Writes the directory of columns and returns the destination.
+  ///
+  /// Will return an error if the provided `Write` errors.
+  pub fn finish(mut self) -> PcoResult<W> {
+    let mut directory = Vec::new();
+    write_varint_aligned(self.columns.len(), &mut directory)?;
+    for column in &self.columns {
+      write_bytes(column.name.as_bytes(), &mut directory)?;
+      directory.push(column.dtype_byte);
+      write_varint_aligned(column.n, &mut directory)?;
+      write_varint_aligned(column.end - column.start, &mut directory)?;
+    }
+
+    let directory_size = u32::try_from(directory.len()).map_err(|_| {
+      PcoError::invalid_argument(format!(
+        "table directory is too large ({} bytes)",
+        directory.len(),
+      ))
+    })?;
+    self.dst.write_all(&directory)?;
+    self.dst.write_all(&directory_size.to_le_bytes())?;
+    self.dst.write_all(&MAGIC_FOOTER)?;
+    Ok(self.dst)
+  }
+}
@@ -0,0 +1,8 @@
+// ascii for pcot, distinguishing containers from standalone files
+pub const MAGIC_HEADER: [u8; 4] = [112, 99, 111, 116];
+pub const MAGIC_FOOTER: [u8; 4] = MAGIC_HEADER;
+pub const CURRENT_CONTAINER_VERSION: u8 = 1;
+// the magic header and container version
+pub const HEADER_SIZE: usize = MAGIC_HEADER.len() + 1;
+// the directory's byte size as a u32, followed by the magic footer
+pub const TRAILER_SIZE: usize = 4 + MAGIC_FOOTER.len();
@@ -0,0 +1,321 @@
+use std::collections::HashSet;
+
+use crate::bit_reader::BitReader;
+use crate::constants::OVERSHOOT_PADDING;
+use crate::container::constants::*;
+use crate::data_types::NumberLike;
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone;
+use crate::standalone::footer::{read_string, read_varint_aligned};
+
+/// Information about one column of a container file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnMeta {
+  /// The column's name, which is unique within the container.
+  pub name: String,
+  /// The byte for the column's data type, which can be converted with
+  /// [`CoreDataType::from_byte`][crate::data_types::CoreDataType::from_byte].
+  pub dtype_byte: u8,
+  /// The count of numbers in the column.
+  pub n: usize,
+  /// The byte offset where the column's standalone file starts.
+  pub start: usize,
+  /// The byte offset where the column's standalone file ends.
+  pub end: usize,
+}
+
+/// Top-level entry point for decompressing container files written by a
+/// [`TableCompressor`][crate::container::TableCompressor].
+///
+/// Only the header and directory of columns are read upfront, so
+/// decompressing a column doesn't require reading any other columns.
+#[derive(Clone, Debug)]
+pub struct TableDecompressor<'a> {
+  src: &'a [u8],
+  columns: Vec<ColumnMeta>,
+}
+
+impl<'a> TableDecompressor<'a> {
+  /// Reads the container's header and directory of columns from the start
+  /// and end of `src`, which should be the entire container file.
+  ///
+  /// Will return an error if any corruptions, version incompatibilities, or
+  /// insufficient data are found.
+  pub fn new(src: &'a [u8]) -> PcoResult<Self> {
+    if src.len() < HEADER_SIZE + TRAILER_SIZE {
+      return Err(PcoError::insufficient_data(format!(
+        "container of {} bytes is too short to have a header and footer",
+        src.len(),
+      )));
+    }
+
+    let header = &src[..MAGIC_HEADER.len()];
+    if header != MAGIC_HEADER {
+      return Err(PcoError::corruption(format!(
+        "magic header does not match {:?}; instead found {:?}",
+        MAGIC_HEADER, header,
+      )));
+    }
+    let version = src[MAGIC_HEADER.len()];
+    if version > CURRENT_CONTAINER_VERSION {
+      return Err(PcoError::compatibility(format!(
+        "container version ({}) exceeds max supported ({}); consider upgrading pco",
+        version, CURRENT_CONTAINER_VERSION,
+      )));
+    }
+
+    let trailer_start = src.len() - TRAILER_SIZE;
+    let (size_bytes, footer) = src[trailer_start..].split_at(TRAILER_SIZE - MAGIC_FOOTER.len());
+    if footer != MAGIC_FOOTER {
+      return Err(PcoError::corruption(format!(
+        "magic footer does not match {:?}; instead found {:?}",
+        MAGIC_FOOTER, footer,
+      )));
+    }
+    let directory_size = u32::from_le_bytes(size_bytes.try_into().unwrap()) as usize;
+    let directory_start = trailer_start
+      .checked_sub(directory_size)
+      .filter(|&start| start >= HEADER_SIZE)
+      .ok_or_else(|| {
+        PcoError::corruption(format!(
+          "directory of {} bytes does not fit in container of {} bytes",
+          directory_size,
+          src.len(),
+        ))
+      })?;
+
+    let columns = parse_directory(&src[directory_start..trailer_start])?;
+    let columns_end = columns.last().map_or(HEADER_SIZE, |column| column.end);
+    if columns_end != directory_start {
+      return Err(PcoError::corruption(format!(
+        "columns end at byte {} but directory starts at byte {}",
+        columns_end, directory_start,
+      )));
+    }
+
+    Ok(Self { src, columns })
+  }
+
+  /// Returns information about every column, in the order they were
+  /// written.
+  pub fn columns(&self) -> &[ColumnMeta] {
+    &self.columns
+  }
+
+  /// Returns information about the column with the given name.
+  ///
+  /// Will return an invalid argument error if there is no such column.
+  pub fn column(&self, name: &str) -> PcoResult<&ColumnMeta> {
+    self
+      .columns
+      .iter()
+      .find(|column| column.name == name)
+      .ok_or_else(|| PcoError::invalid_argument(format!("no column named {:?}", name)))
+  }
+
+  /// Returns the column's standalone file, which can be decompressed with
+  /// any of the [`standalone`][crate::standalone] APIs, e.g. to decompress it
+  /// chunk by chunk.
+  ///
+  /// Will return an invalid argument error if there is no such column.
+  pub fn column_src(&self, name: &str) -> PcoResult<&'a [u8]> {
+    let column = self.column(name)?;
+    Ok(&self.src[column.start..column.end])
+  }
+
+  /// Decompresses the entire column with the given name.
+  ///
+  /// Will return an invalid argument error if there is no such column or
+  /// its data type doesn't match `T`, or an error if there are any
+  /// compatibility, corruption, or insufficient data issues.
+  pub fn decompress_column<T: NumberLike>(&self, name: &str) -> PcoResult<Vec<T>> {
+    let column = self.column(name)?;
+    if column.dtype_byte != T::DTYPE_BYTE {
+      return Err(PcoError::invalid_argument(format!(
+        "column {:?} has data type byte {}, not {}",
+        name,
+        column.dtype_byte,
+        T::DTYPE_BYTE,
+      )));
+    }
+
+    let nums = standalone::simple_decompress::<T>(&self.src[column.start..column.end])?;
+    if nums.len() != column.n {
+      return Err(PcoError::corruption(format!(
+        "column {:?} has {} numbers but the directory claims {}",
+        name,
+        nums.len(),
+        column.n,
+      )));
+    }
+    Ok(nums)
+  }
+}
+
+fn parse_directory(directory: &[u8]) -> PcoResult<Vec<ColumnMeta>> {
+  let size = directory.len();
+  let mut padded = vec![0; size + OVERSHOOT_PADDING];
+  padded[..size].copy_from_slice(directory);
+  let mut reader = BitReader::new(&padded, size, 0);
+
+  let mut columns = Vec::new();
+  let mut names = HashSet::new();
+  let mut start = HEADER_SIZE;
+  unsafe {
+    let n_columns = read_varint_aligned(&mut reader, "column count")?;
+    for _ in 0..n_columns {
+      let name = read_string(&mut reader, size, "column name")?;
+      let dtype_byte = reader.read_aligned_bytes(1)?[0];
+      let n = read_varint_aligned(&mut reader, "column n")?;
+      let column_size = read_varint_aligned(&mut reader, "column size")?;
+      let end = start
+        .checked_add(column_size)
+        .ok_or_else(|| PcoError::corruption("column offsets overflow"))?;
+
+      if !names.insert(name.clone()) {
+        return Err(PcoError::corruption(format!(
+          "duplicate column name {:?}",
+          name
+        )));
+      }
+      columns.push(ColumnMeta {
+        name,
+        dtype_byte,
+        n,
+        start,
+        end,
+      });
+      start = end;
+    }
+  }
+
+  if reader.bit_idx() != size * 8 {
+    return Err(PcoError::corruption(format!(
+      "directory has {} unexpected trailing bytes",
+      size - reader.bit_idx() / 8,
+    )));
+  }
+  Ok(columns)
+}
+
+#[cfg(test)]
+mod tests {
+  use half::f16;
+
+  use super::*;
+  use crate::container::TableCompressor;
+  use crate::data_types::CoreDataType;
+  use crate::errors::ErrorKind;
+  use crate::standalone::footer::write_varint_aligned;
+  use crate::{ChunkConfig, PagingSpec};
+
+  #[test]
+  fn test_table_roundtrip() -> PcoResult<()> {
+    let ids = (0..3000).collect::<Vec<u64>>();
+    let prices = ids.iter().map(|&i| i as f64 * 0.25).collect::<Vec<_>>();
+    let halves = ids
+      .iter()
+      .map(|&i| f16::from_f32((i % 100) as f32))
+      .collect::<Vec<_>>();
+    let config = ChunkConfig::default().with_chunking_spec(PagingSpec::EqualPagesUpTo(1000));
+
+    let mut table_compressor = TableCompressor::new(Vec::new())?;
+    table_compressor.write_column("id", &ids, &config)?;
+    table_compressor.write_column("price", &prices, &config)?;
+    table_compressor.write_column("empty", &Vec::<i32>::new(), &config)?;
+    table_compressor.write_column("ünïcode", &halves, &config)?;
+    assert!(table_compressor.write_column("id", &ids, &config).is_err());
+    let mut src = table_compressor.finish()?;
+
+    let table_decompressor = TableDecompressor::new(&src)?;
+    let names = table_decompressor
+      .columns()
+      .iter()
+      .map(|column| column.name.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      names,
+      vec!["id", "price", "empty", "ünïcode"]
+    );
+    let price_meta = table_decompressor.column("price")?.clone();
+    assert_eq!(price_meta.n, 3000);
+    assert_eq!(
+      CoreDataType::from_byte(price_meta.dtype_byte),
+      Some(CoreDataType::F64)
+    );
+    assert_eq!(
+      table_decompressor.decompress_column::<u64>("id")?,
+      ids
+    );
+    assert_eq!(
+      table_decompressor.decompress_column::<i32>("empty")?,
+      Vec::<i32>::new()
+    );
+    assert_eq!(
+      table_decompressor.decompress_column::<f16>("ünïcode")?,
+      halves
+    );
+    assert_eq!(
+      standalone::simple_decompress::<f64>(table_decompressor.column_src("price")?)?,
+      prices
+    );
+
+    let err = table_decompressor
+      .decompress_column::<f32>("price")
+      .unwrap_err();
+    assert!(matches!(
+      err.kind,
+      ErrorKind::InvalidArgument
+    ));
+    assert!(table_decompressor.column("missing").is_err());
+
+    // other columns can be read even if one is corrupt
+    src[(price_meta.start + price_meta.end) / 2] ^= 0xff;
+    src[price_meta.end - 1] ^= 0xff;
+    let table_decompressor = TableDecompressor::new(&src)?;
+    assert!(table_decompressor
+      .decompress_column::<f64>("price")
+      .is_err());
+    assert_eq!(
+      table_decompressor.decompress_column::<u64>("id")?,
+      ids
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_table_errors() -> PcoResult<()> {
+    let mut table_compressor = TableCompressor::new(Vec::new())?;
+    table_compressor.write_column("x", &[1_i32, 2, 3], &ChunkConfig::default())?;
+    let src = table_compressor.finish()?;
+
+    for i in 0..src.len() {
+      assert!(TableDecompressor::new(&src[..i]).is_err());
+    }
+
+    let mut future_version = src.clone();
+    future_version[MAGIC_HEADER.len()] = CURRENT_CONTAINER_VERSION + 1;
+    let err = TableDecompressor::new(&future_version).unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::Compatibility));
+
+    // an empty table
+    let src = TableCompressor::new(Vec::new())?.finish()?;
+    assert!(TableDecompressor::new(&src)?.columns().is_empty());
+
+    // a corrupt directory with an overflowing column name length
+    let mut directory = Vec::new();
+    write_varint_aligned(1, &mut directory)?;
+    write_varint_aligned(usize::MAX, &mut directory)?;
+    directory.extend([0; 4]);
+    let mut corrupt = src[..HEADER_SIZE].to_vec();
+    corrupt.extend(&directory);
+    corrupt.extend((directory.len() as u32).to_le_bytes());
+    corrupt.extend(MAGIC_FOOTER);
+    let err = TableDecompressor::new(&corrupt).unwrap_err();
+    assert!(matches!(
+      err.kind,
+      ErrorKind::InsufficientData
+    ));
+    Ok(())
+  }
+}
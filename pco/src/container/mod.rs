@@ -0,0 +1,6 @@
+pub use compressor::TableCompressor;
+pub use decompressor::{ColumnMeta, TableDecompressor};
+
+mod compressor;
+mod constants;
+mod decompressor;
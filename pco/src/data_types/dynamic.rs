@@ -54,6 +54,11 @@ macro_rules! with_core_dtypes {
       I64(U64) => i64,
       F32(U32) => f32,
       F64(U64) => f64,
+      U16(U16) => u16,
+      I16(U16) => i16,
+      F16(U16) => half::f16,
+      U128(U128) => u128,
+      I128(U128) => i128,
     );
   }
 }
@@ -64,8 +69,10 @@ macro_rules! with_core_dtypes {
 macro_rules! with_core_latents {
   ($inner:ident) => {
     $inner!(
+      U16 => u16,
       U32 => u32,
       U64 => u64,
+      U128 => u128,
     );
   }
 }
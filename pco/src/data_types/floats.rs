@@ -1,16 +1,71 @@
 use std::mem;
+use std::num::FpCategory;
 
-use crate::constants::Bitlen;
+use half::f16;
+
+use crate::constants::{Bitlen, MAX_DECIMAL_EXPONENT};
 use crate::data_types::{split_latents_classic, FloatLike, Latent, NumberLike};
-use crate::{float_mult_utils, ChunkConfig, FloatMultSpec, Mode};
+use crate::{
+  decimal_utils, float_mult_utils, lossy_utils, ChunkConfig, DecimalSpec, FloatMultSpec, LossySpec,
+  Mode,
+};
 
 fn choose_mode_and_split_latents<F: FloatLike>(
   nums: &[F],
   chunk_config: &ChunkConfig,
 ) -> (Mode<F::L>, Vec<Vec<F::L>>) {
+  match chunk_config.lossy_spec {
+    LossySpec::None => choose_mode_and_split_quantized_latents(nums, chunk_config),
+    spec => {
+      let quantized = lossy_utils::quantize(nums, spec);
+      // Lossy float mult would round the quantized floats a second time,
+      // which could exceed the tolerance, so we fall back to lossless float
+      // mult.
+      let float_mult_spec = match chunk_config.float_mult_spec {
+        FloatMultSpec::LossyEnabled => FloatMultSpec::Enabled,
+        FloatMultSpec::LossyProvided(base) => FloatMultSpec::Provided(base),
+        other => other,
+      };
+      let config = chunk_config.clone().with_float_mult_spec(float_mult_spec);
+      choose_mode_and_split_quantized_latents(&quantized, &config)
+    }
+  }
+}
+
+fn choose_mode_and_split_quantized_latents<F: FloatLike>(
+  nums: &[F],
+  chunk_config: &ChunkConfig,
+) -> (Mode<F::L>, Vec<Vec<F::L>>) {
+  let maybe_decimal_exponent = match (
+    chunk_config.decimal_spec,
+    chunk_config.float_mult_spec,
+  ) {
+    (DecimalSpec::Enabled, FloatMultSpec::Enabled | FloatMultSpec::Disabled) => {
+      decimal_utils::choose_exponent(nums)
+    }
+    _ => None,
+  };
+  let split_decimal = |exponent| {
+    (
+      Mode::Decimal(exponent),
+      decimal_utils::split_latents(nums, exponent),
+    )
+  };
+
   match chunk_config.float_mult_spec {
     FloatMultSpec::Enabled => {
-      if let Some(fm_config) = float_mult_utils::choose_config(nums) {
+      let maybe_fm_config = float_mult_utils::choose_config(nums);
+      if let Some(exponent) = maybe_decimal_exponent {
+        // When float mult finds the same grid, decimal mode is better because
+        // it reconstructs decimals exactly instead of needing adjustments.
+        let fm_is_coarser = maybe_fm_config
+          .is_some_and(|fm_config| !decimal_utils::is_decimal_base(fm_config.base, exponent));
+        if !fm_is_coarser {
+          return split_decimal(exponent);
+        }
+      }
+
+      if let Some(fm_config) = maybe_fm_config {
         let mode = Mode::float_mult(fm_config.base);
         let latents = float_mult_utils::split_latents(nums, fm_config.base, fm_config.inv_base);
         (mode, latents)
@@ -24,7 +79,25 @@ fn choose_mode_and_split_latents<F: FloatLike>(
       let latents = float_mult_utils::split_latents(nums, base, base.inv());
       (mode, latents)
     }
-    FloatMultSpec::Disabled => (Mode::Classic, split_latents_classic(nums)),
+    FloatMultSpec::LossyEnabled => {
+      if let Some(fm_config) = float_mult_utils::choose_config(nums) {
+        let mode = Mode::lossy_float_mult(fm_config.base);
+        let latents = float_mult_utils::split_latents_lossy(nums, fm_config.inv_base);
+        (mode, latents)
+      } else {
+        (Mode::Classic, split_latents_classic(nums))
+      }
+    }
+    FloatMultSpec::LossyProvided(base_f64) => {
+      let base = F::from_f64(base_f64);
+      let mode = Mode::lossy_float_mult(base);
+      let latents = float_mult_utils::split_latents_lossy(nums, base.inv());
+      (mode, latents)
+    }
+    FloatMultSpec::Disabled => match maybe_decimal_exponent {
+      Some(exponent) => split_decimal(exponent),
+      None => (Mode::Classic, split_latents_classic(nums)),
+    },
   }
 }
 
@@ -36,8 +109,8 @@ fn format_delta<L: Latent>(adj: L, suffix: &str) -> String {
   }
 }
 
-macro_rules! impl_float_number {
-  ($t: ty, $latent: ty, $bits: expr, $sign_bit_mask: expr, $header_byte: expr, $exp_offset: expr) => {
+macro_rules! impl_float_like {
+  ($t: ty, $bits: expr, $exp_offset: expr) => {
     impl FloatLike for $t {
       const BITS: Bitlen = $bits;
       const PRECISION_BITS: Bitlen = Self::MANTISSA_DIGITS as Bitlen - 1;
@@ -147,7 +220,128 @@ macro_rules! impl_float_number {
         l as Self
       }
     }
+  };
+}
+
+impl_float_like!(f32, 32, -127);
+impl_float_like!(f64, 64, -1023);
+
+// f16 doesn't support the `as` casts or most of the arithmetic methods the
+// primitive floats have, so we implement it by hand, going through f32 where
+// needed.
+impl FloatLike for f16 {
+  const BITS: Bitlen = 16;
+  const PRECISION_BITS: Bitlen = Self::MANTISSA_DIGITS as Bitlen - 1;
+  const ZERO: Self = f16::ZERO;
+  // this is f16::MAX * 0.5
+  const MAX_FOR_SAMPLING: Self = f16::from_bits(0x77ff);
+
+  #[inline]
+  fn abs(self) -> Self {
+    Self::from_bits(self.to_bits() & 0x7fff)
+  }
+
+  fn inv(self) -> Self {
+    Self::ONE / self
+  }
+
+  #[inline]
+  fn round(self) -> Self {
+    Self::from_f32(self.to_f32().round())
+  }
+
+  #[inline]
+  fn exp2(power: i32) -> Self {
+    Self::from_f32(f32::exp2(power as f32))
+  }
+
+  #[inline]
+  fn from_f64(x: f64) -> Self {
+    Self::from_f64(x)
+  }
+
+  #[inline]
+  fn to_f64(self) -> f64 {
+    self.to_f64()
+  }
+
+  #[inline]
+  fn is_finite_and_normal(&self) -> bool {
+    self.is_finite() && self.classify() != FpCategory::Subnormal
+  }
+
+  #[inline]
+  fn exponent(&self) -> i32 {
+    (self.abs().to_bits() >> Self::PRECISION_BITS) as i32 - 15
+  }
+
+  #[inline]
+  fn trailing_zeros(&self) -> u32 {
+    self.to_bits().trailing_zeros()
+  }
+
+  #[inline]
+  fn max(a: Self, b: Self) -> Self {
+    Self::max(a, b)
+  }
+
+  #[inline]
+  fn min(a: Self, b: Self) -> Self {
+    Self::min(a, b)
+  }
+
+  #[inline]
+  fn to_latent_bits(self) -> Self::L {
+    self.to_bits()
+  }
+
+  #[inline]
+  fn int_float_from_latent(l: Self::L) -> Self {
+    let mid = Self::L::MID;
+    let (negative, abs_int) = if l >= mid {
+      (false, l - mid)
+    } else {
+      (true, mid - 1 - l)
+    };
+    let gpi = 1 << Self::MANTISSA_DIGITS;
+    let abs_float = if abs_int < gpi {
+      Self::from_f32(abs_int as f32)
+    } else {
+      Self::from_bits(Self::from_f32(gpi as f32).to_bits() + (abs_int - gpi))
+    };
+    if negative {
+      -abs_float
+    } else {
+      abs_float
+    }
+  }
+
+  #[inline]
+  fn int_float_to_latent(self) -> Self::L {
+    let abs = self.abs();
+    let gpi = 1 << Self::MANTISSA_DIGITS;
+    let gpi_float = Self::from_f32(gpi as f32);
+    let abs_int = if abs < gpi_float {
+      abs.to_f32() as Self::L
+    } else {
+      gpi + (abs.to_bits() - gpi_float.to_bits())
+    };
+    if self.is_sign_positive() {
+      Self::L::MID + abs_int
+    } else {
+      // -1 because we need to distinguish -0.0 from +0.0
+      Self::L::MID - 1 - abs_int
+    }
+  }
 
+  #[inline]
+  fn from_latent_numerical(l: Self::L) -> Self {
+    Self::from_f32(l as f32)
+  }
+}
+
+macro_rules! impl_float_number {
+  ($t: ty, $latent: ty, $sign_bit_mask: expr, $header_byte: expr) => {
     impl NumberLike for $t {
       const DTYPE_BYTE: u8 = $header_byte;
       const TRANSMUTABLE_TO_LATENT: bool = true;
@@ -164,17 +358,35 @@ macro_rules! impl_float_number {
         match (mode, latent_var_idx, delta_encoding_order) {
           (Classic, 0, 0) => Self::from_latent_ordered(l).to_string(),
           (Classic, 0, _) => format_delta(l, " ULPs"),
-          (FloatMult(_), 0, 0) => format!("{}x", Self::int_float_from_latent(l)),
-          (FloatMult(_), 0, _) => format_delta(l, "x"),
+          (FloatMult(_) | LossyFloatMult(_), 0, 0) => {
+            format!("{}x", Self::int_float_from_latent(l))
+          }
+          (FloatMult(_) | LossyFloatMult(_), 0, _) => format_delta(l, "x"),
           (FloatMult(_), 1, _) => format_delta(l, " ULPs"),
+          (Dictionary, 0, 0) => format!("#{}", l),
+          (Dictionary, 0, _) => format_delta(l, ""),
+          (RunLength, 0, 0) => Self::from_latent_ordered(l).to_string(),
+          (RunLength, 0, _) => format_delta(l, " ULPs"),
+          (RunLength, 1, _) => l.to_u64().saturating_add(1).to_string(),
+          (Lpc, 0, _) => format_delta(l, " ULPs"),
+          (Decimal(exponent), 0, 0) => {
+            format!(
+              "{}e-{}",
+              Self::int_float_from_latent(l),
+              exponent
+            )
+          }
+          (Decimal(exponent), 0, _) => format_delta(l, &format!("e-{}", exponent)),
+          (Decimal(_), 1, _) => format_delta(l, " ULPs"),
           _ => panic!("invalid context for latent"),
         }
       }
 
       fn mode_is_valid(mode: Mode<Self::L>) -> bool {
         match mode {
-          Mode::Classic => true,
-          Mode::FloatMult(base_latent) => {
+          Mode::Classic | Mode::Dictionary | Mode::RunLength | Mode::Lpc => true,
+          Mode::Decimal(exponent) => exponent <= MAX_DECIMAL_EXPONENT,
+          Mode::FloatMult(base_latent) | Mode::LossyFloatMult(base_latent) => {
             Self::from_latent_ordered(base_latent).is_finite_and_normal()
           }
           _ => false,
@@ -208,13 +420,26 @@ macro_rules! impl_float_number {
           mem_layout ^ $sign_bit_mask
         }
       }
+      #[inline]
+      fn to_f64_lossy(self) -> f64 {
+        FloatLike::to_f64(self)
+      }
       fn join_latents(mode: Mode<Self::L>, primary: &mut [Self::L], secondary: &[Self::L]) {
         match mode {
-          Mode::Classic => (),
+          // dictionary lookups happen before this on the latent level, and
+          // run length and linear prediction modes don't join latents
+          Mode::Classic | Mode::Dictionary | Mode::RunLength | Mode::Lpc => (),
           Mode::FloatMult(base_latent) => {
             let base = Self::from_latent_ordered(base_latent);
             float_mult_utils::join_latents(base, primary, secondary)
           }
+          Mode::LossyFloatMult(base_latent) => {
+            let base = Self::from_latent_ordered(base_latent);
+            float_mult_utils::join_latents_lossy(base, primary)
+          }
+          Mode::Decimal(exponent) => {
+            decimal_utils::join_latents::<Self>(exponent, primary, secondary)
+          }
           _ => unreachable!("impossible mode for floats"),
         }
       }
@@ -229,8 +454,9 @@ macro_rules! impl_float_number {
   };
 }
 
-impl_float_number!(f32, u32, 32, 1_u32 << 31, 5, -127);
-impl_float_number!(f64, u64, 64, 1_u64 << 63, 6, -1023);
+impl_float_number!(f32, u32, 1_u32 << 31, 5);
+impl_float_number!(f64, u64, 1_u64 << 63, 6);
+impl_float_number!(f16, u16, 1_u16 << 15, 9);
 
 #[cfg(test)]
 mod tests {
@@ -251,6 +477,8 @@ mod tests {
     assert_eq!(3.3333_f32.exponent(), 1);
     assert_eq!(0.3333_f32.exponent(), -2);
     assert_eq!(31.0_f32.exponent(), 4);
+    assert_eq!(f16::ONE.exponent(), 0);
+    assert_eq!(f16::from_f32(0.3333).exponent(), -2);
   }
 
   #[test]
@@ -307,6 +535,33 @@ mod tests {
     }
   }
 
+  #[test]
+  fn int_float16_invertibility() {
+    for x in [
+      -f16::NAN,
+      f16::NEG_INFINITY,
+      f16::MIN,
+      -f16::ONE,
+      f16::NEG_ZERO,
+      f16::ZERO,
+      f16::from_f32(3.0),
+      f16::from_f32(4097.0),
+      f16::MAX,
+      f16::INFINITY,
+      f16::NAN,
+    ] {
+      let int = x.int_float_to_latent();
+      let recovered = f16::int_float_from_latent(int);
+      assert_eq!(
+        x.to_bits(),
+        recovered.to_bits(),
+        "{} != {}",
+        x,
+        recovered
+      );
+    }
+  }
+
   #[test]
   fn int_float_ordering() {
     let values = vec![
@@ -142,7 +142,7 @@ pub trait NumberLike: Copy + Debug + Display + Default + PartialEq + Send + Sync
   ///
   /// To choose a header byte for a new data type, review all header bytes in
   /// the library and pick an unused one. For instance, as of writing, bytes
-  /// 1 through 6 are used, so 7 would be a good choice for another
+  /// 1 through 11 are used, so 12 would be a good choice for another
   /// `pco` data type implementation.
   const DTYPE_BYTE: u8;
   /// If true, decompressors write the primary latent stream to `dst` directly
@@ -171,6 +171,10 @@ pub trait NumberLike: Copy + Debug + Display + Default + PartialEq + Send + Sync
 
   fn from_latent_ordered(l: Self::L) -> Self;
   fn to_latent_ordered(self) -> Self::L;
+  /// Converts the number to the nearest `f64`, which may lose precision.
+  ///
+  /// This is used for computing sums during aggregation.
+  fn to_f64_lossy(self) -> f64;
   fn join_latents(mode: Mode<Self::L>, primary: &mut [Self::L], secondary: &[Self::L]);
 
   fn transmute_to_latents(_slice: &mut [Self]) -> &mut [Self::L] {
@@ -23,7 +23,7 @@ macro_rules! impl_signed {
 
       fn mode_is_valid(mode: Mode<Self::L>) -> bool {
         match mode {
-          Mode::Classic => true,
+          Mode::Classic | Mode::Dictionary | Mode::RunLength | Mode::Lpc => true,
           Mode::IntMult(_) => true,
           _ => false,
         }
@@ -43,9 +43,15 @@ macro_rules! impl_signed {
       fn to_latent_ordered(self) -> Self::L {
         self.wrapping_sub(Self::MIN) as $latent
       }
+      #[inline]
+      fn to_f64_lossy(self) -> f64 {
+        self as f64
+      }
       fn join_latents(mode: Mode<Self::L>, primary: &mut [Self::L], secondary: &[Self::L]) {
         match mode {
-          Mode::Classic => (),
+          // dictionary lookups happen before this on the latent level, and
+          // run length and linear prediction modes don't join latents
+          Mode::Classic | Mode::Dictionary | Mode::RunLength | Mode::Lpc => (),
           Mode::IntMult(base) => int_mult_utils::join_latents(base, primary, secondary),
           _ => unreachable!("impossible mode for signed ints"),
         }
@@ -63,6 +69,8 @@ macro_rules! impl_signed {
 
 impl_signed!(i32, u32, 3);
 impl_signed!(i64, u64, 4);
+impl_signed!(i16, u16, 8);
+impl_signed!(i128, u128, 11);
 
 #[cfg(test)]
 mod tests {
@@ -74,5 +82,11 @@ mod tests {
     assert_eq!((-1_i32).to_latent_ordered(), u32::MID - 1);
     assert_eq!(0_i32.to_latent_ordered(), u32::MID);
     assert_eq!(i32::MAX.to_latent_ordered(), u32::MAX);
+    assert_eq!(i16::MIN.to_latent_ordered(), 0_u16);
+    assert_eq!(0_i16.to_latent_ordered(), u16::MID);
+    assert_eq!(i16::MAX.to_latent_ordered(), u16::MAX);
+    assert_eq!(i128::MIN.to_latent_ordered(), 0_u128);
+    assert_eq!((-1_i128).to_latent_ordered(), u128::MID - 1);
+    assert_eq!(i128::MAX.to_latent_ordered(), u128::MAX);
   }
 }
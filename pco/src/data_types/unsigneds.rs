@@ -25,7 +25,11 @@ pub fn latent_to_string<T: NumberLike>(
       let relative_to_0 = l.wrapping_sub(latent_0 / base);
       T::from_latent_ordered(latent_0.wrapping_add(relative_to_0)).to_string()
     }
-    (Classic, 0, _) | (IntMult(_), 0, _) => format_as_signed(),
+    (Dictionary, 0, 0) => format!("#{}", l),
+    (RunLength, 0, 0) => T::from_latent_ordered(l).to_string(),
+    (Classic, 0, _) | (IntMult(_), 0, _) | (Dictionary, 0, _) | (RunLength, 0, _) | (Lpc, 0, _) => {
+      format_as_signed()
+    }
     (IntMult(base), 1, _) => {
       let latent_0_rem = T::default().to_latent_ordered() % base;
       if l < latent_0_rem {
@@ -34,6 +38,7 @@ pub fn latent_to_string<T: NumberLike>(
         (l - latent_0_rem).to_string()
       }
     }
+    (RunLength, 1, _) => l.to_u64().saturating_add(1).to_string(),
     _ => panic!("invalid context for latent"),
   }
 }
@@ -101,8 +106,10 @@ macro_rules! impl_latent {
   };
 }
 
+impl_latent!(u16);
 impl_latent!(u32);
 impl_latent!(u64);
+impl_latent!(u128);
 
 macro_rules! impl_unsigned_number {
   ($t: ty, $header_byte: expr) => {
@@ -123,7 +130,7 @@ macro_rules! impl_unsigned_number {
 
       fn mode_is_valid(mode: Mode<Self::L>) -> bool {
         match mode {
-          Mode::Classic => true,
+          Mode::Classic | Mode::Dictionary | Mode::RunLength | Mode::Lpc => true,
           Mode::IntMult(_) => true,
           _ => false,
         }
@@ -143,9 +150,15 @@ macro_rules! impl_unsigned_number {
       fn to_latent_ordered(self) -> Self::L {
         self
       }
+      #[inline]
+      fn to_f64_lossy(self) -> f64 {
+        self as f64
+      }
       fn join_latents(mode: Mode<Self::L>, primary: &mut [Self::L], secondary: &[Self::L]) {
         match mode {
-          Mode::Classic => (),
+          // dictionary lookups happen before this on the latent level, and
+          // run length and linear prediction modes don't join latents
+          Mode::Classic | Mode::Dictionary | Mode::RunLength | Mode::Lpc => (),
           Mode::IntMult(base) => int_mult_utils::join_latents(base, primary, secondary),
           _ => unreachable!("impossible mode for unsigned ints"),
         }
@@ -163,3 +176,5 @@ macro_rules! impl_unsigned_number {
 
 impl_unsigned_number!(u32, 1);
 impl_unsigned_number!(u64, 2);
+impl_unsigned_number!(u16, 7);
+impl_unsigned_number!(u128, 10);
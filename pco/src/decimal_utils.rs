@@ -0,0 +1,168 @@
+use crate::constants::MAX_DECIMAL_EXPONENT;
+use crate::data_types::{FloatLike, Latent};
+use crate::sampling;
+
+// We pick the exponent that minimizes a crude estimate of bits per number:
+// each exception costs a full ULPs adjustment, and each increment of the
+// exponent makes the integers log2(10) bits wider.
+const LOG2_10: f64 = std::f64::consts::LOG2_10;
+const MAX_EXCEPTION_FREQUENCY: f64 = 0.1;
+// A float mult base within this relative distance of 10^-exponent describes
+// the same grid as the decimal exponent.
+const DECIMAL_BASE_TOLERANCE: f64 = 0.01;
+
+// Powers of ten up to 10^22 are exactly representable as f64s, and dividing
+// an integer by one is correctly rounded, so this is how decimal strings get
+// parsed into floats in the first place.
+pub(crate) fn power_of_ten<F: FloatLike>(exponent: u32) -> F {
+  F::from_f64(10_f64.powi(exponent as i32))
+}
+
+#[inline(never)]
+pub(crate) fn join_latents<F: FloatLike>(exponent: u32, primary: &mut [F::L], secondary: &[F::L]) {
+  let power = power_of_ten::<F>(exponent);
+  for (int_and_dst, &adj) in primary.iter_mut().zip(secondary.iter()) {
+    let unadjusted = F::int_float_from_latent(*int_and_dst) / power;
+    *int_and_dst = unadjusted
+      .to_latent_ordered()
+      .wrapping_add(adj)
+      .toggle_center();
+  }
+}
+
+pub(crate) fn split_latents<F: FloatLike>(page_nums: &[F], exponent: u32) -> Vec<Vec<F::L>> {
+  let power = power_of_ten::<F>(exponent);
+  let n = page_nums.len();
+  let mut primary = Vec::with_capacity(n);
+  let mut adjustments = Vec::with_capacity(n);
+  for &num in page_nums {
+    let int = (num * power).round();
+    primary.push(F::int_float_to_latent(int));
+    // exceptions that don't round trip get a nonzero ULPs adjustment
+    adjustments.push(
+      num
+        .to_latent_ordered()
+        .wrapping_sub((int / power).to_latent_ordered())
+        .toggle_center(),
+    );
+  }
+  vec![primary, adjustments]
+}
+
+fn is_exact<F: FloatLike>(num: F, power: F) -> bool {
+  let int = (num * power).round();
+  // beyond this, consecutive integers aren't representable
+  int.abs() <= F::exp2(F::PRECISION_BITS as i32 + 1)
+    && (int / power).to_latent_ordered() == num.to_latent_ordered()
+}
+
+pub(crate) fn is_decimal_base<F: FloatLike>(base: F, exponent: u32) -> bool {
+  let relative_base = base.to_f64() * power_of_ten::<f64>(exponent);
+  (relative_base - 1.0).abs() < DECIMAL_BASE_TOLERANCE
+}
+
+// Returns the decimal exponent `e` such that most numbers are exactly
+// `int / 10^e`, if there is a useful one.
+// We don't consider numbers that are mostly integers to be decimals, since
+// float mult mode handles them equally well.
+#[inline(never)]
+pub(crate) fn choose_exponent<F: FloatLike>(nums: &[F]) -> Option<u32> {
+  let sample = sampling::choose_sample(nums, |&num| {
+    if num.is_finite_and_normal() {
+      Some(num)
+    } else {
+      None
+    }
+  })?;
+
+  let mut best_exponent = 0;
+  let mut best_exception_freq = 1.0;
+  let mut best_cost = f64::INFINITY;
+  for exponent in 0..MAX_DECIMAL_EXPONENT + 1 {
+    let power = power_of_ten::<F>(exponent);
+    if power.to_f64() != power_of_ten::<f64>(exponent) {
+      // the data type can't represent this power of ten exactly
+      break;
+    }
+
+    let n_exact = sample.iter().filter(|&&num| is_exact(num, power)).count();
+    let exception_freq = 1.0 - n_exact as f64 / sample.len() as f64;
+    let cost = exception_freq * F::BITS as f64 + exponent as f64 * LOG2_10;
+    if cost < best_cost {
+      best_exponent = exponent;
+      best_exception_freq = exception_freq;
+      best_cost = cost;
+    }
+  }
+
+  if best_exponent > 0 && best_exception_freq <= MAX_EXCEPTION_FREQUENCY {
+    Some(best_exponent)
+  } else {
+    None
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::data_types::NumberLike;
+
+  #[test]
+  fn test_split_join() {
+    let nums = vec![
+      12.37_f64,
+      0.005,
+      -3.1,
+      0.0,
+      -0.0,
+      1.0 / 3.0,
+      f64::NAN,
+      f64::NEG_INFINITY,
+      1E300,
+    ];
+    let latents = split_latents(&nums, 3);
+    // the first 5 numbers round trip without adjustment
+    for &adj in &latents[1][..5] {
+      assert_eq!(adj, u64::MID);
+    }
+    assert_eq!(
+      f64::int_float_from_latent(latents[0][0]),
+      12370.0
+    );
+
+    let mut primary = latents[0].clone();
+    join_latents::<f64>(3, &mut primary, &latents[1]);
+    for (&latent, &num) in primary.iter().zip(&nums) {
+      assert_eq!(latent, num.to_latent_ordered());
+    }
+  }
+
+  #[test]
+  fn test_choose_exponent() {
+    // prices with varying precision
+    let nums = (0..1000)
+      .map(|i| match i % 3 {
+        0 => (i as f64) / 10.0,
+        1 => (i as f64) / 100.0,
+        _ => (i as f64) / 1000.0,
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(choose_exponent(&nums), Some(3));
+
+    // integers aren't decimals
+    let nums = (0..1000).map(|i| i as f32).collect::<Vec<_>>();
+    assert_eq!(choose_exponent(&nums), None);
+
+    // nor are arbitrary floats
+    let nums = (0..1000).map(|i| (i as f64).sqrt()).collect::<Vec<_>>();
+    assert_eq!(choose_exponent(&nums), None);
+  }
+
+  #[test]
+  fn test_is_decimal_base() {
+    assert!(is_decimal_base(0.01_f32, 2));
+    assert!(is_decimal_base(0.0099999_f64, 2));
+    assert!(!is_decimal_base(0.25_f64, 2));
+    assert!(!is_decimal_base(0.1_f64, 2));
+  }
+}
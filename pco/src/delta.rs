@@ -1,13 +1,17 @@
+use std::cmp::min;
 use std::io::Write;
 
-use crate::bit_reader::BitReader;
+use better_io::BetterBufRead;
+
+use crate::bit_reader::BitReaderBuilder;
 use crate::bit_writer::BitWriter;
+use crate::constants::FULL_BATCH_N;
 use crate::data_types::Latent;
 use crate::errors::PcoResult;
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct DeltaMoments<L: Latent> {
-  // length = delta encoding order
+  // length = delta encoding order * delta lag
   pub moments: Vec<L>,
 }
 
@@ -16,21 +20,38 @@ impl<L: Latent> DeltaMoments<L> {
     Self { moments }
   }
 
-  pub unsafe fn parse_from(reader: &mut BitReader, order: usize) -> PcoResult<Self> {
-    let mut moments = Vec::new();
-    for _ in 0..order {
-      moments.push(reader.read_uint::<L>(L::BITS));
+  // With lagged delta encoding, there can be too many moments to read or
+  // write at once, so we do them in batches.
+  pub unsafe fn parse_from<R: BetterBufRead>(
+    reader_builder: &mut BitReaderBuilder<R>,
+    n_moments: usize,
+  ) -> PcoResult<Self> {
+    let mut moments = Vec::with_capacity(n_moments);
+    while moments.len() < n_moments {
+      let batch_size = min(n_moments - moments.len(), FULL_BATCH_N);
+      reader_builder.with_reader(|reader| {
+        for _ in 0..batch_size {
+          moments.push(reader.read_uint::<L>(L::BITS));
+        }
+        Ok(())
+      })?;
     }
     Ok(DeltaMoments { moments })
   }
 
-  pub unsafe fn write_to<W: Write>(&self, writer: &mut BitWriter<W>) {
-    for &moment in &self.moments {
-      writer.write_uint(moment, L::BITS);
+  pub unsafe fn write_to<W: Write>(&self, writer: &mut BitWriter<W>) -> PcoResult<()> {
+    for moment_batch in self.moments.chunks(FULL_BATCH_N) {
+      for &moment in moment_batch {
+        writer.write_uint(moment, L::BITS);
+      }
+      writer.flush()?;
     }
+    Ok(())
   }
 
-  pub fn order(&self) -> usize {
+  // This is also the count of latents at the start of each page that don't
+  // need to be encoded as deltas.
+  pub fn n_moments(&self) -> usize {
     self.moments.len()
   }
 }
@@ -47,19 +68,21 @@ pub fn toggle_center_in_place<L: Latent>(latents: &mut [L]) {
   }
 }
 
-fn first_order_encode_in_place<L: Latent>(latents: &mut [L]) {
-  if latents.is_empty() {
-    return;
-  }
-
-  for i in 0..latents.len() - 1 {
-    latents[i] = latents[i + 1].wrapping_sub(latents[i]);
+fn first_order_encode_in_place<L: Latent>(latents: &mut [L], lag: usize) {
+  for i in 0..latents.len().saturating_sub(lag) {
+    latents[i] = latents[i + lag].wrapping_sub(latents[i]);
   }
 }
 
 // used for a single page, so we return the delta moments
+// Each order of delta encoding takes differences between latents that are
+// `lag` apart, so lag 1 is ordinary delta encoding.
 #[inline(never)]
-pub fn encode_in_place<L: Latent>(mut latents: &mut [L], order: usize) -> DeltaMoments<L> {
+pub fn encode_in_place<L: Latent>(
+  mut latents: &mut [L],
+  order: usize,
+  lag: usize,
+) -> DeltaMoments<L> {
   // TODO this function could be made faster by doing all steps on mini batches
   // of ~512 at a time
   if order == 0 {
@@ -67,12 +90,14 @@ pub fn encode_in_place<L: Latent>(mut latents: &mut [L], order: usize) -> DeltaM
     return DeltaMoments::default();
   }
 
-  let mut page_moments = Vec::with_capacity(order);
+  let mut page_moments = Vec::with_capacity(order * lag);
   for _ in 0..order {
-    page_moments.push(latents.first().copied().unwrap_or(L::ZERO));
+    for i in 0..lag {
+      page_moments.push(latents.get(i).copied().unwrap_or(L::ZERO));
+    }
 
-    first_order_encode_in_place(latents);
-    let truncated_len = latents.len().saturating_sub(1);
+    first_order_encode_in_place(latents, lag);
+    let truncated_len = latents.len().saturating_sub(lag);
     latents = &mut latents[0..truncated_len];
   }
   toggle_center_in_place(latents);
@@ -88,17 +113,41 @@ fn first_order_decode_in_place<L: Latent>(moment: &mut L, latents: &mut [L]) {
   }
 }
 
+// The moments here are the next `lag` latents, in order.
+fn lagged_first_order_decode_in_place<L: Latent>(moments: &mut [L], latents: &mut [L]) {
+  let lag = moments.len();
+  for latent_chunk in latents.chunks_mut(lag) {
+    for (delta, moment) in latent_chunk.iter_mut().zip(moments.iter_mut()) {
+      let tmp = *delta;
+      *delta = *moment;
+      *moment = moment.wrapping_add(tmp);
+    }
+  }
+  // keep the moments in order for the next batch
+  moments.rotate_left(latents.len() % lag);
+}
+
 // used for a single batch, so we mutate the delta moments
 #[inline(never)]
-pub fn decode_in_place<L: Latent>(delta_moments: &mut DeltaMoments<L>, latents: &mut [L]) {
-  if delta_moments.order() == 0 {
+pub fn decode_in_place<L: Latent>(
+  delta_moments: &mut DeltaMoments<L>,
+  lag: usize,
+  latents: &mut [L],
+) {
+  if delta_moments.n_moments() == 0 {
     // exit early so we don't toggle to signed values
     return;
   }
 
   toggle_center_in_place(latents);
-  for moment in delta_moments.moments.iter_mut().rev() {
-    first_order_decode_in_place(moment, latents);
+  if lag == 1 {
+    for moment in delta_moments.moments.iter_mut().rev() {
+      first_order_decode_in_place(moment, latents);
+    }
+  } else {
+    for moments in delta_moments.moments.chunks_mut(lag).rev() {
+      lagged_first_order_decode_in_place(moments, latents);
+    }
   }
 }
 
@@ -112,17 +161,38 @@ mod tests {
     let mut deltas = orig_latents.to_vec();
     let order = 2;
     let zero_delta = u32::MID;
-    let mut moments = encode_in_place(&mut deltas, order);
+    let mut moments = encode_in_place(&mut deltas, order, 1);
 
     // add back some padding we lose during compression
     for _ in 0..order {
       deltas.push(zero_delta);
     }
 
-    decode_in_place::<u32>(&mut moments, &mut deltas[..3]);
+    decode_in_place::<u32>(&mut moments, 1, &mut deltas[..3]);
     assert_eq!(&deltas[..3], &orig_latents[..3]);
 
-    decode_in_place::<u32>(&mut moments, &mut deltas[3..]);
+    decode_in_place::<u32>(&mut moments, 1, &mut deltas[3..]);
     assert_eq!(&deltas[3..5], &orig_latents[3..5]);
   }
+
+  #[test]
+  fn test_lagged_delta_encode_decode() {
+    let orig_latents: Vec<u32> = (0..20).map(|i| (i % 3) * 100 + i).collect();
+    let mut deltas = orig_latents.to_vec();
+    let order = 2;
+    let lag = 3;
+    let mut moments = encode_in_place(&mut deltas, order, lag);
+    assert_eq!(moments.n_moments(), order * lag);
+    // each lag-3 second order delta of this sequence is 0
+    assert!(deltas[..14].iter().all(|&delta| delta == u32::MID));
+
+    // decode in batches whose sizes aren't multiples of the lag
+    for (start, end) in [(0, 4), (4, 11), (11, 20)] {
+      decode_in_place::<u32>(&mut moments, lag, &mut deltas[start..end]);
+      assert_eq!(
+        &deltas[start..end],
+        &orig_latents[start..end]
+      );
+    }
+  }
 }
@@ -0,0 +1,88 @@
+use std::cmp::min;
+
+use crate::constants::Bitlen;
+use crate::data_types::Latent;
+
+// We look for a lag in a contiguous window at the start of the chunk, since a
+// random sample wouldn't preserve the distances between latents.
+const MAX_WINDOW_SIZE: usize = 4096;
+const MAX_AUTO_DELTA_LAG: usize = 256;
+// the window should contain a few periods of any lag we choose
+const MIN_PERIODS_PER_WINDOW: usize = 4;
+const REQUIRED_BITS_SAVED_PER_NUM: f64 = 0.5;
+// Multiples of the true period work about as well as the period itself, so
+// we prefer the shortest lag that is nearly as good as the best one.
+// Shorter lags also need fewer delta moments per page.
+const SHORTER_LAG_TOLERANCE_BITS: f64 = 0.1;
+
+fn delta_bits<L: Latent>(x: L, y: L) -> Bitlen {
+  let delta = y.wrapping_sub(x);
+  let magnitude = min(delta, L::ZERO.wrapping_sub(delta));
+  L::BITS - magnitude.leading_zeros()
+}
+
+// a rough proxy for how many bits each delta would take to encode
+fn avg_delta_bits<L: Latent>(window: &[L], lag: usize) -> f64 {
+  let total_bits: u64 = window
+    .iter()
+    .zip(&window[lag..])
+    .map(|(&x, &y)| delta_bits(x, y) as u64)
+    .sum();
+  total_bits as f64 / (window.len() - lag) as f64
+}
+
+pub(crate) fn choose_lag<L: Latent>(latents: &[L]) -> Option<usize> {
+  let window = &latents[..min(latents.len(), MAX_WINDOW_SIZE)];
+  let max_lag = min(
+    MAX_AUTO_DELTA_LAG,
+    window.len() / MIN_PERIODS_PER_WINDOW,
+  );
+  if max_lag < 2 {
+    return None;
+  }
+
+  // bits_by_lag[i] is for lag i + 1
+  let bits_by_lag = (1..max_lag + 1)
+    .map(|lag| avg_delta_bits(window, lag))
+    .collect::<Vec<_>>();
+  let best_bits = bits_by_lag[1..]
+    .iter()
+    .cloned()
+    .fold(f64::INFINITY, f64::min);
+  if best_bits > bits_by_lag[0] - REQUIRED_BITS_SAVED_PER_NUM {
+    return None;
+  }
+
+  (2..max_lag + 1).find(|&lag| bits_by_lag[lag - 1] <= best_bits + SHORTER_LAG_TOLERANCE_BITS)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_choose_lag_periodic() {
+    let latents = (0..2000)
+      .map(|i| ((i % 96) * 1000 + i / 96) as u32)
+      .collect::<Vec<_>>();
+    assert_eq!(choose_lag(&latents), Some(96));
+  }
+
+  #[test]
+  fn test_choose_lag_interleaved() {
+    // 3 channels, each trending slowly in a different range
+    let latents = (0..3000_u64)
+      .map(|i| (i % 3) * 1_000_000 + i / 3)
+      .collect::<Vec<_>>();
+    assert_eq!(choose_lag(&latents), Some(3));
+  }
+
+  #[test]
+  fn test_choose_lag_none() {
+    // smooth data is best with ordinary delta encoding
+    let latents = (0..2000_u32).map(|i| i * 7).collect::<Vec<_>>();
+    assert_eq!(choose_lag(&latents), None);
+    // too few latents
+    assert_eq!(choose_lag(&[1_u32, 5, 1, 5, 1]), None);
+  }
+}
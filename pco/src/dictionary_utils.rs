@@ -0,0 +1,93 @@
+use std::collections::HashSet;
+
+use crate::constants::MAX_DICTIONARY_SIZE;
+use crate::data_types::Latent;
+use crate::errors::{PcoError, PcoResult};
+use crate::sampling;
+
+#[inline(never)]
+pub(crate) fn join_latents<L: Latent>(dictionary: &[L], primary: &mut [L]) -> PcoResult<()> {
+  let size = L::from_u64(dictionary.len() as u64);
+  for idx_and_dst in primary.iter_mut() {
+    if *idx_and_dst >= size {
+      return Err(PcoError::corruption(format!(
+        "dictionary index {} is out of bounds for dictionary of size {}",
+        idx_and_dst, size,
+      )));
+    }
+    *idx_and_dst = dictionary[idx_and_dst.to_u64() as usize];
+  }
+  Ok(())
+}
+
+pub(crate) fn split_latents<L: Latent>(dictionary: &[L], latents: &[L]) -> Vec<Vec<L>> {
+  let indices = latents
+    .iter()
+    .map(|latent| {
+      // every latent is in the dictionary by construction
+      let idx = dictionary.binary_search(latent).unwrap();
+      L::from_u64(idx as u64)
+    })
+    .collect();
+  vec![indices]
+}
+
+fn count_distinct_up_to<L: Latent>(latents: &[L], limit: usize) -> Option<HashSet<L>> {
+  let mut distinct = HashSet::new();
+  for &latent in latents {
+    distinct.insert(latent);
+    if distinct.len() > limit {
+      return None;
+    }
+  }
+  Some(distinct)
+}
+
+// Returns the sorted distinct latents if there are few enough of them for
+// dictionary mode to be worth considering.
+pub(crate) fn choose_dictionary<L: Latent>(latents: &[L]) -> Option<Vec<L>> {
+  // check a sample first so we can quickly rule out high-cardinality data
+  let sample = sampling::choose_sample(latents, |&latent| Some(latent))?;
+  count_distinct_up_to(&sample, MAX_DICTIONARY_SIZE)?;
+
+  let distinct = count_distinct_up_to(latents, MAX_DICTIONARY_SIZE)?;
+  if distinct.len() <= 1 {
+    // classic mode already handles constants trivially
+    return None;
+  }
+
+  let mut dictionary = distinct.into_iter().collect::<Vec<_>>();
+  dictionary.sort_unstable();
+  Some(dictionary)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_split_join_latents() -> PcoResult<()> {
+    let latents = [700_u32, 5, 5, 1 << 30, 700, 5];
+    let nums = latents.repeat(10);
+    let dictionary = choose_dictionary(&nums).unwrap();
+    assert_eq!(dictionary, vec![5, 700, 1 << 30]);
+
+    let mut split = split_latents(&dictionary, &nums);
+    assert_eq!(split.len(), 1);
+    assert_eq!(&split[0][..6], &[1, 0, 0, 2, 1, 0]);
+
+    join_latents(&dictionary, &mut split[0])?;
+    assert_eq!(split[0], nums);
+
+    let mut corrupt_indices = vec![0, 2, 3];
+    assert!(join_latents(&dictionary, &mut corrupt_indices).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_choose_dictionary_high_cardinality() {
+    let latents = (0..2 * MAX_DICTIONARY_SIZE as u32).collect::<Vec<_>>();
+    assert_eq!(choose_dictionary(&latents), None);
+    assert_eq!(choose_dictionary(&[7_u64; 100]), None);
+  }
+}
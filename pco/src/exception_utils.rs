@@ -0,0 +1,167 @@
+use crate::data_types::{Latent, NumberLike};
+use crate::sampling;
+
+// Exceptions are rare outliers, like sentinel values (-9999 or u64::MAX),
+// that would otherwise force wide bins on the rest of the chunk and throw off
+// mode detection. We pull them out of the chunk, compress the chunk as if
+// they were copies of the previous number, and patch them back in after
+// decompression.
+
+// We model the bulk of the data as the range between these quantiles.
+const BULK_QUANTILE: f64 = 0.01;
+// Numbers farther than 2^this times the bulk's spread away from the bulk
+// are exceptions.
+const OUTLIER_SPREAD_MULT_LOG: u32 = 4;
+// Exceptions are expensive, so we give up if there are too many of them.
+const MAX_EXCEPTION_FREQUENCY: f64 = 0.005;
+
+/// An outlier stored in a page as a (position, value) pair instead of being
+/// compressed along with the rest of the page.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Exception<L: Latent> {
+  // position within the page
+  pub idx: usize,
+  pub latent: L,
+}
+
+fn outlier_bounds<L: Latent>(latents: &[L]) -> Option<(L, L)> {
+  let mut sample = sampling::choose_sample(latents, |&latent| Some(latent))?;
+  sample.sort_unstable();
+  let quantile_idx = (sample.len() as f64 * BULK_QUANTILE) as usize;
+  let lo = sample[quantile_idx];
+  let hi = sample[sample.len() - 1 - quantile_idx];
+  let spread = hi - lo;
+  let margin = if spread.leading_zeros() > OUTLIER_SPREAD_MULT_LOG {
+    spread << OUTLIER_SPREAD_MULT_LOG
+  } else {
+    L::MAX
+  };
+  let lower = if lo > margin { lo - margin } else { L::ZERO };
+  let upper = if L::MAX - hi > margin {
+    hi + margin
+  } else {
+    L::MAX
+  };
+  Some((lower, upper))
+}
+
+// Returns the indices of outliers in the chunk, if there are a few of them.
+#[inline(never)]
+pub(crate) fn choose_exception_idxs<L: Latent>(latents: &[L]) -> Option<Vec<usize>> {
+  let (lower, upper) = outlier_bounds(latents)?;
+  let max_n_exceptions = (latents.len() as f64 * MAX_EXCEPTION_FREQUENCY) as usize;
+  let mut idxs = Vec::new();
+  for (idx, &latent) in latents.iter().enumerate() {
+    if latent < lower || latent > upper {
+      if idxs.len() == max_n_exceptions {
+        return None;
+      }
+      idxs.push(idx);
+    }
+  }
+
+  if idxs.is_empty() {
+    None
+  } else {
+    Some(idxs)
+  }
+}
+
+// Splits the exceptions into pages, with positions relative to each page.
+pub(crate) fn exceptions_per_page<T: NumberLike>(
+  nums: &[T],
+  exception_idxs: &[usize],
+  n_per_page: &[usize],
+) -> Vec<Vec<Exception<T::L>>> {
+  let mut res = vec![Vec::new(); n_per_page.len()];
+  let mut page_idx = 0;
+  let mut page_start = 0;
+  for &idx in exception_idxs {
+    while idx >= page_start + n_per_page[page_idx] {
+      page_start += n_per_page[page_idx];
+      page_idx += 1;
+    }
+    res[page_idx].push(Exception {
+      idx: idx - page_start,
+      latent: nums[idx].to_latent_ordered(),
+    });
+  }
+  res
+}
+
+// Returns a copy of the numbers with each exception replaced by the previous
+// non-exception number.
+pub(crate) fn patch<T: NumberLike>(nums: &[T], exception_idxs: &[usize]) -> Vec<T> {
+  // Exceptions are sparse, so there is always a non-exception to fill with.
+  // Leading exceptions get filled with the first non-exception.
+  let first_fill_idx = exception_idxs
+    .iter()
+    .enumerate()
+    .take_while(|&(i, &idx)| i == idx)
+    .count();
+  let mut fill = nums[first_fill_idx];
+  let mut patched = nums.to_vec();
+  let mut exception_idxs = exception_idxs.iter().peekable();
+  for (idx, num) in patched.iter_mut().enumerate() {
+    if exception_idxs.next_if_eq(&&idx).is_some() {
+      *num = fill;
+    } else {
+      fill = *num;
+    }
+  }
+  patched
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_choose_exception_idxs() {
+    let mut latents = (0..2000_u32).map(|i| 100_000 + i % 100).collect::<Vec<_>>();
+    assert_eq!(choose_exception_idxs(&latents), None);
+
+    latents[5] = u32::MAX;
+    latents[1234] = 0;
+    assert_eq!(
+      choose_exception_idxs(&latents),
+      Some(vec![5, 1234])
+    );
+
+    // too many to be exceptions
+    for latent in latents.iter_mut().step_by(50) {
+      *latent = u32::MAX;
+    }
+    assert_eq!(choose_exception_idxs(&latents), None);
+  }
+
+  #[test]
+  fn test_patch_and_split() {
+    let nums = vec![-9999_i32, 1, 2, -9999, -9999, 5, 6];
+    let exception_idxs = [0, 3, 4];
+    assert_eq!(
+      patch(&nums, &exception_idxs),
+      vec![1, 1, 2, 2, 2, 5, 6]
+    );
+    let sentinel = (-9999_i32).to_latent_ordered();
+    assert_eq!(
+      exceptions_per_page(&nums, &exception_idxs, &[4, 3]),
+      vec![
+        vec![
+          Exception {
+            idx: 0,
+            latent: sentinel
+          },
+          Exception {
+            idx: 3,
+            latent: sentinel
+          },
+        ],
+        vec![Exception {
+          idx: 0,
+          latent: sentinel
+        }],
+      ]
+    );
+  }
+}
@@ -16,6 +16,13 @@ pub(crate) fn join_latents<F: FloatLike>(base: F, primary: &mut [F::L], secondar
   }
 }
 
+#[inline(never)]
+pub(crate) fn join_latents_lossy<F: FloatLike>(base: F, primary: &mut [F::L]) {
+  for mult_and_dst in primary.iter_mut() {
+    *mult_and_dst = (F::int_float_from_latent(*mult_and_dst) * base).to_latent_ordered();
+  }
+}
+
 pub(crate) fn split_latents<F: FloatLike>(page_nums: &[F], base: F, inv_base: F) -> Vec<Vec<F::L>> {
   let n = page_nums.len();
   let uninit_vec = || unsafe {
@@ -41,6 +48,14 @@ pub(crate) fn split_latents<F: FloatLike>(page_nums: &[F], base: F, inv_base: F)
   vec![primary, adjustments]
 }
 
+pub(crate) fn split_latents_lossy<F: FloatLike>(page_nums: &[F], inv_base: F) -> Vec<Vec<F::L>> {
+  let primary = page_nums
+    .iter()
+    .map(|&num| F::int_float_to_latent((num * inv_base).round()))
+    .collect();
+  vec![primary]
+}
+
 // The rest of this file concerns automatically detecting the float `base`
 // such that `x = mult * base + adj * ULP` usefully splits a delta `x` into
 // latent variables `mult` and `adj` (if such a `base` exists).
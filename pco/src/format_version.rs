@@ -46,4 +46,40 @@ impl FormatVersion {
   pub(crate) fn used_old_gcds(&self) -> bool {
     self.0 == 0
   }
+
+  pub(crate) fn supports_delta_lag(&self) -> bool {
+    self.0 >= 2
+  }
+
+  pub(crate) fn supports_exceptions(&self) -> bool {
+    self.0 >= 3
+  }
+
+  pub(crate) fn supports_seek_index(&self) -> bool {
+    self.0 >= 4
+  }
+
+  pub(crate) fn supports_value_bounds(&self) -> bool {
+    self.0 >= 5
+  }
+
+  pub(crate) fn supports_lossy_float_mult(&self) -> bool {
+    self.0 >= 6
+  }
+
+  pub(crate) fn supports_dictionary(&self) -> bool {
+    self.0 >= 7
+  }
+
+  pub(crate) fn supports_run_length(&self) -> bool {
+    self.0 >= 8
+  }
+
+  pub(crate) fn supports_lpc(&self) -> bool {
+    self.0 >= 9
+  }
+
+  pub(crate) fn supports_decimal(&self) -> bool {
+    self.0 >= 10
+  }
 }
@@ -205,6 +205,10 @@ impl<L: Latent> LatentBatchDecompressor<L> {
     }
   }
 
+  pub fn set_ans_state_idxs(&mut self, state_idxs: [AnsState; ANS_INTERLEAVING]) {
+    self.state.state_idxs = state_idxs;
+  }
+
   // If hits a corruption, it returns an error and leaves reader and self unchanged.
   // May contaminate dst.
   pub unsafe fn decompress_latent_batch(
@@ -116,6 +116,7 @@ impl<'a, L: Latent> LatentBatchDissector<'a, L> {
       offsets,
       offset_bits,
       ans_final_states,
+      ..
     } = dst;
 
     let search_idxs = self.binary_search(latents);
@@ -37,11 +37,17 @@
 
 #![allow(clippy::uninit_vec)]
 
+pub use aggregation::{Agg, AggValue};
 pub use auto::auto_delta_encoding_order;
 pub use bin::Bin;
-pub use chunk_config::{ChunkConfig, FloatMultSpec, IntMultSpec, PagingSpec};
-pub use chunk_meta::{ChunkLatentVarMeta, ChunkMeta};
-pub use constants::{DEFAULT_COMPRESSION_LEVEL, DEFAULT_MAX_PAGE_N, FULL_BATCH_N};
+pub use chunk_config::{
+  ChunkConfig, DecimalSpec, DeltaLagSpec, DictionarySpec, ExceptionSpec, FloatMultSpec,
+  IntMultSpec, LossySpec, LpcSpec, PagingSpec, RunLengthSpec, ValueBoundsSpec,
+};
+pub use chunk_meta::{ChunkLatentVarMeta, ChunkMeta, HistogramBin};
+pub use constants::{
+  DEFAULT_COMPRESSION_LEVEL, DEFAULT_MAX_CHUNK_N, DEFAULT_MAX_PAGE_N, FULL_BATCH_N,
+};
 pub use mode::Mode;
 pub use progress::Progress;
 
@@ -49,6 +55,8 @@ pub use progress::Progress;
 #[cfg(doctest)]
 struct ReadmeDoctest;
 
+/// for compressing/decompressing several named columns in one file
+pub mod container;
 pub mod data_types;
 pub mod errors;
 /// for compressing/decompressing .pco files
@@ -56,6 +64,7 @@ pub mod standalone;
 /// for compressing/decompressing as part of an outer, wrapping format
 pub mod wrapped;
 
+mod aggregation;
 mod ans;
 mod auto;
 mod bin;
@@ -68,17 +77,24 @@ mod chunk_meta;
 mod compression_intermediates;
 mod compression_table;
 mod constants;
+mod decimal_utils;
 mod delta;
+mod delta_lag_utils;
+mod dictionary_utils;
+mod exception_utils;
 mod float_mult_utils;
 mod format_version;
 mod histograms;
 mod int_mult_utils;
 mod latent_batch_decompressor;
 mod latent_batch_dissector;
+mod lossy_utils;
+mod lpc_utils;
 mod mode;
 mod page_meta;
 mod progress;
 mod read_write_uint;
+mod run_len_utils;
 mod sampling;
 mod sort_utils;
 
@@ -0,0 +1,89 @@
+use crate::data_types::FloatLike;
+use crate::LossySpec;
+
+// Quantization happens before mode selection, so that the existing machinery
+// (e.g. float mult mode) can take advantage of the reduced precision.
+// Decompression is exact with respect to the quantized values, so the error
+// bound only needs to be checked here.
+
+fn quantize_to_multiple<F: FloatLike>(x: F, step: F, inv_step: F) -> F {
+  (x * inv_step).round() * step
+}
+
+fn quantize_abs<F: FloatLike>(nums: &[F], tolerance: f64) -> Vec<F> {
+  let step = F::from_f64(2.0 * tolerance);
+  let inv_step = step.inv();
+  nums
+    .iter()
+    .map(|&x| {
+      let quantized = quantize_to_multiple(x, step, inv_step);
+      let err = (quantized.to_f64() - x.to_f64()).abs();
+      // NaNs fail this check, so we fall back to the original number
+      if err <= tolerance {
+        quantized
+      } else {
+        x
+      }
+    })
+    .collect()
+}
+
+fn quantize_rel<F: FloatLike>(nums: &[F], tolerance: f64) -> Vec<F> {
+  // Rounding a float to m bits of precision after its leading 1 incurs a
+  // relative error of at most 2^-(m + 1).
+  let precision = (-tolerance.log2() - 1.0).ceil().max(0.0);
+  if precision >= F::PRECISION_BITS as f64 {
+    return nums.to_vec();
+  }
+  let precision = precision as i32;
+
+  nums
+    .iter()
+    .map(|&x| {
+      if !x.is_finite_and_normal() || x == F::ZERO {
+        return x;
+      }
+
+      // powers of 2 give exact multiplication and division
+      let step = F::exp2(x.exponent() - precision);
+      let quantized = quantize_to_multiple(x, step, step.inv());
+      let x_f64 = x.to_f64();
+      let err = (quantized.to_f64() - x_f64).abs();
+      if err <= tolerance * x_f64.abs() {
+        quantized
+      } else {
+        x
+      }
+    })
+    .collect()
+}
+
+pub(crate) fn quantize<F: FloatLike>(nums: &[F], spec: LossySpec) -> Vec<F> {
+  match spec {
+    LossySpec::None => nums.to_vec(),
+    LossySpec::AbsError(tolerance) => quantize_abs(nums, tolerance),
+    LossySpec::RelError(tolerance) => quantize_rel(nums, tolerance),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_quantize_abs() {
+    let nums = vec![0.24_f64, -0.26, 1.0, f64::NAN, f64::INFINITY];
+    let quantized = quantize(&nums, LossySpec::AbsError(0.25));
+    assert_eq!(&quantized[..3], &[0.0, -0.5, 1.0]);
+    assert!(quantized[3].is_nan());
+    assert_eq!(quantized[4], f64::INFINITY);
+  }
+
+  #[test]
+  fn test_quantize_rel() {
+    // 0.1 tolerance keeps 3 bits of precision after the leading 1
+    let nums = vec![1.0_f32, 1.05, 1.1875, -9.5, 0.0];
+    let quantized = quantize(&nums, LossySpec::RelError(0.1));
+    assert_eq!(quantized, vec![1.0, 1.0, 1.25, -10.0, 0.0]);
+  }
+}
@@ -0,0 +1,272 @@
+use std::cmp::min;
+
+use crate::constants::{LPC_PRECISION_BITS, MAX_LPC_ORDER};
+use crate::data_types::Latent;
+use crate::delta::{toggle_center_in_place, DeltaMoments};
+
+// We fit the predictor on a contiguous window at the start of the chunk.
+const MAX_FIT_SIZE: usize = 1 << 14;
+const MIN_FIT_SIZE: usize = 256;
+const REQUIRED_BITS_SAVED_PER_NUM: f64 = 0.5;
+// Higher orders need to improve the fit by at least this much to be worth
+// their extra delta moments and multiplications.
+const BITS_SAVED_PER_ORDER: f64 = 0.05;
+
+// Interprets a wrapped difference between latents as a signed number.
+// For 128-bit latents, this truncates very large differences, which just
+// makes the prediction worse.
+fn latent_to_signed<L: Latent>(l: L) -> i64 {
+  if l >= L::MID {
+    (L::ZERO.wrapping_sub(l).to_u64() as i64).wrapping_neg()
+  } else {
+    l.to_u64() as i64
+  }
+}
+
+fn signed_to_latent<L: Latent>(x: i64) -> L {
+  let magnitude = L::from_u64(x.unsigned_abs());
+  if x < 0 {
+    L::ZERO.wrapping_sub(magnitude)
+  } else {
+    magnitude
+  }
+}
+
+// The predictor is fitted to first differences rather than the latents
+// themselves, which keeps it well-conditioned when the latents have a large
+// offset. So given `history` of the previous k + 1 latents (oldest first),
+// we predict the next first difference from the previous k.
+#[inline]
+fn predict<L: Latent>(coefficients: &[i16], history: &[L]) -> L {
+  let order = coefficients.len();
+  let last = history[order];
+  let mut sum = 0_i128;
+  for (lag_minus_1, &coefficient) in coefficients.iter().enumerate() {
+    let delta = history[order - lag_minus_1].wrapping_sub(history[order - lag_minus_1 - 1]);
+    sum += coefficient as i128 * latent_to_signed(delta) as i128;
+  }
+  let predicted_delta = (sum + (1 << (LPC_PRECISION_BITS - 1))) >> LPC_PRECISION_BITS;
+  last.wrapping_add(signed_to_latent(predicted_delta as i64))
+}
+
+// used for a single page, so we return the delta moments, which are the
+// first k + 1 latents
+#[inline(never)]
+pub fn encode_in_place<L: Latent>(coefficients: &[i16], latents: &mut [L]) -> DeltaMoments<L> {
+  let n_moments = coefficients.len() + 1;
+  let moments = (0..n_moments)
+    .map(|i| latents.get(i).copied().unwrap_or(L::ZERO))
+    .collect::<Vec<_>>();
+
+  let n_residuals = latents.len().saturating_sub(n_moments);
+  for i in 0..n_residuals {
+    let prediction = predict(coefficients, &latents[i..i + n_moments]);
+    latents[i] = latents[i + n_moments].wrapping_sub(prediction);
+  }
+  toggle_center_in_place(&mut latents[..n_residuals]);
+
+  DeltaMoments { moments }
+}
+
+// used for a single batch, so we mutate the delta moments, which always hold
+// the next k + 1 latents
+#[inline(never)]
+pub fn decode_in_place<L: Latent>(
+  coefficients: &[i16],
+  delta_moments: &mut DeltaMoments<L>,
+  latents: &mut [L],
+) {
+  toggle_center_in_place(latents);
+  let history = &mut delta_moments.moments;
+  let order = coefficients.len();
+  for latent in latents.iter_mut() {
+    let next = predict(coefficients, history).wrapping_add(*latent);
+    *latent = history[0];
+    history.rotate_left(1);
+    history[order] = next;
+  }
+}
+
+fn mean_sq_residual(deltas: &[f64], coefficients: &[f64]) -> f64 {
+  let order = coefficients.len();
+  let mut total = 0.0;
+  for i in order..deltas.len() {
+    let mut residual = deltas[i];
+    for (lag_minus_1, &coefficient) in coefficients.iter().enumerate() {
+      residual -= coefficient * deltas[i - lag_minus_1 - 1];
+    }
+    total += residual * residual;
+  }
+  total / (deltas.len() - order) as f64
+}
+
+// solves the least squares normal equations for the given order
+fn fit(deltas: &[f64], order: usize) -> Option<Vec<f64>> {
+  // augmented matrix [R | r]
+  let mut matrix = vec![vec![0.0; order + 1]; order];
+  for i in order..deltas.len() {
+    for p in 0..order {
+      let x = deltas[i - p - 1];
+      for q in 0..order {
+        matrix[p][q] += x * deltas[i - q - 1];
+      }
+      matrix[p][order] += x * deltas[i];
+    }
+  }
+
+  // Gaussian elimination with partial pivoting
+  for col in 0..order {
+    let pivot =
+      (col..order).max_by(|&a, &b| matrix[a][col].abs().total_cmp(&matrix[b][col].abs()))?;
+    if matrix[pivot][col].abs() < f64::MIN_POSITIVE {
+      return None;
+    }
+    matrix.swap(col, pivot);
+    let (upper, lower) = matrix.split_at_mut(col + 1);
+    let pivot_row = &upper[col];
+    for row in lower.iter_mut() {
+      let factor = row[col] / pivot_row[col];
+      for (x, &pivot_x) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+        *x -= factor * pivot_x;
+      }
+    }
+  }
+  let mut res = vec![0.0; order];
+  for row in (0..order).rev() {
+    let mut x = matrix[row][order];
+    for k in row + 1..order {
+      x -= matrix[row][k] * res[k];
+    }
+    res[row] = x / matrix[row][row];
+  }
+  Some(res)
+}
+
+fn quantize(coefficients: &[f64]) -> Option<Vec<i16>> {
+  let scale = (1 << LPC_PRECISION_BITS) as f64;
+  coefficients
+    .iter()
+    .map(|&coefficient| {
+      let quantized = (coefficient * scale).round();
+      if quantized.is_finite() && quantized >= i16::MIN as f64 && quantized <= i16::MAX as f64 {
+        Some(quantized as i16)
+      } else {
+        None
+      }
+    })
+    .collect()
+}
+
+fn dequantize(coefficients: &[i16]) -> Vec<f64> {
+  let scale = (1 << LPC_PRECISION_BITS) as f64;
+  coefficients
+    .iter()
+    .map(|&coefficient| coefficient as f64 / scale)
+    .collect()
+}
+
+// Returns quantized predictor coefficients if a fitted predictor looks
+// substantially better than delta encoding.
+pub(crate) fn choose_coefficients<L: Latent>(latents: &[L]) -> Option<Vec<i16>> {
+  let window = &latents[..min(latents.len(), MAX_FIT_SIZE)];
+  if window.len() < MIN_FIT_SIZE {
+    return None;
+  }
+
+  let deltas = window
+    .windows(2)
+    .map(|pair| latent_to_signed(pair[1].wrapping_sub(pair[0])) as f64)
+    .collect::<Vec<_>>();
+
+  // delta encoding orders 1, 2, and 3 are the predictors [], [1], and [2, -1]
+  let delta_bits = [&[][..], &[1.0], &[2.0, -1.0]]
+    .iter()
+    .map(|coefficients| 0.5 * mean_sq_residual(&deltas, coefficients).log2())
+    .fold(f64::INFINITY, f64::min);
+  if !delta_bits.is_finite() {
+    return None;
+  }
+
+  let mut best: Option<(Vec<i16>, f64)> = None;
+  for order in 1..MAX_LPC_ORDER + 1 {
+    let Some(coefficients) = fit(&deltas, order).and_then(|fitted| quantize(&fitted)) else {
+      continue;
+    };
+    let bits = 0.5 * mean_sq_residual(&deltas, &dequantize(&coefficients)).log2();
+    let is_better = match &best {
+      Some((_, best_bits)) => bits < best_bits - BITS_SAVED_PER_ORDER,
+      None => true,
+    };
+    if is_better {
+      best = Some((coefficients, bits));
+    }
+  }
+
+  match best {
+    Some((coefficients, bits)) if bits < delta_bits - REQUIRED_BITS_SAVED_PER_NUM => {
+      Some(coefficients)
+    }
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn noisy_sinusoid(n: usize) -> Vec<u32> {
+    (0..n)
+      .map(|i| {
+        let x = 1_000_000.0 * (i as f64 * 0.3).sin() + (i * 7 % 5) as f64;
+        (x as i64 + (1 << 31)) as u32
+      })
+      .collect()
+  }
+
+  #[test]
+  fn test_encode_decode() {
+    let orig_latents = noisy_sinusoid(300);
+    let coefficients = [7000_i16, -4000];
+    let mut residuals = orig_latents.clone();
+    let mut moments = encode_in_place(&coefficients, &mut residuals);
+    assert_eq!(moments.n_moments(), 3);
+
+    // decode in batches of uneven sizes
+    for (start, end) in [(0, 2), (2, 100), (100, 300)] {
+      decode_in_place(
+        &coefficients,
+        &mut moments,
+        &mut residuals[start..end],
+      );
+      assert_eq!(
+        &residuals[start..end],
+        &orig_latents[start..end]
+      );
+    }
+  }
+
+  #[test]
+  fn test_encode_decode_wrapping() {
+    let orig_latents = [0_u16, u16::MAX, 3, u16::MAX - 100, 7, 0, 1];
+    let coefficients = [i16::MAX, i16::MIN];
+    let mut residuals = orig_latents.to_vec();
+    let mut moments = encode_in_place(&coefficients, &mut residuals);
+    decode_in_place(&coefficients, &mut moments, &mut residuals);
+    assert_eq!(residuals, orig_latents);
+  }
+
+  #[test]
+  fn test_choose_coefficients() {
+    // the first differences of a sinusoid satisfy
+    // d[i] = 2 cos(0.3) d[i - 1] - d[i - 2]
+    let coefficients = choose_coefficients(&noisy_sinusoid(2000)).unwrap();
+    let fitted = dequantize(&coefficients);
+    assert_eq!(fitted.len(), 2);
+    assert!((fitted[0] - 2.0 * 0.3_f64.cos()).abs() < 0.01);
+    assert!((fitted[1] + 1.0).abs() < 0.01);
+
+    // a linear trend is best with ordinary delta encoding
+    let linear = (0..2000_u32).map(|i| i * 3).collect::<Vec<_>>();
+    assert_eq!(choose_coefficients(&linear), None);
+  }
+}
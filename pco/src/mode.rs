@@ -16,6 +16,24 @@ use crate::data_types::{FloatLike, Latent};
 //   whose outputs get multiplied by the base and perturbed by floating point
 //   errors.
 //
+// LossyFloatMult: The same as FloatMult, except that we deem the floating
+//   point errors unimportant and discard them.
+//
+// Decimal: The data is generated by a smooth distribution whose outputs
+//   are integers divided by a power of ten, as when floats get parsed from
+//   decimal strings. Occasional exceptions are perturbed arbitrarily.
+//
+// Dictionary: The data is drawn from a small set of distinct values that may
+//   be spread far apart, and we model the distribution of their indices
+//   instead.
+//
+// RunLength: The data is generated by a smooth distribution whose outputs
+//   get repeated some number of times, drawn from another distribution.
+//
+// Lpc: The data is generated by a linear autoregressive process, so each
+//   first difference is approximately a fixed linear combination of the
+//   previous few, plus smooth noise.
+//
 // Note the differences between int mult and float mult,
 // which have equivalent formulas.
 
@@ -46,6 +64,60 @@ pub enum Mode<L: Latent> {
   /// Formula: (bin.lower + offset) * mode.base +
   /// (adj_bin.lower + adj_bin.offset) * machine_epsilon
   FloatMult(L),
+  /// Each number is compressed as
+  /// * which bin it's in and
+  /// * the approximate offset in that bin as a multiplier of the base.
+  ///
+  /// This is like [`FloatMult`][Mode::FloatMult], but without the ULPs
+  /// adjustment, so it is lossy.
+  ///
+  /// Formula: (bin.lower + offset) * mode.base
+  LossyFloatMult(L),
+  /// Each number is compressed as
+  /// * which bin it's in,
+  /// * the offset in that bin as an integer multiple of 10^-exponent,
+  /// * which bin the additional ULPs adjustment is in, and
+  /// * the offset in that adjustment bin.
+  ///
+  /// The adjustment is 0 except for exceptions that aren't exactly an
+  /// integer divided by 10^exponent.
+  ///
+  /// Formula: (bin.lower + offset) / 10^exponent +
+  /// (adj_bin.lower + adj_bin.offset) * machine_epsilon
+  Decimal(u32),
+  /// Each number is compressed as
+  /// * which bin its index in the chunk's dictionary is in and
+  /// * the offset in that bin.
+  ///
+  /// The dictionary of sorted, distinct values is stored in
+  /// [`ChunkMeta::dictionary`][crate::ChunkMeta::dictionary].
+  ///
+  /// Formula: dictionary[bin.lower + offset]
+  Dictionary,
+  /// Each run of repeated numbers is compressed as
+  /// * which bin it's in,
+  /// * the offset in that bin,
+  /// * which bin the run length is in, and
+  /// * the offset in that run length bin.
+  ///
+  /// Runs never span multiple pages.
+  ///
+  /// Formula: bin.lower + offset, repeated
+  /// (len_bin.lower + len_bin.offset + 1) times
+  RunLength,
+  /// Each number is compressed as
+  /// * which bin its prediction residual is in and
+  /// * the offset in that bin.
+  ///
+  /// The prediction is a fixed-point linear combination of the previous
+  /// first differences, using the coefficients in
+  /// [`ChunkMeta::lpc_coefficients`][crate::ChunkMeta::lpc_coefficients].
+  /// The first `coefficients.len() + 1` numbers of each page are stored in
+  /// the page metadata.
+  ///
+  /// Formula: x[i - 1] + round(sum_m coefficients\[m\] *
+  /// (x[i - m - 1] - x[i - m - 2]) / 2^12) + bin.lower + offset
+  Lpc,
 }
 
 impl<L: Latent> Mode<L> {
@@ -53,8 +125,8 @@ impl<L: Latent> Mode<L> {
     use Mode::*;
 
     match self {
-      Classic => 1,
-      FloatMult(_) | IntMult(_) => 2,
+      Classic | LossyFloatMult(_) | Dictionary | Lpc => 1,
+      FloatMult(_) | IntMult(_) | RunLength | Decimal(_) => 2,
     }
   }
 
@@ -66,8 +138,15 @@ impl<L: Latent> Mode<L> {
     use Mode::*;
 
     match (self, latent_var_idx) {
-      (Classic, 0) | (FloatMult(_), 0) | (IntMult(_), 0) => delta_order,
-      (FloatMult(_), 1) | (IntMult(_), 1) => 0,
+      (Classic, 0)
+      | (FloatMult(_), 0)
+      | (IntMult(_), 0)
+      | (LossyFloatMult(_), 0)
+      | (Dictionary, 0)
+      | (RunLength, 0)
+      | (Lpc, 0)
+      | (Decimal(_), 0) => delta_order,
+      (FloatMult(_), 1) | (IntMult(_), 1) | (RunLength, 1) | (Decimal(_), 1) => 0,
       _ => unreachable!(
         "unknown latent {:?}/{}",
         self, latent_var_idx
@@ -78,4 +157,8 @@ impl<L: Latent> Mode<L> {
   pub(crate) fn float_mult<F: FloatLike<L = L>>(base: F) -> Self {
     Self::FloatMult(base.to_latent_ordered())
   }
+
+  pub(crate) fn lossy_float_mult<F: FloatLike<L = L>>(base: F) -> Self {
+    Self::LossyFloatMult(base.to_latent_ordered())
+  }
 }
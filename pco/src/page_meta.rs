@@ -1,13 +1,20 @@
+use std::cmp::min;
 use std::io::Write;
 
+use better_io::BetterBufRead;
+
 use crate::ans::AnsState;
-use crate::bit_reader::BitReader;
+use crate::bit_reader::BitReaderBuilder;
 use crate::bit_writer::BitWriter;
-use crate::constants::{Bitlen, ANS_INTERLEAVING};
+use crate::constants::{
+  Bitlen, ANS_INTERLEAVING, BITS_TO_ENCODE_EXCEPTION_IDX, BITS_TO_ENCODE_N_EXCEPTIONS,
+  BITS_TO_ENCODE_N_RUNS, BITS_TO_ENCODE_SEEK_BIT_IDX, FULL_BIN_BATCH_SIZE,
+};
 use crate::data_types::Latent;
 use crate::delta::DeltaMoments;
-use crate::errors::PcoResult;
-use crate::ChunkMeta;
+use crate::errors::{PcoError, PcoResult};
+use crate::exception_utils::Exception;
+use crate::{ChunkMeta, Mode};
 
 #[derive(Clone, Debug)]
 pub struct PageLatentVarMeta<L: Latent> {
@@ -16,25 +23,33 @@ pub struct PageLatentVarMeta<L: Latent> {
 }
 
 impl<L: Latent> PageLatentVarMeta<L> {
-  pub unsafe fn write_to<W: Write>(&self, ans_size_log: Bitlen, writer: &mut BitWriter<W>) {
-    self.delta_moments.write_to(writer);
+  pub unsafe fn write_to<W: Write>(
+    &self,
+    ans_size_log: Bitlen,
+    writer: &mut BitWriter<W>,
+  ) -> PcoResult<()> {
+    self.delta_moments.write_to(writer)?;
 
     // write the final ANS state, moving it down the range [0, table_size)
     for state_idx in self.ans_final_state_idxs {
       writer.write_uint(state_idx, ans_size_log);
     }
+    Ok(())
   }
 
-  pub unsafe fn parse_from(
-    reader: &mut BitReader,
-    delta_order: usize,
+  pub unsafe fn parse_from<R: BetterBufRead>(
+    reader_builder: &mut BitReaderBuilder<R>,
+    n_delta_moments: usize,
     ans_size_log: Bitlen,
   ) -> PcoResult<Self> {
-    let delta_moments = DeltaMoments::parse_from(reader, delta_order)?;
+    let delta_moments = DeltaMoments::parse_from(reader_builder, n_delta_moments)?;
     let mut ans_final_state_idxs = [0; ANS_INTERLEAVING];
-    for state in &mut ans_final_state_idxs {
-      *state = reader.read_uint::<AnsState>(ans_size_log);
-    }
+    reader_builder.with_reader(|reader| {
+      for state in &mut ans_final_state_idxs {
+        *state = reader.read_uint::<AnsState>(ans_size_log);
+      }
+      Ok(())
+    })?;
     Ok(Self {
       delta_moments,
       ans_final_state_idxs,
@@ -42,6 +57,111 @@ impl<L: Latent> PageLatentVarMeta<L> {
   }
 }
 
+unsafe fn write_exceptions<L: Latent, W: Write>(
+  exceptions: &[Exception<L>],
+  writer: &mut BitWriter<W>,
+) -> PcoResult<()> {
+  writer.write_usize(exceptions.len(), BITS_TO_ENCODE_N_EXCEPTIONS);
+  for exception_batch in exceptions.chunks(FULL_BIN_BATCH_SIZE) {
+    for exception in exception_batch {
+      writer.write_usize(exception.idx, BITS_TO_ENCODE_EXCEPTION_IDX);
+      writer.write_uint(exception.latent, L::BITS);
+    }
+    writer.flush()?;
+  }
+  Ok(())
+}
+
+unsafe fn parse_exceptions<L: Latent, R: BetterBufRead>(
+  reader_builder: &mut BitReaderBuilder<R>,
+) -> PcoResult<Vec<Exception<L>>> {
+  let n_exceptions =
+    reader_builder.with_reader(|reader| Ok(reader.read_usize(BITS_TO_ENCODE_N_EXCEPTIONS)))?;
+  let mut exceptions = Vec::with_capacity(n_exceptions);
+  while exceptions.len() < n_exceptions {
+    let batch_size = min(
+      n_exceptions - exceptions.len(),
+      FULL_BIN_BATCH_SIZE,
+    );
+    reader_builder.with_reader(|reader| {
+      for _ in 0..batch_size {
+        let idx = reader.read_usize(BITS_TO_ENCODE_EXCEPTION_IDX);
+        let latent = reader.read_uint::<L>(L::BITS);
+        exceptions.push(Exception { idx, latent });
+      }
+      Ok(())
+    })?;
+  }
+
+  if exceptions.windows(2).any(|pair| pair[0].idx >= pair[1].idx) {
+    return Err(PcoError::corruption(
+      "exception positions should be strictly increasing",
+    ));
+  }
+
+  Ok(exceptions)
+}
+
+// The decoder state at the start of a batch partway through a page, so
+// decompression can resume there.
+#[derive(Clone, Debug)]
+pub struct SeekPoint<L: Latent> {
+  // relative to the start of the page body, just after the page meta
+  pub bit_idx: usize,
+  pub per_var: Vec<PageLatentVarMeta<L>>,
+}
+
+unsafe fn write_seek_points<L: Latent, I: Iterator<Item = Bitlen> + Clone, W: Write>(
+  seek_points: &[SeekPoint<L>],
+  ans_size_logs: I,
+  writer: &mut BitWriter<W>,
+) -> PcoResult<()> {
+  for seek_point in seek_points {
+    writer.write_uint(
+      seek_point.bit_idx as u64,
+      BITS_TO_ENCODE_SEEK_BIT_IDX,
+    );
+    for (latent_idx, ans_size_log) in ans_size_logs.clone().enumerate() {
+      seek_point.per_var[latent_idx].write_to(ans_size_log, writer)?;
+    }
+    writer.flush()?;
+  }
+  Ok(())
+}
+
+unsafe fn parse_seek_points<L: Latent, R: BetterBufRead>(
+  reader_builder: &mut BitReaderBuilder<R>,
+  chunk_meta: &ChunkMeta<L>,
+  n: usize,
+) -> PcoResult<Vec<SeekPoint<L>>> {
+  let n_seek_points = chunk_meta.n_seek_points(n);
+  let mut seek_points = Vec::with_capacity(n_seek_points);
+  for _ in 0..n_seek_points {
+    let bit_idx = reader_builder
+      .with_reader(|reader| Ok(reader.read_uint::<u64>(BITS_TO_ENCODE_SEEK_BIT_IDX) as usize))?;
+    let mut per_var = Vec::with_capacity(chunk_meta.per_latent_var.len());
+    for (latent_idx, chunk_latent_var_meta) in chunk_meta.per_latent_var.iter().enumerate() {
+      per_var.push(PageLatentVarMeta::parse_from(
+        reader_builder,
+        chunk_meta.n_delta_moments_for_latent_var(latent_idx),
+        chunk_latent_var_meta.ans_size_log,
+      )?);
+    }
+    seek_points.push(SeekPoint { bit_idx, per_var });
+  }
+
+  if seek_points
+    .windows(2)
+    .any(|pair| pair[0].bit_idx > pair[1].bit_idx)
+  {
+    return Err(PcoError::corruption(
+      "seek index positions should be increasing",
+    ));
+  }
+
+  Ok(seek_points)
+}
+
 // Data page metadata is slightly semantically different from chunk metadata,
 // so it gets its own type.
 // Importantly, `n` and `compressed_body_size` might come from either the
@@ -49,32 +169,72 @@ impl<L: Latent> PageLatentVarMeta<L> {
 // (wrapped mode).
 #[derive(Clone, Debug)]
 pub struct PageMeta<L: Latent> {
+  // only present in run length mode
+  pub n_runs: Option<usize>,
+  // only present when the chunk has exceptions
+  pub exceptions: Option<Vec<Exception<L>>>,
   pub per_var: Vec<PageLatentVarMeta<L>>,
+  // one per seek index interval after the first batch, if the chunk has a
+  // seek index
+  pub seek_points: Vec<SeekPoint<L>>,
 }
 
 impl<L: Latent> PageMeta<L> {
-  pub unsafe fn write_to<I: Iterator<Item = Bitlen>, W: Write>(
+  pub unsafe fn write_to<I: Iterator<Item = Bitlen> + Clone, W: Write>(
     &self,
     ans_size_logs: I,
     writer: &mut BitWriter<W>,
-  ) {
-    for (latent_idx, ans_size_log) in ans_size_logs.enumerate() {
-      self.per_var[latent_idx].write_to(ans_size_log, writer);
+  ) -> PcoResult<()> {
+    if let Some(n_runs) = self.n_runs {
+      writer.write_usize(n_runs - 1, BITS_TO_ENCODE_N_RUNS);
     }
+    if let Some(exceptions) = &self.exceptions {
+      write_exceptions(exceptions, writer)?;
+    }
+    for (latent_idx, ans_size_log) in ans_size_logs.clone().enumerate() {
+      self.per_var[latent_idx].write_to(ans_size_log, writer)?;
+    }
+    write_seek_points(&self.seek_points, ans_size_logs, writer)?;
     writer.finish_byte();
+    Ok(())
   }
 
-  pub unsafe fn parse_from(reader: &mut BitReader, chunk_meta: &ChunkMeta<L>) -> PcoResult<Self> {
+  pub unsafe fn parse_from<R: BetterBufRead>(
+    reader_builder: &mut BitReaderBuilder<R>,
+    chunk_meta: &ChunkMeta<L>,
+    n: usize,
+  ) -> PcoResult<Self> {
+    // chunk metas only parse as run length mode on format versions that
+    // support it, so n_runs is gated on the format version too
+    let n_runs = reader_builder.with_reader(|reader| {
+      Ok(match chunk_meta.mode {
+        Mode::RunLength => Some(reader.read_usize(BITS_TO_ENCODE_N_RUNS) + 1),
+        _ => None,
+      })
+    })?;
+    let exceptions = if chunk_meta.has_exceptions {
+      Some(parse_exceptions(reader_builder)?)
+    } else {
+      None
+    };
     let mut per_var = Vec::with_capacity(chunk_meta.per_latent_var.len());
     for (latent_idx, chunk_latent_var_meta) in chunk_meta.per_latent_var.iter().enumerate() {
       per_var.push(PageLatentVarMeta::parse_from(
-        reader,
-        chunk_meta.delta_order_for_latent_var(latent_idx),
+        reader_builder,
+        chunk_meta.n_delta_moments_for_latent_var(latent_idx),
         chunk_latent_var_meta.ans_size_log,
       )?);
     }
-    reader.drain_empty_byte("non-zero bits at end of data page metadata")?;
+    let seek_points = parse_seek_points(reader_builder, chunk_meta, n)?;
+    reader_builder.with_reader(|reader| {
+      reader.drain_empty_byte("non-zero bits at end of data page metadata")
+    })?;
 
-    Ok(Self { per_var })
+    Ok(Self {
+      n_runs,
+      exceptions,
+      per_var,
+      seek_points,
+    })
   }
 }
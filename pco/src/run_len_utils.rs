@@ -0,0 +1,94 @@
+use crate::data_types::Latent;
+
+// Runs need to be at least this long on average for run length mode to be
+// worth trying.
+const MIN_AVG_RUN_LEN: usize = 4;
+
+fn count_runs<L: Latent>(latents: &[L]) -> usize {
+  if latents.is_empty() {
+    return 0;
+  }
+
+  1 + latents.windows(2).filter(|pair| pair[0] != pair[1]).count()
+}
+
+pub(crate) fn has_long_runs<L: Latent>(latents: &[L]) -> bool {
+  count_runs(latents) * MIN_AVG_RUN_LEN <= latents.len()
+}
+
+// Used for choosing the delta encoding order, since the run values are what
+// get delta encoded.
+pub(crate) fn run_values<L: Latent>(latents: &[L]) -> Vec<L> {
+  let mut res = Vec::with_capacity(count_runs(latents));
+  for &latent in latents {
+    if res.last() != Some(&latent) {
+      res.push(latent);
+    }
+  }
+  res
+}
+
+// Splits the latents into run values and run lengths (minus 1), without
+// letting any runs span multiple pages.
+// Returns the split latents and the count of runs in each page.
+pub(crate) fn split_latents<L: Latent>(
+  latents: &[L],
+  n_per_page: &[usize],
+) -> (Vec<Vec<L>>, Vec<usize>) {
+  // run lengths must fit in the latent type, which matters for 16-bit types
+  let max_len_minus_1 = L::MAX.to_u64();
+  let mut values = Vec::new();
+  let mut lengths = Vec::new();
+  let mut n_runs_per_page = Vec::with_capacity(n_per_page.len());
+  let mut page_start = 0;
+  for &page_n in n_per_page {
+    let page_latents = &latents[page_start..page_start + page_n];
+    let n_runs_before = values.len();
+    let mut run_start = 0;
+    for i in 1..page_n + 1 {
+      let len_minus_1 = (i - run_start - 1) as u64;
+      if i == page_n || page_latents[i] != page_latents[run_start] || len_minus_1 == max_len_minus_1
+      {
+        values.push(page_latents[run_start]);
+        lengths.push(L::from_u64(len_minus_1));
+        run_start = i;
+      }
+    }
+    n_runs_per_page.push(values.len() - n_runs_before);
+    page_start += page_n;
+  }
+
+  (vec![values, lengths], n_runs_per_page)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_split_latents() {
+    let latents = [1_u32, 1, 1, 2, 2, 1, 3, 3, 3, 3];
+    let (split, n_runs_per_page) = split_latents(&latents, &[4, 6]);
+    assert_eq!(n_runs_per_page, vec![2, 3]);
+    assert_eq!(split[0], vec![1, 2, 2, 1, 3]);
+    assert_eq!(split[1], vec![2, 0, 0, 0, 3]);
+    assert_eq!(run_values(&latents), vec![1, 2, 1, 3]);
+  }
+
+  #[test]
+  fn test_split_latents_max_len() {
+    let latents = vec![7_u16; (1 << 16) + 3];
+    let (split, n_runs_per_page) = split_latents(&latents, &[latents.len()]);
+    assert_eq!(n_runs_per_page, vec![2]);
+    assert_eq!(split[0], vec![7, 7]);
+    assert_eq!(split[1], vec![u16::MAX, 2]);
+  }
+
+  #[test]
+  fn test_has_long_runs() {
+    assert!(has_long_runs(&[0_u32; 100]));
+    assert!(!has_long_runs(
+      &(0_u32..100).collect::<Vec<_>>()
+    ));
+  }
+}
@@ -0,0 +1,373 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::bit_reader::BitReader;
+use crate::bit_writer::BitWriter;
+use crate::constants::{Bitlen, CURRENT_FORMAT_VERSION, OVERSHOOT_PADDING};
+use crate::data_types::Latent;
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::compressor::{varint_power, ChunkCompressor, FileCompressor};
+use crate::standalone::constants::*;
+use crate::standalone::{guarantee, ChunkSpan, FileDecompressor, FileMetadata};
+
+fn read_at<F: Read + Seek>(file: &mut F, pos: u64, max_len: usize) -> PcoResult<Vec<u8>> {
+  file.seek(SeekFrom::Start(pos))?;
+  let mut res = Vec::new();
+  file.by_ref().take(max_len as u64).read_to_end(&mut res)?;
+  Ok(res)
+}
+
+/// Appends chunks to an existing standalone .pco file in place.
+///
+/// Example:
+/// ```
+/// use std::io::Cursor;
+/// use pco::ChunkConfig;
+/// use pco::standalone::{simple_compress, simple_decompress, FileAppender, FileCompressor};
+/// # use pco::errors::PcoResult;
+///
+/// # fn main() -> PcoResult<()> {
+/// let compressed = simple_compress(&[1_i64, 2, 3], &ChunkConfig::default())?;
+/// let mut appender = FileAppender::open(Cursor::new(compressed))?;
+/// let chunk_compressor = FileCompressor::default()
+///   .chunk_compressor(&[4_i64, 5], &ChunkConfig::default())?;
+/// appender.append_chunk(&chunk_compressor)?;
+/// let compressed = appender.finish()?.into_inner();
+/// assert_eq!(simple_decompress::<i64>(&compressed)?, vec![1, 2, 3, 4, 5]);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// New chunks overwrite the file's footer, and a new footer is written by
+/// [`finish`][FileAppender::finish], so the file is incomplete until then.
+/// Any metadata in the old footer is kept, and if it had a complete chunk
+/// directory, the new chunks are added to it.
+///
+/// The file's `n_hint` is updated in place to the new total count of
+/// numbers if it fits in the bits the header already uses for it, and is
+/// otherwise set to 0 (unknown).
+/// Compressing the original file with an
+/// [`n_hint`][FileCompressor::with_n_hint] at least as large as the
+/// eventual total reserves enough bits.
+pub struct FileAppender<F: Read + Write + Seek> {
+  file: F,
+  standalone_version: usize,
+  // the bits of the header used to encode n_hint
+  n_hint_power: Bitlen,
+  header_size: usize,
+  // where the termination byte was, and where the next chunk will go
+  chunks_end: u64,
+  n: usize,
+  dtype_byte: Option<u8>,
+  metadata: Option<FileMetadata>,
+}
+
+impl<F: Read + Write + Seek> FileAppender<F> {
+  /// Reads the file's header and chunk preambles to find where its chunks
+  /// end, returning a `FileAppender` for it.
+  ///
+  /// Chunk data is skipped without being read.
+  ///
+  /// Will return a compatibility error if the file was written with an older
+  /// version of pco, since its chunks couldn't be mixed with new ones, an
+  /// invalid argument error if the file is nullable, or an error if
+  /// corruptions, insufficient data, or IO errors are found.
+  pub fn open(mut file: F) -> PcoResult<Self> {
+    let header = read_at(&mut file, 0, guarantee::header_size())?;
+    let (fd, rest) = FileDecompressor::new(header.as_slice())?;
+    let header_size = header.len() - rest.len();
+    if fd.standalone_version() != CURRENT_STANDALONE_VERSION
+      || fd.format_version() != CURRENT_FORMAT_VERSION
+    {
+      return Err(PcoError::compatibility(format!(
+        "unable to append to file with standalone version {} and format version \
+         {}; only standalone version {} and format version {} are supported",
+        fd.standalone_version(),
+        fd.format_version(),
+        CURRENT_STANDALONE_VERSION,
+        CURRENT_FORMAT_VERSION,
+      )));
+    }
+    if fd.is_nullable() {
+      return Err(PcoError::invalid_argument(
+        "unable to append to a nullable file",
+      ));
+    }
+
+    // The varint power follows the magic header and version byte.
+    let mut padded = header.clone();
+    padded.resize(header.len() + OVERSHOOT_PADDING, 0);
+    let n_hint_power = unsafe {
+      let mut reader = BitReader::new(&padded, header.len(), 0);
+      reader.read_aligned_bytes(MAGIC_HEADER.len() + 1)?;
+      1 + reader.read_bitlen(BITS_TO_ENCODE_VARINT_POWER)
+    };
+
+    let mut pos = header_size as u64;
+    let mut n = 0;
+    let mut dtype_byte = None;
+    let footer = loop {
+      let preamble_bytes = read_at(&mut file, pos, MAX_CHUNK_PREAMBLE_SIZE)?;
+      if preamble_bytes.first() == Some(&MAGIC_TERMINATION_BYTE) {
+        let mut footer = Vec::new();
+        file.seek(SeekFrom::Start(pos + 1))?;
+        file.read_to_end(&mut footer)?;
+        break footer;
+      }
+
+      let (maybe_preamble, rest) = fd.read_chunk_preamble(preamble_bytes.as_slice())?;
+      let preamble = maybe_preamble.unwrap();
+      let chunk_size = preamble_bytes.len() - rest.len() + preamble.body_size.unwrap();
+      dtype_byte = Some(preamble.dtype_byte);
+      n += preamble.n;
+      pos += chunk_size as u64;
+    };
+
+    let (metadata, rest) = fd.read_footer(footer.as_slice())?;
+    if !rest.is_empty() {
+      return Err(PcoError::corruption(format!(
+        "found {} unexpected bytes after the footer",
+        rest.len(),
+      )));
+    }
+
+    Ok(Self {
+      file,
+      standalone_version: fd.standalone_version(),
+      n_hint_power,
+      header_size,
+      chunks_end: pos,
+      n,
+      dtype_byte,
+      metadata,
+    })
+  }
+
+  /// Returns the total count of numbers in the file, including appended
+  /// chunks.
+  pub fn n(&self) -> usize {
+    self.n
+  }
+
+  /// Writes the chunk after the file's existing chunks.
+  ///
+  /// Will return an invalid argument error if the chunk's data type differs
+  /// from the file's existing chunks, or an error if writing fails.
+  pub fn append_chunk<L: Latent>(
+    &mut self,
+    chunk_compressor: &ChunkCompressor<L>,
+  ) -> PcoResult<()> {
+    let chunk_dtype_byte = chunk_compressor.dtype_byte();
+    if let Some(dtype_byte) = self.dtype_byte {
+      if dtype_byte != chunk_dtype_byte {
+        return Err(PcoError::invalid_argument(format!(
+          "chunk data type byte ({}) does not match file's ({})",
+          chunk_dtype_byte, dtype_byte,
+        )));
+      }
+    }
+
+    let chunk = chunk_compressor.write_chunk(Vec::new())?;
+    self.file.seek(SeekFrom::Start(self.chunks_end))?;
+    self.file.write_all(&chunk)?;
+
+    // Since chunk spans are contiguous from offset 0, this only extends a
+    // complete chunk directory.
+    let start = self.chunks_end as usize - self.header_size;
+    if let Some(metadata) = &mut self.metadata {
+      if metadata.chunk_spans.last().map_or(0, |span| span.end) == start {
+        metadata.chunk_spans.push(ChunkSpan {
+          n: chunk_compressor.n(),
+          start,
+          end: start + chunk.len(),
+        });
+      }
+    }
+    self.dtype_byte = Some(chunk_dtype_byte);
+    self.chunks_end += chunk.len() as u64;
+    self.n += chunk_compressor.n();
+    Ok(())
+  }
+
+  /// Writes a new footer and updates the header's `n_hint`, returning the
+  /// file.
+  ///
+  /// Will return an error if writing fails.
+  pub fn finish(mut self) -> PcoResult<F> {
+    self.file.seek(SeekFrom::Start(self.chunks_end))?;
+    let file_compressor = FileCompressor::default();
+    self.file = match &self.metadata {
+      Some(metadata) => file_compressor.write_footer_with_metadata(self.file, metadata)?,
+      None => file_compressor.write_footer(self.file)?,
+    };
+
+    let n_hint = if varint_power(self.n as u64) <= self.n_hint_power {
+      self.n
+    } else {
+      0
+    };
+    let mut writer = BitWriter::new(Vec::new(), STANDALONE_HEADER_PADDING);
+    unsafe {
+      writer.write_usize(
+        self.standalone_version,
+        BITS_TO_ENCODE_STANDALONE_VERSION,
+      );
+      writer.write_bitlen(
+        self.n_hint_power - 1,
+        BITS_TO_ENCODE_VARINT_POWER,
+      );
+      writer.write_uint(n_hint as u64, self.n_hint_power);
+    }
+    writer.finish_byte();
+    writer.flush()?;
+    self.file.seek(SeekFrom::Start(MAGIC_HEADER.len() as u64))?;
+    self.file.write_all(&writer.into_inner())?;
+    self.file.flush()?;
+    Ok(self.file)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::io::Cursor;
+
+  use super::*;
+  use crate::errors::ErrorKind;
+  use crate::standalone::{simple_decompress, FileDecompressor};
+  use crate::ChunkConfig;
+
+  fn compress_file(chunks: &[Vec<i32>], n_hint: usize, metadata: Option<&FileMetadata>) -> Vec<u8> {
+    let file_compressor = FileCompressor::default().with_n_hint(n_hint);
+    let mut dst = file_compressor.write_header(Vec::new()).unwrap();
+    for chunk in chunks {
+      dst = file_compressor
+        .chunk_compressor(chunk, &ChunkConfig::default())
+        .unwrap()
+        .write_chunk(dst)
+        .unwrap();
+    }
+    match metadata {
+      Some(metadata) => file_compressor.write_footer_with_metadata(dst, metadata),
+      None => file_compressor.write_footer(dst),
+    }
+    .unwrap()
+  }
+
+  fn append(src: Vec<u8>, chunks: &[Vec<i32>]) -> PcoResult<Vec<u8>> {
+    let mut appender = FileAppender::open(Cursor::new(src))?;
+    for chunk in chunks {
+      let chunk_compressor =
+        FileCompressor::default().chunk_compressor(chunk, &ChunkConfig::default())?;
+      appender.append_chunk(&chunk_compressor)?;
+    }
+    Ok(appender.finish()?.into_inner())
+  }
+
+  #[test]
+  fn test_append_roundtrip() -> PcoResult<()> {
+    let first = (0..1000).collect::<Vec<i32>>();
+    let second = vec![7; 300];
+    let third = (0..500).map(|i| i * 3 - 100).collect::<Vec<i32>>();
+
+    let src = compress_file(std::slice::from_ref(&first), 2000, None);
+    let src = append(src, std::slice::from_ref(&second))?;
+    let src = append(src, &[third.clone(), vec![-1]])?;
+
+    let expected = [first, second, third, vec![-1]].concat();
+    assert_eq!(simple_decompress::<i32>(&src)?, expected);
+    let (file_decompressor, rest) = FileDecompressor::new(src.as_slice())?;
+    // 1801 fits in the 11 bits the header reserved for n_hint
+    assert_eq!(file_decompressor.n_hint(), expected.len());
+    assert_eq!(file_decompressor.chunk_spans(rest)?.len(), 4);
+    assert_eq!(file_decompressor.metadata(rest)?, None);
+
+    // appending nothing leaves the file unchanged
+    assert_eq!(append(src.clone(), &[])?, src);
+    Ok(())
+  }
+
+  #[test]
+  fn test_append_n_hint_overflow() -> PcoResult<()> {
+    let src = compress_file(&[vec![1, 2, 3]], 3, None);
+    let src = append(src, &[vec![4, 5]])?;
+    let (file_decompressor, _) = FileDecompressor::new(src.as_slice())?;
+    // 5 doesn't fit in the 2 bits the header reserved for n_hint
+    assert_eq!(file_decompressor.n_hint(), 0);
+    assert_eq!(
+      simple_decompress::<i32>(&src)?,
+      vec![1, 2, 3, 4, 5]
+    );
+
+    // an empty file
+    let src = append(compress_file(&[], 0, None), &[vec![1]])?;
+    let (file_decompressor, _) = FileDecompressor::new(src.as_slice())?;
+    assert_eq!(file_decompressor.n_hint(), 1);
+    assert_eq!(simple_decompress::<i32>(&src)?, vec![1]);
+    Ok(())
+  }
+
+  #[test]
+  fn test_append_metadata() -> PcoResult<()> {
+    let mut metadata = FileMetadata::default();
+    metadata
+      .key_values
+      .insert("units".to_string(), "ms".to_string());
+    let src = compress_file(&[vec![1, 2, 3]], 0, Some(&metadata));
+    let (file_decompressor, rest) = FileDecompressor::new(src.as_slice())?;
+    metadata.chunk_spans = file_decompressor.chunk_spans(rest)?;
+    let src = compress_file(&[vec![1, 2, 3]], 0, Some(&metadata));
+
+    let src = append(src, &[vec![4, 5]])?;
+    let (file_decompressor, rest) = FileDecompressor::new(src.as_slice())?;
+    let appended_metadata = file_decompressor.metadata(rest)?.unwrap();
+    assert_eq!(
+      appended_metadata.key_values,
+      metadata.key_values
+    );
+    assert_eq!(
+      appended_metadata.chunk_spans,
+      file_decompressor.chunk_spans(rest)?
+    );
+    assert_eq!(appended_metadata.chunk_spans.len(), 2);
+
+    // an incomplete chunk directory is not extended
+    let src = compress_file(
+      &[vec![1, 2, 3]],
+      0,
+      Some(&FileMetadata::default()),
+    );
+    let src = append(src, &[vec![4, 5]])?;
+    let (file_decompressor, rest) = FileDecompressor::new(src.as_slice())?;
+    assert_eq!(
+      file_decompressor.metadata(rest)?,
+      Some(FileMetadata::default())
+    );
+    assert_eq!(
+      simple_decompress::<i32>(&src)?,
+      vec![1, 2, 3, 4, 5]
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_append_errors() -> PcoResult<()> {
+    let src = compress_file(&[vec![1, 2, 3]], 0, None);
+    let mut appender = FileAppender::open(Cursor::new(src.clone()))?;
+    let chunk_compressor =
+      FileCompressor::default().chunk_compressor(&[1.0_f32], &ChunkConfig::default())?;
+    let err = appender.append_chunk(&chunk_compressor).unwrap_err();
+    assert!(matches!(
+      err.kind,
+      ErrorKind::InvalidArgument
+    ));
+
+    let mut old_version = src.clone();
+    old_version[MAGIC_HEADER.len()] = 3;
+    let err = FileAppender::open(Cursor::new(old_version)).err().unwrap();
+    assert!(matches!(err.kind, ErrorKind::Compatibility));
+
+    for i in 0..src.len() {
+      assert!(FileAppender::open(Cursor::new(src[..i].to_vec())).is_err());
+    }
+    Ok(())
+  }
+}
@@ -1,18 +1,72 @@
+use std::cmp::{max, min};
 use std::io::Write;
 
 use crate::bit_writer::BitWriter;
 use crate::chunk_config::PagingSpec;
+use crate::constants::{Bitlen, MAX_ENTRIES};
 use crate::data_types::{Latent, NumberLike};
-use crate::errors::PcoResult;
+use crate::errors::{PcoError, PcoResult};
 use crate::standalone::constants::*;
-use crate::{bits, wrapped, ChunkConfig, ChunkMeta};
+use crate::standalone::{footer, guarantee, FileMetadata};
+use crate::{bits, wrapped, ChunkConfig, ChunkMeta, FULL_BATCH_N};
 
-unsafe fn write_varint<W: Write>(n: u64, writer: &mut BitWriter<W>) {
-  let power = if n == 0 { 1 } else { n.ilog2() + 1 };
+pub(crate) fn varint_power(n: u64) -> Bitlen {
+  if n == 0 {
+    1
+  } else {
+    n.ilog2() + 1
+  }
+}
+
+pub(crate) unsafe fn write_varint<W: Write>(n: u64, writer: &mut BitWriter<W>) {
+  let power = varint_power(n);
   writer.write_uint(power - 1, BITS_TO_ENCODE_VARINT_POWER);
   writer.write_uint(bits::lowest_bits(n, power), power);
 }
 
+// Splits n numbers into as few pages as possible, each holding up to
+// max_page_n numbers, while keeping all pages but the last to whole batches.
+fn batch_aligned_n_per_page(n: usize, max_page_n: usize) -> Vec<usize> {
+  let n_batches = n.div_ceil(FULL_BATCH_N);
+  let max_page_batches = max(max_page_n / FULL_BATCH_N, 1);
+  let n_pages = n_batches.div_ceil(max_page_batches);
+  let mut res = Vec::with_capacity(n_pages);
+  let mut start = 0;
+  for i in 0..n_pages {
+    let end = min(
+      ((i + 1) * n_batches) / n_pages * FULL_BATCH_N,
+      n,
+    );
+    res.push(end - start);
+    start = end;
+  }
+  res
+}
+
+// Applies the paging spec to a standalone chunk, in which every page but the
+// last must hold whole batches, so a decompressor's destination stays
+// batch-aligned across pages.
+pub(crate) fn standalone_n_per_page(paging_spec: &PagingSpec, n: usize) -> PcoResult<Vec<usize>> {
+  let n_per_page = match paging_spec {
+    PagingSpec::EqualPagesUpTo(max_page_n) => {
+      batch_aligned_n_per_page(n, min(*max_page_n, MAX_ENTRIES))
+    }
+    _ => paging_spec.n_per_page(n)?,
+  };
+
+  let n_pages = n_per_page.len();
+  for &page_n in n_per_page.iter().take(n_pages.saturating_sub(1)) {
+    if page_n % FULL_BATCH_N != 0 {
+      return Err(PcoError::invalid_argument(format!(
+        "every standalone page but the last must have a multiple of {} \
+         numbers (was {})",
+        FULL_BATCH_N, page_n,
+      )));
+    }
+  }
+  Ok(n_per_page)
+}
+
 /// Top-level entry point for compressing standalone .pco files.
 ///
 /// Example of the lowest level API for writing a .pco file:
@@ -44,6 +98,7 @@ unsafe fn write_varint<W: Write>(n: u64, writer: &mut BitWriter<W>) {
 pub struct FileCompressor {
   inner: wrapped::FileCompressor,
   n_hint: usize,
+  nullable: bool,
 }
 
 impl FileCompressor {
@@ -52,6 +107,18 @@ impl FileCompressor {
     self
   }
 
+  /// Marks the file as nullable, so that its chunks must be written with
+  /// [`nullable_chunk_compressor`][FileCompressor::nullable_chunk_compressor]
+  /// instead of [`chunk_compressor`][FileCompressor::chunk_compressor].
+  ///
+  /// This is recorded in the header, and decompressors will only read a
+  /// nullable file via
+  /// [`decompress_nullable_chunk`][crate::standalone::FileDecompressor::decompress_nullable_chunk].
+  pub fn with_nullable(mut self, nullable: bool) -> Self {
+    self.nullable = nullable;
+    self
+  }
+
   /// Writes a short header to the destination.
   ///
   /// Will return an error if the provided `Write` errors.
@@ -64,6 +131,13 @@ impl FileCompressor {
         BITS_TO_ENCODE_STANDALONE_VERSION,
       );
       write_varint(self.n_hint as u64, &mut writer);
+      writer.finish_byte();
+      let flags = if self.nullable {
+        NULLABLE_HEADER_FLAG
+      } else {
+        0
+      };
+      writer.write_usize(flags, BITS_TO_ENCODE_HEADER_FLAGS);
     }
     writer.finish_byte();
     writer.flush()?;
@@ -74,7 +148,12 @@ impl FileCompressor {
   /// Creates a `ChunkCompressor` that can be used to write entire chunks
   /// at a time.
   ///
-  /// Will return an error if any arguments provided are invalid.
+  /// Chunks may hold up to 2^31 numbers, sharing one chunk metadata, and
+  /// are split into pages according to the config's
+  /// [`paging_spec`][ChunkConfig::paging_spec].
+  ///
+  /// Will return an error if the file is nullable or if any arguments
+  /// provided are invalid.
   ///
   /// Although this doesn't write anything yet, it does the bulk of
   /// compute necessary for the compression.
@@ -82,9 +161,26 @@ impl FileCompressor {
     &self,
     nums: &[T],
     config: &ChunkConfig,
+  ) -> PcoResult<ChunkCompressor<T::L>> {
+    if self.nullable {
+      return Err(PcoError::invalid_argument(
+        "chunks of a nullable file must be written with a nullable chunk compressor",
+      ));
+    }
+
+    self.raw_chunk_compressor(nums, config)
+  }
+
+  fn raw_chunk_compressor<T: NumberLike>(
+    &self,
+    nums: &[T],
+    config: &ChunkConfig,
   ) -> PcoResult<ChunkCompressor<T::L>> {
     let mut config = config.clone();
-    config.paging_spec = PagingSpec::Exact(vec![nums.len()]);
+    config.paging_spec = PagingSpec::Exact(standalone_n_per_page(
+      &config.paging_spec,
+      nums.len(),
+    )?);
 
     Ok(ChunkCompressor {
       inner: self.inner.chunk_compressor(nums, &config)?,
@@ -92,15 +188,106 @@ impl FileCompressor {
     })
   }
 
+  /// Creates a `NullableChunkCompressor` that can be used to write entire
+  /// chunks of nullable numbers at a time.
+  ///
+  /// `validity` must have the same length as `nums`, and is `false` for each
+  /// null number.
+  /// Null numbers are skipped entirely, so their values don't matter and
+  /// don't affect compression.
+  ///
+  /// Will return an error if the file isn't nullable (see
+  /// [`with_nullable`][FileCompressor::with_nullable]) or if any arguments
+  /// provided are invalid.
+  pub fn nullable_chunk_compressor<T: NumberLike>(
+    &self,
+    nums: &[T],
+    validity: &[bool],
+    config: &ChunkConfig,
+  ) -> PcoResult<NullableChunkCompressor<T::L>> {
+    if !self.nullable {
+      return Err(PcoError::invalid_argument(
+        "nullable chunks may only be written to a nullable file",
+      ));
+    }
+    if nums.len() != validity.len() {
+      return Err(PcoError::invalid_argument(format!(
+        "validity length must match numbers length ({} != {})",
+        validity.len(),
+        nums.len(),
+      )));
+    }
+
+    // validity flags are the numbers with latents 0 and 1, so every chunk in
+    // the file has the same data type
+    let validity_flags = validity
+      .iter()
+      .map(|&is_valid| T::from_latent_ordered(if is_valid { T::L::ONE } else { T::L::ZERO }))
+      .collect::<Vec<_>>();
+    let validity_config = ChunkConfig::default().with_compression_level(config.compression_level);
+    let validity_cc = self.raw_chunk_compressor(&validity_flags, &validity_config)?;
+
+    let valid_nums = nums
+      .iter()
+      .zip(validity)
+      .filter_map(|(&num, &is_valid)| is_valid.then_some(num))
+      .collect::<Vec<_>>();
+    let maybe_values_cc = if valid_nums.is_empty() {
+      None
+    } else {
+      Some(self.raw_chunk_compressor(&valid_nums, config)?)
+    };
+
+    Ok(NullableChunkCompressor {
+      validity: validity_cc,
+      maybe_values: maybe_values_cc,
+    })
+  }
+
   /// Writes a short footer to the destination.
   ///
   /// Will return an error if the provided `Write` errors.
   pub fn write_footer<W: Write>(&self, dst: W) -> PcoResult<W> {
-    let mut writer = BitWriter::new(dst, 1);
+    let mut writer = BitWriter::new(dst, STANDALONE_CHUNK_PREAMBLE_PADDING);
     writer.write_aligned_bytes(&[MAGIC_TERMINATION_BYTE])?;
+    unsafe {
+      write_varint(0, &mut writer);
+    }
+    writer.finish_byte();
     writer.flush()?;
     Ok(writer.into_inner())
   }
+
+  /// Writes a footer containing the given metadata to the destination.
+  ///
+  /// This can be used instead of
+  /// [`write_footer`][FileCompressor::write_footer] to store a chunk
+  /// directory and arbitrary key/value pairs, which decompressors can read
+  /// from the end of the file via
+  /// [`FileDecompressor::metadata`][crate::standalone::FileDecompressor::metadata].
+  ///
+  /// Will return an error if the chunk spans are not contiguous, if the
+  /// metadata exceeds 4GiB, or if the provided `Write` errors.
+  pub fn write_footer_with_metadata<W: Write>(
+    &self,
+    dst: W,
+    metadata: &FileMetadata,
+  ) -> PcoResult<W> {
+    let footer = footer::write_footer(metadata)?;
+    let body_size = footer.len() - FOOTER_TRAILER_SIZE;
+
+    let mut writer = BitWriter::new(dst, STANDALONE_CHUNK_PREAMBLE_PADDING);
+    writer.write_aligned_bytes(&[MAGIC_TERMINATION_BYTE])?;
+    unsafe {
+      write_varint(body_size as u64, &mut writer);
+    }
+    writer.finish_byte();
+    writer.flush()?;
+
+    let mut dst = writer.into_inner();
+    dst.write_all(&footer)?;
+    Ok(dst)
+  }
 }
 
 /// Holds metadata about a chunk and supports compression.
@@ -116,30 +303,97 @@ impl<L: Latent> ChunkCompressor<L> {
     self.inner.meta()
   }
 
+  pub(crate) fn n(&self) -> usize {
+    self.inner.n_per_page().iter().sum()
+  }
+
+  pub(crate) fn dtype_byte(&self) -> u8 {
+    self.dtype_byte
+  }
+
   /// Returns an estimate of the overall size of the chunk.
   ///
   /// This can be useful when building the file as a `Vec<u8>` in memory;
   /// you can `.reserve(chunk_compressor.chunk_size_hint())` ahead of time.
   pub fn chunk_size_hint(&self) -> usize {
-    1 + BITS_TO_ENCODE_N_ENTRIES.div_ceil(8) as usize
-      + self.inner.chunk_meta_size_hint()
-      + self.inner.page_size_hint(0)
+    let body_size_hint = self.body_size_hint();
+    guarantee::chunk_preamble_size(self.n(), body_size_hint) + body_size_hint
+  }
+
+  fn body_size_hint(&self) -> usize {
+    let n_per_page = self.inner.n_per_page();
+    let pages_size_hint = (0..n_per_page.len())
+      .map(|page_idx| {
+        guarantee::varint_size(n_per_page[page_idx]) + self.inner.page_size_hint(page_idx)
+      })
+      .sum::<usize>();
+    self.inner.chunk_meta_size_hint() + pages_size_hint
   }
 
   /// Writes an entire chunk to the destination.
   ///
   /// Will return an error if the provided `Write` errors.
   pub fn write_chunk<W: Write>(&self, dst: W) -> PcoResult<W> {
+    // We write the body first so we can record its size in the preamble,
+    // which lets decompressors find chunk boundaries without decompressing.
+    let mut body = Vec::with_capacity(self.body_size_hint());
+    body = self.inner.write_chunk_meta(body)?;
+    for (page_idx, page_n) in self.inner.n_per_page().into_iter().enumerate() {
+      footer::write_varint_aligned(page_n, &mut body)?;
+      body = self.inner.write_page(page_idx, body)?;
+    }
+
     let mut writer = BitWriter::new(dst, STANDALONE_CHUNK_PREAMBLE_PADDING);
     writer.write_aligned_bytes(&[self.dtype_byte])?;
-    let n = self.inner.n_per_page()[0];
     unsafe {
-      writer.write_usize(n - 1, BITS_TO_ENCODE_N_ENTRIES);
+      write_varint(self.n() as u64, &mut writer);
+      writer.finish_byte();
+      write_varint(body.len() as u64, &mut writer);
     }
-
+    writer.finish_byte();
     writer.flush()?;
-    let dst = writer.into_inner();
-    let dst = self.inner.write_chunk_meta(dst)?;
-    self.inner.write_page(0, dst)
+
+    let mut dst = writer.into_inner();
+    dst.write_all(&body)?;
+    Ok(dst)
+  }
+}
+
+/// Holds metadata about a chunk of nullable numbers and supports compression.
+///
+/// In the standalone format, this is written as a chunk of validity flags
+/// followed by a chunk of only the valid numbers, if there are any.
+#[derive(Clone, Debug)]
+pub struct NullableChunkCompressor<L: Latent> {
+  validity: ChunkCompressor<L>,
+  maybe_values: Option<ChunkCompressor<L>>,
+}
+
+impl<L: Latent> NullableChunkCompressor<L> {
+  /// Returns pre-computed information about the chunk of valid numbers, if
+  /// there are any.
+  pub fn meta(&self) -> Option<&ChunkMeta<L>> {
+    self.maybe_values.as_ref().map(ChunkCompressor::meta)
+  }
+
+  /// Returns an estimate of the overall size of the chunk, including
+  /// validity.
+  pub fn chunk_size_hint(&self) -> usize {
+    self.validity.chunk_size_hint()
+      + self
+        .maybe_values
+        .as_ref()
+        .map_or(0, ChunkCompressor::chunk_size_hint)
+  }
+
+  /// Writes the validity and valid numbers to the destination.
+  ///
+  /// Will return an error if the provided `Write` errors.
+  pub fn write_chunk<W: Write>(&self, dst: W) -> PcoResult<W> {
+    let dst = self.validity.write_chunk(dst)?;
+    match &self.maybe_values {
+      Some(values) => values.write_chunk(dst),
+      None => Ok(dst),
+    }
   }
 }
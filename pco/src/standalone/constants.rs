@@ -3,14 +3,24 @@ use crate::constants::{Bitlen, OVERSHOOT_PADDING};
 // ascii for pco!
 pub const MAGIC_HEADER: [u8; 4] = [112, 99, 111, 33];
 pub const MAGIC_TERMINATION_BYTE: u8 = 0;
+pub const MAGIC_FOOTER: [u8; 4] = MAGIC_HEADER;
+// only used by standalone versions < 5, which stored chunk_n - 1 in a fixed
+// number of bits
 pub const BITS_TO_ENCODE_N_ENTRIES: Bitlen = 24;
+pub const BITS_TO_ENCODE_HEADER_FLAGS: Bitlen = 8;
 pub const BITS_TO_ENCODE_STANDALONE_VERSION: Bitlen = 8;
 pub const BITS_TO_ENCODE_VARINT_POWER: Bitlen = 6;
-pub const CURRENT_STANDALONE_VERSION: usize = 2;
+pub const CURRENT_STANDALONE_VERSION: usize = 6;
+pub const NULLABLE_HEADER_FLAG: usize = 1;
+// a footer's body size as a u32, followed by the magic footer
+pub const FOOTER_TRAILER_SIZE: usize = 4 + MAGIC_FOOTER.len();
+
+// the maximum byte size of a chunk's dtype byte, count, and body size
+pub const MAX_CHUNK_PREAMBLE_SIZE: usize =
+  1 + 2 * (BITS_TO_ENCODE_VARINT_POWER + 64).div_ceil(8) as usize;
 
 // padding
-pub const STANDALONE_CHUNK_PREAMBLE_PADDING: usize =
-  1 + BITS_TO_ENCODE_N_ENTRIES as usize + OVERSHOOT_PADDING;
+pub const STANDALONE_CHUNK_PREAMBLE_PADDING: usize = MAX_CHUNK_PREAMBLE_SIZE + OVERSHOOT_PADDING;
 pub const STANDALONE_HEADER_PADDING: usize = 30;
 
 #[cfg(test)]
@@ -1,21 +1,60 @@
+use std::cmp::min;
+use std::ops::RangeInclusive;
+
 use better_io::BetterBufRead;
 
+use crate::aggregation::Aggregator;
 use crate::bit_reader::{BitReader, BitReaderBuilder};
 use crate::constants::Bitlen;
-use crate::data_types::NumberLike;
+use crate::data_types::{Latent, NumberLike};
 use crate::errors::{PcoError, PcoResult};
 use crate::progress::Progress;
 use crate::standalone::constants::*;
-use crate::standalone::DataTypeOrTermination;
-use crate::{bit_reader, wrapped, ChunkMeta};
+use crate::standalone::{footer, DataTypeOrTermination, FileMetadata};
+use crate::{bit_reader, wrapped, Agg, AggValue, ChunkMeta, FULL_BATCH_N};
 
-unsafe fn read_varint(reader: &mut BitReader) -> PcoResult<u64> {
+pub(crate) unsafe fn read_varint(reader: &mut BitReader, name: &str) -> PcoResult<u64> {
   let power = 1 + reader.read_uint::<Bitlen>(BITS_TO_ENCODE_VARINT_POWER);
   let res = reader.read_uint(power);
-  reader.drain_empty_byte("standalone size hint")?;
+  reader.drain_empty_byte(&format!("standalone {}", name))?;
   Ok(res)
 }
 
+// Fills `src` with the `footer_size` bytes of a footer. The buffer only grows
+// as data actually arrives, so a corrupt footer size on a Read stream returns
+// an error instead of allocating a huge buffer.
+fn fill_footer<R: BetterBufRead>(src: &mut R, footer_size: usize) -> PcoResult<()> {
+  let mut target = match src.capacity() {
+    Some(capacity) => footer_size.min(capacity.max(1)),
+    None => footer_size,
+  };
+  loop {
+    bit_reader::ensure_buf_read_capacity(src, target);
+    src.fill_or_eof(target)?;
+    if src.buffer().len() < target {
+      return Err(PcoError::insufficient_data(format!(
+        "footer of {} bytes exceeds the {} bytes available",
+        footer_size,
+        src.buffer().len(),
+      )));
+    }
+    if target == footer_size {
+      return Ok(());
+    }
+    target = footer_size.min(target.saturating_mul(2));
+  }
+}
+
+// a chunk's count of numbers and its wrapped decompressor
+type CountAndChunkDecompressor<T> = (usize, wrapped::ChunkDecompressor<T>);
+
+pub(crate) struct ChunkPreamble {
+  pub dtype_byte: u8,
+  pub n: usize,
+  // only present in standalone versions >= 3
+  pub body_size: Option<usize>,
+}
+
 /// Top-level entry point for decompressing standalone .pco files.
 ///
 /// Example of the lowest level API for reading a .pco file:
@@ -35,24 +74,41 @@ unsafe fn read_varint(reader: &mut BitReader) -> PcoResult<u64> {
 ///     // Do something with &nums[0..progress.n_processed]
 ///     finished_chunk = progress.finished;
 ///   }
-///   src = chunk_decompressor.into_src();
+///   src = chunk_decompressor.into_src()?;
 /// }
 /// # Ok(())
 /// # }
 /// ```
 #[derive(Clone, Debug)]
 pub struct FileDecompressor {
+  standalone_version: usize,
   n_hint: usize,
+  is_nullable: bool,
   inner: wrapped::FileDecompressor,
 }
 
+/// The location of a chunk within a standalone file, as found by
+/// [`FileDecompressor::chunk_spans`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkSpan {
+  /// The count of numbers in the chunk.
+  pub n: usize,
+  /// The byte offset where the chunk starts, including its preamble.
+  pub start: usize,
+  /// The byte offset where the chunk ends.
+  pub end: usize,
+}
+
 /// The outcome of starting a new chunk of a standalone file.
+// This is short-lived, so we don't bother boxing the chunk decompressor.
+#[allow(clippy::large_enum_variant)]
 pub enum MaybeChunkDecompressor<T: NumberLike, R: BetterBufRead> {
   /// We get a `ChunkDecompressor` when there is another chunk as evidenced
   /// by the data type byte.
   Some(ChunkDecompressor<T, R>),
   /// We are at the end of the pco data if we encounter a null byte instead of
   /// a data type byte.
+  /// The returned source starts after the file's footer.
   EndOfData(R),
 }
 
@@ -76,18 +132,23 @@ impl FileDecompressor {
       )));
     }
 
-    let (standalone_version, n_hint) = reader_builder.with_reader(|reader| unsafe {
+    let (standalone_version, n_hint, flags) = reader_builder.with_reader(|reader| unsafe {
       let standalone_version = reader.read_usize(BITS_TO_ENCODE_STANDALONE_VERSION);
       let n_hint = if standalone_version >= 2 {
-        read_varint(reader)? as usize
+        read_varint(reader, "size hint")? as usize
       } else {
         // These versions only had wrapped version; we need to rewind so they can
         // reuse it.
         reader.bits_past_byte -= BITS_TO_ENCODE_STANDALONE_VERSION;
         0
       };
+      let flags = if standalone_version >= 6 {
+        reader.read_usize(BITS_TO_ENCODE_HEADER_FLAGS)
+      } else {
+        0
+      };
 
-      Ok((standalone_version, n_hint))
+      Ok((standalone_version, n_hint, flags))
     })?;
 
     if standalone_version > CURRENT_STANDALONE_VERSION {
@@ -97,8 +158,27 @@ impl FileDecompressor {
       )));
     }
 
+    if flags & !NULLABLE_HEADER_FLAG != 0 {
+      return Err(PcoError::corruption(format!(
+        "unknown standalone header flags: {}",
+        flags
+      )));
+    }
+
     let (inner, rest) = wrapped::FileDecompressor::new(reader_builder.into_inner())?;
-    Ok((Self { inner, n_hint }, rest))
+    Ok((
+      Self {
+        standalone_version,
+        inner,
+        n_hint,
+        is_nullable: flags & NULLABLE_HEADER_FLAG != 0,
+      },
+      rest,
+    ))
+  }
+
+  pub(crate) fn standalone_version(&self) -> usize {
+    self.standalone_version
   }
 
   pub fn format_version(&self) -> u8 {
@@ -109,6 +189,319 @@ impl FileDecompressor {
     self.n_hint
   }
 
+  /// Returns whether the file was written with
+  /// [`with_nullable`][crate::standalone::FileCompressor::with_nullable],
+  /// in which case its chunks must be read with
+  /// [`decompress_nullable_chunk`][FileDecompressor::decompress_nullable_chunk].
+  pub fn is_nullable(&self) -> bool {
+    self.is_nullable
+  }
+
+  pub(crate) fn check_not_nullable(&self) -> PcoResult<()> {
+    if self.is_nullable {
+      return Err(PcoError::invalid_argument(
+        "file is nullable and must be read with decompress_nullable_chunk",
+      ));
+    }
+    Ok(())
+  }
+
+  /// Returns whether each chunk records its byte size, which allows
+  /// finding chunk boundaries without decompressing.
+  pub fn has_chunk_sizes(&self) -> bool {
+    self.standalone_version >= 3
+  }
+
+  fn supports_footer(&self) -> bool {
+    self.standalone_version >= 4
+  }
+
+  // Whether chunks may have multiple pages, each preceded by its count of
+  // numbers.
+  fn has_page_counts(&self) -> bool {
+    self.standalone_version >= 5
+  }
+
+  /// Reads the metadata from the file's footer, if it has one.
+  ///
+  /// `src` should be the source returned by [`FileDecompressor::new`],
+  /// ending exactly at the end of the file, since the footer is found by
+  /// reading backward from there.
+  /// Its chunk spans are relative to the start of `src`.
+  /// Files without a footer, including all files from before standalone
+  /// version 4, return `None`.
+  ///
+  /// Will return an error if corruptions or insufficient data are found.
+  pub fn metadata(&self, src: &[u8]) -> PcoResult<Option<FileMetadata>> {
+    if !self.supports_footer() || !src.ends_with(&MAGIC_FOOTER) {
+      // without a footer, the file ends with the termination byte and an
+      // empty footer size
+      return if !self.supports_footer() || src.ends_with(&[MAGIC_TERMINATION_BYTE, 0]) {
+        Ok(None)
+      } else {
+        Err(PcoError::corruption(
+          "expected src to end with a standalone footer",
+        ))
+      };
+    }
+
+    let trailer_start = src.len().saturating_sub(FOOTER_TRAILER_SIZE);
+    let body_size = footer::parse_footer_trailer(&src[trailer_start..])?;
+    let footer_start = trailer_start.checked_sub(body_size).ok_or_else(|| {
+      PcoError::insufficient_data(format!(
+        "footer of {} bytes exceeds the {} bytes available",
+        body_size, trailer_start,
+      ))
+    })?;
+    let metadata = footer::parse_footer(&src[footer_start..])?;
+
+    if let Some(last_span) = metadata.chunk_spans.last() {
+      if last_span.end >= footer_start || src[last_span.end] != MAGIC_TERMINATION_BYTE {
+        return Err(PcoError::corruption(format!(
+          "footer chunk spans end at byte {}, which is not the termination byte",
+          last_span.end,
+        )));
+      }
+    }
+    Ok(Some(metadata))
+  }
+
+  // Reads past the footer that follows the termination byte, if any.
+  pub(crate) fn read_footer<R: BetterBufRead>(
+    &self,
+    mut src: R,
+  ) -> PcoResult<(Option<FileMetadata>, R)> {
+    if !self.supports_footer() {
+      return Ok((None, src));
+    }
+
+    bit_reader::ensure_buf_read_capacity(&mut src, STANDALONE_CHUNK_PREAMBLE_PADDING);
+    let mut reader_builder = BitReaderBuilder::new(src, STANDALONE_CHUNK_PREAMBLE_PADDING, 0);
+    let body_size =
+      reader_builder.with_reader(|reader| unsafe { read_varint(reader, "footer size") })? as usize;
+    let mut src = reader_builder.into_inner();
+    if body_size == 0 {
+      return Ok((None, src));
+    }
+
+    let footer_size = body_size.checked_add(FOOTER_TRAILER_SIZE).ok_or_else(|| {
+      PcoError::corruption(format!(
+        "footer size {} overflows",
+        body_size
+      ))
+    })?;
+    fill_footer(&mut src, footer_size)?;
+    let metadata = footer::parse_footer(&src.buffer()[..footer_size])?;
+    src.consume(footer_size);
+    Ok((Some(metadata), src))
+  }
+
+  pub(crate) fn read_chunk_preamble<R: BetterBufRead>(
+    &self,
+    mut src: R,
+  ) -> PcoResult<(Option<ChunkPreamble>, R)> {
+    bit_reader::ensure_buf_read_capacity(&mut src, STANDALONE_CHUNK_PREAMBLE_PADDING);
+    let mut reader_builder = BitReaderBuilder::new(src, STANDALONE_CHUNK_PREAMBLE_PADDING, 0);
+    let dtype_byte = reader_builder.with_reader(|reader| Ok(reader.read_aligned_bytes(1)?[0]))?;
+    if dtype_byte == MAGIC_TERMINATION_BYTE {
+      let (_, src) = self.read_footer(reader_builder.into_inner())?;
+      return Ok((None, src));
+    }
+
+    let has_chunk_sizes = self.has_chunk_sizes();
+    let has_page_counts = self.has_page_counts();
+    let (n, body_size) = reader_builder.with_reader(|reader| unsafe {
+      let n = if has_page_counts {
+        read_varint(reader, "chunk n")? as usize
+      } else {
+        reader.read_usize(BITS_TO_ENCODE_N_ENTRIES) + 1
+      };
+      if n == 0 {
+        return Err(PcoError::corruption(
+          "standalone chunk n was 0",
+        ));
+      }
+      let body_size = if has_chunk_sizes {
+        Some(read_varint(reader, "chunk size")? as usize)
+      } else {
+        None
+      };
+      Ok((n, body_size))
+    })?;
+    let preamble = ChunkPreamble {
+      dtype_byte,
+      n,
+      body_size,
+    };
+    Ok((Some(preamble), reader_builder.into_inner()))
+  }
+
+  /// Finds the location of every chunk in the rest of the file without
+  /// decompressing any of them.
+  ///
+  /// `src` should be the source returned by [`FileDecompressor::new`], and
+  /// the returned byte offsets are relative to it.
+  /// Each chunk can then be decompressed independently by passing
+  /// `&src[span.start..span.end]` to
+  /// [`chunk_decompressor`][FileDecompressor::chunk_decompressor].
+  ///
+  /// Will return a compatibility error if the file predates chunk sizes
+  /// (see [`has_chunk_sizes`][FileDecompressor::has_chunk_sizes]), or
+  /// an error if corruptions or insufficient data are found.
+  pub fn chunk_spans(&self, src: &[u8]) -> PcoResult<Vec<ChunkSpan>> {
+    if !self.has_chunk_sizes() {
+      return Err(PcoError::compatibility(format!(
+        "standalone version {} does not record chunk sizes",
+        self.standalone_version,
+      )));
+    }
+
+    let mut spans = Vec::new();
+    let mut start = 0;
+    while let Some(span) = self.peek_chunk_span(&src[start..])? {
+      spans.push(ChunkSpan {
+        n: span.n,
+        start,
+        end: start + span.end,
+      });
+      start += span.end;
+    }
+    Ok(spans)
+  }
+
+  // Finds the span of the next chunk, if any, without decompressing it.
+  // Only valid if the file has chunk sizes.
+  pub(crate) fn peek_chunk_span(&self, src: &[u8]) -> PcoResult<Option<ChunkSpan>> {
+    let (maybe_preamble, rest) = self.read_chunk_preamble(src)?;
+    let preamble = match maybe_preamble {
+      Some(preamble) => preamble,
+      None => return Ok(None),
+    };
+    let body_size = preamble.body_size.unwrap();
+    let end = (src.len() - rest.len())
+      .checked_add(body_size)
+      .ok_or_else(|| PcoError::corruption(format!("chunk size {} overflows", body_size)))?;
+    if end > src.len() {
+      return Err(PcoError::insufficient_data(format!(
+        "chunk ends at byte {} but only {} bytes are available",
+        end,
+        src.len(),
+      )));
+    }
+
+    Ok(Some(ChunkSpan {
+      n: preamble.n,
+      start: 0,
+      end,
+    }))
+  }
+
+  // Reads a chunk's preamble and metadata, stopping before its page.
+  fn read_chunk_meta<T: NumberLike, R: BetterBufRead>(
+    &self,
+    src: R,
+  ) -> PcoResult<(Option<CountAndChunkDecompressor<T>>, R)> {
+    let (maybe_preamble, src) = self.read_chunk_preamble(src)?;
+    let preamble = match maybe_preamble {
+      Some(preamble) => preamble,
+      None => return Ok((None, src)),
+    };
+
+    if preamble.dtype_byte != T::DTYPE_BYTE {
+      return Err(PcoError::corruption(format!(
+        "data type byte does not match {:?}; instead found {:?}",
+        T::DTYPE_BYTE,
+        preamble.dtype_byte,
+      )));
+    }
+
+    let (inner_cd, src) = self.inner.chunk_decompressor::<T, R>(src)?;
+    Ok((Some((preamble.n, inner_cd)), src))
+  }
+
+  /// Decompresses the rest of the file, returning the index and value of
+  /// each number in `value_range`.
+  ///
+  /// Indices count from the first number in `src`, which should be the
+  /// source returned by [`FileDecompressor::new`].
+  /// Numbers are compared by the order of their latents, which for floats
+  /// puts negative NaNs before -inf and positive NaNs after +inf.
+  ///
+  /// Chunks whose [`value_bounds`][ChunkMeta::value_bounds] rule out any
+  /// matches are skipped without decompressing their page when the file
+  /// records chunk sizes (see
+  /// [`has_chunk_sizes`][FileDecompressor::has_chunk_sizes]).
+  /// Chunks compressed with
+  /// [`ValueBoundsSpec::Enabled`][crate::ValueBoundsSpec::Enabled] always
+  /// have bounds.
+  ///
+  /// Will return an error if the range is empty, if the file is nullable, or
+  /// if there are any corruption or insufficient data issues.
+  pub fn decompress_filtered<T: NumberLike>(
+    &self,
+    mut src: &[u8],
+    value_range: RangeInclusive<T>,
+  ) -> PcoResult<Vec<(usize, T)>> {
+    self.check_not_nullable()?;
+    let lower = value_range.start().to_latent_ordered();
+    let upper = value_range.end().to_latent_ordered();
+    if lower > upper {
+      return Err(PcoError::invalid_argument(format!(
+        "value range start must not exceed end ({} > {})",
+        value_range.start(),
+        value_range.end(),
+      )));
+    }
+    let is_match = |x: &T| (lower..=upper).contains(&x.to_latent_ordered());
+
+    let mut res = Vec::new();
+    let mut batch = vec![T::default(); FULL_BATCH_N];
+    // the index of the first number in the current chunk
+    let mut chunk_start = 0;
+    loop {
+      let (maybe_chunk, rest) = self.read_chunk_meta::<T, _>(src)?;
+      let (n, inner_cd) = match maybe_chunk {
+        Some(chunk) => chunk,
+        None => break,
+      };
+      let could_match =
+        inner_cd
+          .meta
+          .value_bounds::<T>()
+          .map_or(true, |(chunk_lower, chunk_upper)| {
+            chunk_lower.to_latent_ordered() <= upper && chunk_upper.to_latent_ordered() >= lower
+          });
+
+      if !could_match && self.has_chunk_sizes() {
+        let span = self.peek_chunk_span(src)?.unwrap();
+        src = &src[span.end..];
+        chunk_start += n;
+        continue;
+      }
+
+      let mut chunk_decompressor =
+        ChunkDecompressor::new(inner_cd, n, rest, self.has_page_counts())?;
+      let mut batch_start = chunk_start;
+      loop {
+        let progress = chunk_decompressor.decompress(&mut batch)?;
+        if could_match {
+          for (i, x) in batch[..progress.n_processed].iter().enumerate() {
+            if is_match(x) {
+              res.push((batch_start + i, *x));
+            }
+          }
+        }
+        batch_start += progress.n_processed;
+        if progress.finished {
+          break;
+        }
+      }
+      src = chunk_decompressor.into_src()?;
+      chunk_start += n;
+    }
+    Ok(res)
+  }
+
   /// Peeks at what's next in the file, returning whether it's a termination
   /// or chunk with some data type.
   ///
@@ -125,55 +518,226 @@ impl FileDecompressor {
   /// Reads a chunk's metadata and returns either a `ChunkDecompressor` or
   /// the rest of the source if at the end of the pco file.
   ///
-  /// Will return an error if corruptions or insufficient
-  /// data are found.
+  /// Will return an error if the file is nullable (see
+  /// [`is_nullable`][FileDecompressor::is_nullable]), or if corruptions or
+  /// insufficient data are found.
   pub fn chunk_decompressor<T: NumberLike, R: BetterBufRead>(
     &self,
-    mut src: R,
+    src: R,
   ) -> PcoResult<MaybeChunkDecompressor<T, R>> {
-    bit_reader::ensure_buf_read_capacity(&mut src, STANDALONE_CHUNK_PREAMBLE_PADDING);
-    let mut reader_builder = BitReaderBuilder::new(src, STANDALONE_CHUNK_PREAMBLE_PADDING, 0);
-    let dtype_or_termination_byte =
-      reader_builder.with_reader(|reader| Ok(reader.read_aligned_bytes(1)?[0]))?;
-    if dtype_or_termination_byte == MAGIC_TERMINATION_BYTE {
-      return Ok(MaybeChunkDecompressor::EndOfData(
-        reader_builder.into_inner(),
+    self.check_not_nullable()?;
+    self.raw_chunk_decompressor(src)
+  }
+
+  fn raw_chunk_decompressor<T: NumberLike, R: BetterBufRead>(
+    &self,
+    src: R,
+  ) -> PcoResult<MaybeChunkDecompressor<T, R>> {
+    let (maybe_chunk, src) = self.read_chunk_meta::<T, R>(src)?;
+    let (n, inner_cd) = match maybe_chunk {
+      Some(chunk) => chunk,
+      None => return Ok(MaybeChunkDecompressor::EndOfData(src)),
+    };
+
+    let res = ChunkDecompressor::new(inner_cd, n, src, self.has_page_counts())?;
+    Ok(MaybeChunkDecompressor::Some(res))
+  }
+
+  /// Reads and decompresses an entire chunk of nullable numbers, as written
+  /// by a [`NullableChunkCompressor`][crate::standalone::NullableChunkCompressor],
+  /// returning it (or `None` if at the end of the pco file) and the rest of
+  /// the source.
+  ///
+  /// Will return an error if the file isn't nullable (see
+  /// [`is_nullable`][FileDecompressor::is_nullable]), or if corruptions or
+  /// insufficient data are found.
+  pub fn decompress_nullable_chunk<T: NumberLike, R: BetterBufRead>(
+    &self,
+    src: R,
+  ) -> PcoResult<(Option<NullableChunk<T>>, R)> {
+    if !self.is_nullable {
+      return Err(PcoError::invalid_argument(
+        "file is not nullable and must be read with chunk_decompressor",
       ));
     }
 
-    if dtype_or_termination_byte != T::DTYPE_BYTE {
-      return Err(PcoError::corruption(format!(
-        "data type byte does not match {:?}; instead found {:?}",
-        T::DTYPE_BYTE,
-        dtype_or_termination_byte,
-      )));
+    let mut validity_cd = match self.raw_chunk_decompressor::<T, R>(src)? {
+      MaybeChunkDecompressor::Some(cd) => cd,
+      MaybeChunkDecompressor::EndOfData(src) => return Ok((None, src)),
+    };
+    let mut validity_flags = Vec::new();
+    validity_cd.decompress_remaining_extend(&mut validity_flags)?;
+    let mut src = validity_cd.into_src()?;
+
+    let mut validity = Vec::with_capacity(validity_flags.len());
+    for flag in validity_flags {
+      let latent = flag.to_latent_ordered();
+      if latent > T::L::ONE {
+        return Err(PcoError::corruption(format!(
+          "invalid validity flag latent: {}",
+          latent
+        )));
+      }
+      validity.push(latent == T::L::ONE);
     }
 
-    let n = reader_builder
-      .with_reader(|reader| unsafe { Ok(reader.read_usize(BITS_TO_ENCODE_N_ENTRIES) + 1) })?;
-    let src = reader_builder.into_inner();
-    let (inner_cd, src) = self.inner.chunk_decompressor::<T, R>(src)?;
-    let inner_pd = inner_cd.page_decompressor(src, n)?;
+    let n_valid = validity.iter().filter(|&&is_valid| is_valid).count();
+    let mut valid_nums = Vec::with_capacity(n_valid);
+    if n_valid > 0 {
+      let mut values_cd = match self.raw_chunk_decompressor::<T, R>(src)? {
+        MaybeChunkDecompressor::Some(cd) => cd,
+        MaybeChunkDecompressor::EndOfData(_) => {
+          return Err(PcoError::corruption(
+            "nullable chunk ended before its valid numbers",
+          ))
+        }
+      };
+      if values_cd.n() != n_valid {
+        return Err(PcoError::corruption(format!(
+          "count of valid numbers does not match validity ({} != {})",
+          values_cd.n(),
+          n_valid,
+        )));
+      }
+      values_cd.decompress_remaining_extend(&mut valid_nums)?;
+      src = values_cd.into_src()?;
+    }
 
-    let res = ChunkDecompressor {
-      inner_cd,
-      inner_pd,
-      n,
-      n_processed: 0,
-    };
-    Ok(MaybeChunkDecompressor::Some(res))
+    let mut valid_nums = valid_nums.into_iter();
+    let nums = validity
+      .iter()
+      .map(|&is_valid| {
+        if is_valid {
+          valid_nums.next().unwrap()
+        } else {
+          T::default()
+        }
+      })
+      .collect();
+    Ok((Some(NullableChunk { nums, validity }), src))
   }
 }
 
+/// A fully decompressed chunk of nullable numbers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NullableChunk<T: NumberLike> {
+  /// The numbers, with a default value in place of each null.
+  pub nums: Vec<T>,
+  /// `false` for each null number.
+  pub validity: Vec<bool>,
+}
+
 /// Holds metadata about a chunk and supports decompression.
 pub struct ChunkDecompressor<T: NumberLike, R: BetterBufRead> {
   inner_cd: wrapped::ChunkDecompressor<T>,
-  inner_pd: wrapped::PageDecompressor<T, R>,
+  // only `None` after failing to start a page, after which every method
+  // returns an error
+  inner_pd: Option<wrapped::PageDecompressor<T, R>>,
+  has_page_counts: bool,
   n: usize,
   n_processed: usize,
+  // the range of numbers in the chunk covered by the current page
+  page_start: usize,
+  page_end: usize,
+}
+
+fn failed_page_error() -> PcoError {
+  PcoError::corruption("chunk decompressor failed to start its next page")
+}
+
+// Reads the varint count of numbers that precedes each page in standalone
+// versions >= 5.
+fn read_page_n<R: BetterBufRead>(mut src: R) -> PcoResult<(usize, R)> {
+  bit_reader::ensure_buf_read_capacity(&mut src, STANDALONE_CHUNK_PREAMBLE_PADDING);
+  let mut reader_builder = BitReaderBuilder::new(src, STANDALONE_CHUNK_PREAMBLE_PADDING, 0);
+  let page_n =
+    reader_builder.with_reader(|reader| unsafe { read_varint(reader, "page n") })? as usize;
+  Ok((page_n, reader_builder.into_inner()))
 }
 
 impl<T: NumberLike, R: BetterBufRead> ChunkDecompressor<T, R> {
+  fn new(
+    inner_cd: wrapped::ChunkDecompressor<T>,
+    n: usize,
+    src: R,
+    has_page_counts: bool,
+  ) -> PcoResult<Self> {
+    let mut res = Self {
+      inner_cd,
+      inner_pd: None,
+      has_page_counts,
+      n,
+      n_processed: 0,
+      page_start: 0,
+      page_end: 0,
+    };
+    res.start_page(src)?;
+    Ok(res)
+  }
+
+  fn start_page(&mut self, src: R) -> PcoResult<()> {
+    let (page_n, src) = if self.has_page_counts {
+      read_page_n(src)?
+    } else {
+      (self.n, src)
+    };
+
+    let n_remaining = self.n - self.page_end;
+    // Every page but the last holds whole batches, so destinations stay
+    // batch-aligned from one page to the next.
+    if page_n == 0 || page_n > n_remaining || (page_n < n_remaining && page_n % FULL_BATCH_N != 0) {
+      return Err(PcoError::corruption(format!(
+        "invalid page n ({}) with {} numbers remaining in chunk",
+        page_n, n_remaining,
+      )));
+    }
+
+    self.inner_pd = Some(self.inner_cd.page_decompressor(src, page_n)?);
+    self.page_start = self.page_end;
+    self.page_end += page_n;
+    Ok(())
+  }
+
+  fn inner_pd(&mut self) -> PcoResult<&mut wrapped::PageDecompressor<T, R>> {
+    self.inner_pd.as_mut().ok_or_else(failed_page_error)
+  }
+
+  // If the current page is done, reads the next page's metadata, if any.
+  fn advance_if_page_finished(&mut self) -> PcoResult<()> {
+    if self.n_processed == self.page_end && self.page_end < self.n {
+      let src = self.inner_pd.take().unwrap().into_src();
+      self.start_page(src)?;
+    }
+    Ok(())
+  }
+
+  // Decompresses into `dst` one page at a time, until either is finished.
+  fn decompress_pages<D>(
+    &mut self,
+    dst: &mut [D],
+    decompress_page: impl Fn(&mut wrapped::PageDecompressor<T, R>, &mut [D]) -> PcoResult<Progress>,
+  ) -> PcoResult<Progress> {
+    let mut n_processed = 0;
+    loop {
+      let progress = decompress_page(self.inner_pd()?, &mut dst[n_processed..])?;
+      n_processed += progress.n_processed;
+      self.n_processed += progress.n_processed;
+      if !progress.finished {
+        break;
+      }
+
+      self.advance_if_page_finished()?;
+      if self.n_processed == self.n || n_processed == dst.len() {
+        break;
+      }
+    }
+
+    Ok(Progress {
+      n_processed,
+      finished: self.n_processed == self.n,
+    })
+  }
+
   /// Returns pre-computed information about the chunk.
   pub fn meta(&self) -> &ChunkMeta<T::L> {
     &self.inner_cd.meta
@@ -192,16 +756,77 @@ impl<T: NumberLike, R: BetterBufRead> ChunkDecompressor<T, R> {
   /// `dst` must have length either a multiple of 256 or be at least the count
   /// of numbers remaining in the chunk.
   pub fn decompress(&mut self, dst: &mut [T]) -> PcoResult<Progress> {
-    let progress = self.inner_pd.decompress(dst)?;
+    self.decompress_pages(dst, |inner_pd, dst| inner_pd.decompress(dst))
+  }
 
-    self.n_processed += progress.n_processed;
+  /// Reads the next decompressed numbers into a destination of another
+  /// type, converting each one losslessly.
+  ///
+  /// See [`wrapped::PageDecompressor::decompress_as`] for details.
+  pub fn decompress_as<D: From<T>>(&mut self, dst: &mut [D]) -> PcoResult<Progress> {
+    self.decompress_pages(dst, |inner_pd, dst| {
+      inner_pd.decompress_as(dst)
+    })
+  }
 
-    Ok(progress)
+  /// Computes the aggregate over the numbers remaining in the chunk,
+  /// advancing to the end of the chunk without writing them all out.
+  ///
+  /// See [`wrapped::PageDecompressor::aggregate`] for details.
+  pub fn aggregate(&mut self, agg: Agg) -> PcoResult<AggValue<T>> {
+    let mut aggregator = Aggregator::new(agg);
+    while self.n_processed < self.n {
+      let value = self.inner_pd()?.aggregate(agg)?;
+      aggregator.update_value(value);
+      self.n_processed = self.page_end;
+      self.advance_if_page_finished()?;
+    }
+    Ok(aggregator.finish())
+  }
+
+  /// Advances to the start of the given batch, so the next call to
+  /// [`decompress`][ChunkDecompressor::decompress] begins at number
+  /// `batch_idx * 256`.
+  ///
+  /// See [`wrapped::PageDecompressor::skip_to_batch`] for details.
+  pub fn skip_to_batch(&mut self, batch_idx: usize) -> PcoResult<()> {
+    let n_batches = self.n.div_ceil(FULL_BATCH_N);
+    if batch_idx > n_batches {
+      return Err(PcoError::invalid_argument(format!(
+        "batch idx exceeds num batches ({} > {})",
+        batch_idx, n_batches,
+      )));
+    }
+    let target = min(batch_idx * FULL_BATCH_N, self.n);
+    if target < self.n_processed {
+      return Err(PcoError::invalid_argument(format!(
+        "unable to skip backward to number {} from {}",
+        target, self.n_processed,
+      )));
+    }
+
+    // each page starts at a batch boundary
+    loop {
+      let page_target = min(target, self.page_end);
+      let page_batch_idx = (page_target - self.page_start).div_ceil(FULL_BATCH_N);
+      self.inner_pd()?.skip_to_batch(page_batch_idx)?;
+      self.n_processed = page_target;
+      self.advance_if_page_finished()?;
+      if self.n_processed == target {
+        return Ok(());
+      }
+    }
   }
 
   /// Returns the rest of the compressed data source.
-  pub fn into_src(self) -> R {
-    self.inner_pd.into_src()
+  ///
+  /// Will return a corruption error if a previous call failed to start one
+  /// of the chunk's pages, since the source was consumed in the process.
+  pub fn into_src(self) -> PcoResult<R> {
+    self
+      .inner_pd
+      .map(wrapped::PageDecompressor::into_src)
+      .ok_or_else(failed_page_error)
   }
 
   // a helper for some internal things
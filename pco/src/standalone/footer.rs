@@ -0,0 +1,181 @@
+use std::collections::BTreeMap;
+
+use crate::bit_reader::BitReader;
+use crate::bit_writer::BitWriter;
+use crate::constants::OVERSHOOT_PADDING;
+use crate::errors::{PcoError, PcoResult};
+use crate::standalone::compressor::write_varint;
+use crate::standalone::constants::*;
+use crate::standalone::decompressor::read_varint;
+use crate::standalone::ChunkSpan;
+
+/// Information stored in the optional footer of a standalone file.
+///
+/// This is written by
+/// [`FileCompressor::write_footer_with_metadata`][crate::standalone::FileCompressor::write_footer_with_metadata]
+/// and read by
+/// [`FileDecompressor::metadata`][crate::standalone::FileDecompressor::metadata].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileMetadata {
+  /// The location of each chunk, with byte offsets relative to the end of
+  /// the file's header.
+  ///
+  /// This may be left empty if unknown.
+  /// Otherwise, the chunks must be contiguous, starting at offset 0.
+  pub chunk_spans: Vec<ChunkSpan>,
+  /// Arbitrary key/value pairs, e.g. a column name, units, or the data type
+  /// the numbers came from.
+  pub key_values: BTreeMap<String, String>,
+}
+
+fn validate_chunk_spans(chunk_spans: &[ChunkSpan]) -> PcoResult<()> {
+  let mut prev_end = 0;
+  for span in chunk_spans {
+    if span.start != prev_end || span.end <= span.start || span.n == 0 {
+      return Err(PcoError::invalid_argument(format!(
+        "chunk spans must be nonempty and contiguous from offset 0; found {:?} \
+         after offset {}",
+        span, prev_end,
+      )));
+    }
+    prev_end = span.end;
+  }
+  Ok(())
+}
+
+pub(crate) fn write_varint_aligned(n: usize, dst: &mut Vec<u8>) -> PcoResult<()> {
+  let mut writer = BitWriter::new(dst, STANDALONE_CHUNK_PREAMBLE_PADDING);
+  unsafe {
+    write_varint(n as u64, &mut writer);
+  }
+  writer.finish_byte();
+  writer.flush()
+}
+
+pub(crate) unsafe fn read_varint_aligned(reader: &mut BitReader, name: &str) -> PcoResult<usize> {
+  Ok(read_varint(reader, name)? as usize)
+}
+
+pub(crate) fn write_bytes(bytes: &[u8], dst: &mut Vec<u8>) -> PcoResult<()> {
+  write_varint_aligned(bytes.len(), dst)?;
+  dst.extend_from_slice(bytes);
+  Ok(())
+}
+
+// Returns the footer's body followed by its trailer, which repeats the body
+// size so the footer can be found from the end of the file.
+pub(crate) fn write_footer(metadata: &FileMetadata) -> PcoResult<Vec<u8>> {
+  validate_chunk_spans(&metadata.chunk_spans)?;
+
+  let mut res = Vec::new();
+  write_varint_aligned(metadata.chunk_spans.len(), &mut res)?;
+  for span in &metadata.chunk_spans {
+    write_varint_aligned(span.n, &mut res)?;
+    write_varint_aligned(span.end - span.start, &mut res)?;
+  }
+  write_varint_aligned(metadata.key_values.len(), &mut res)?;
+  for (key, value) in &metadata.key_values {
+    write_bytes(key.as_bytes(), &mut res)?;
+    write_bytes(value.as_bytes(), &mut res)?;
+  }
+
+  let body_size = u32::try_from(res.len()).map_err(|_| {
+    PcoError::invalid_argument(format!(
+      "footer metadata is too large ({} bytes)",
+      res.len()
+    ))
+  })?;
+  res.extend_from_slice(&body_size.to_le_bytes());
+  res.extend_from_slice(&MAGIC_FOOTER);
+  Ok(res)
+}
+
+// Returns the body size recorded in a footer's trailer.
+pub(crate) fn parse_footer_trailer(trailer: &[u8]) -> PcoResult<usize> {
+  let (size_bytes, magic) = trailer.split_at(FOOTER_TRAILER_SIZE - MAGIC_FOOTER.len());
+  if magic != MAGIC_FOOTER {
+    return Err(PcoError::corruption(format!(
+      "magic footer does not match {:?}; instead found {:?}",
+      MAGIC_FOOTER, magic,
+    )));
+  }
+  Ok(u32::from_le_bytes(size_bytes.try_into().unwrap()) as usize)
+}
+
+// Reads a length-prefixed UTF-8 string, as written by `write_bytes`, from a
+// reader over `src_size` unpadded bytes.
+pub(crate) unsafe fn read_string(
+  reader: &mut BitReader,
+  src_size: usize,
+  name: &str,
+) -> PcoResult<String> {
+  let len = read_varint(reader, name)? as usize;
+  let start = reader.bit_idx() / 8;
+  if len > src_size.saturating_sub(start) {
+    return Err(PcoError::insufficient_data(format!(
+      "{} of {} bytes exceeds the {} bytes available",
+      name,
+      len,
+      src_size.saturating_sub(start),
+    )));
+  }
+  let bytes = reader.read_aligned_bytes(len)?;
+  String::from_utf8(bytes.to_vec())
+    .map_err(|_| PcoError::corruption(format!("{} is not valid UTF-8", name)))
+}
+
+// Parses a footer's body followed by its trailer.
+pub(crate) fn parse_footer(footer: &[u8]) -> PcoResult<FileMetadata> {
+  let body_size = footer
+    .len()
+    .checked_sub(FOOTER_TRAILER_SIZE)
+    .ok_or_else(|| PcoError::insufficient_data("footer is shorter than its trailer"))?;
+  let recorded_body_size = parse_footer_trailer(&footer[body_size..])?;
+  if recorded_body_size != body_size {
+    return Err(PcoError::corruption(format!(
+      "footer sizes do not match ({} != {})",
+      recorded_body_size, body_size,
+    )));
+  }
+
+  let mut padded = vec![0; body_size + OVERSHOOT_PADDING];
+  padded[..body_size].copy_from_slice(&footer[..body_size]);
+  let mut reader = BitReader::new(&padded, body_size, 0);
+  let metadata = unsafe {
+    let n_chunks = read_varint(&mut reader, "chunk count")? as usize;
+    let mut chunk_spans = Vec::with_capacity(n_chunks.min(body_size));
+    let mut start = 0_usize;
+    for _ in 0..n_chunks {
+      let n = read_varint(&mut reader, "chunk n")? as usize;
+      let size = read_varint(&mut reader, "chunk size")? as usize;
+      let end = start
+        .checked_add(size)
+        .ok_or_else(|| PcoError::corruption("footer chunk offsets overflow"))?;
+      chunk_spans.push(ChunkSpan { n, start, end });
+      start = end;
+    }
+
+    let n_key_values = read_varint(&mut reader, "key value count")? as usize;
+    let mut key_values = BTreeMap::new();
+    for _ in 0..n_key_values {
+      let key = read_string(&mut reader, body_size, "footer key")?;
+      let value = read_string(&mut reader, body_size, "footer value")?;
+      key_values.insert(key, value);
+    }
+
+    FileMetadata {
+      chunk_spans,
+      key_values,
+    }
+  };
+
+  if reader.bit_idx() != body_size * 8 {
+    return Err(PcoError::corruption(format!(
+      "footer has {} unexpected trailing bytes",
+      body_size - reader.bit_idx() / 8,
+    )));
+  }
+  validate_chunk_spans(&metadata.chunk_spans)
+    .map_err(|_| PcoError::corruption("footer chunk spans are invalid"))?;
+  Ok(metadata)
+}
@@ -1,39 +1,68 @@
 use crate::data_types::Latent;
 use crate::errors::PcoResult;
+use crate::standalone::compressor::{standalone_n_per_page, varint_power};
 use crate::standalone::constants::{
-  BITS_TO_ENCODE_N_ENTRIES, BITS_TO_ENCODE_STANDALONE_VERSION, BITS_TO_ENCODE_VARINT_POWER,
+  BITS_TO_ENCODE_HEADER_FLAGS, BITS_TO_ENCODE_STANDALONE_VERSION, BITS_TO_ENCODE_VARINT_POWER,
   MAGIC_HEADER,
 };
 use crate::wrapped::guarantee as wrapped_guarantee;
-use crate::PagingSpec;
+use crate::{ChunkConfig, PagingSpec};
 
 /// Returns the maximum possible byte size of a standalone header.
 pub fn header_size() -> usize {
   let max_varint_bits = BITS_TO_ENCODE_VARINT_POWER + 64;
   MAGIC_HEADER.len()
     + (max_varint_bits + BITS_TO_ENCODE_STANDALONE_VERSION).div_ceil(8) as usize
+    + BITS_TO_ENCODE_HEADER_FLAGS.div_ceil(8) as usize
     + wrapped_guarantee::header_size()
 }
 
-/// Returns the maximum possible byte size of a standalone chunk for a given
-/// latent type (e.g. u32 or u64) and count of numbers.
-pub fn chunk_size<L: Latent>(n: usize) -> usize {
-  1 + BITS_TO_ENCODE_N_ENTRIES.div_ceil(8) as usize + wrapped_guarantee::chunk_size::<L>(n)
+// the exact byte size of a byte-aligned varint
+pub(crate) fn varint_size(x: usize) -> usize {
+  (BITS_TO_ENCODE_VARINT_POWER + varint_power(x as u64)).div_ceil(8) as usize
 }
 
-/// Returns the maximum possible byte size of a standalone file given a
-/// latent type (e.g. u32 or u64), count of numbers, and `PagingSpec`.
+// the exact byte size of a chunk's dtype byte, count, and body size
+pub(crate) fn chunk_preamble_size(n: usize, body_size: usize) -> usize {
+  1 + varint_size(n) + varint_size(body_size)
+}
+
+/// Returns the maximum possible byte size of a standalone chunk for a given
+/// latent type (e.g. u32 or u64), count of numbers, and `PagingSpec` for its
+/// pages.
+///
+/// Like [`wrapped::guarantee::chunk_size`][crate::wrapped::guarantee::chunk_size],
+/// this assumes the chunk has no seek index.
 ///
 /// Will return an invalid argument error if the paging spec is invalid.
-pub fn file_size<L: Latent>(n: usize, paging_spec: &PagingSpec) -> PcoResult<usize> {
-  let n_per_chunk = paging_spec.n_per_page(n)?;
-  let res = header_size()
-    + n_per_chunk
+pub fn chunk_size<L: Latent>(n: usize, paging_spec: &PagingSpec) -> PcoResult<usize> {
+  let n_per_page = standalone_n_per_page(paging_spec, n)?;
+  let body_size = wrapped_guarantee::chunk_size::<L>(n)
+    + n_per_page
       .iter()
-      .map(|&chunk_n| chunk_size::<L>(chunk_n))
-      .sum::<usize>()
-    + 1;
-  Ok(res)
+      .map(|&page_n| varint_size(page_n))
+      .sum::<usize>();
+  Ok(chunk_preamble_size(n, body_size) + body_size)
+}
+
+/// Returns the maximum possible byte size of a standalone file given a
+/// latent type (e.g. u32 or u64), count of numbers, and `ChunkConfig`, whose
+/// [`chunking_spec`][ChunkConfig::chunking_spec] and
+/// [`paging_spec`][ChunkConfig::paging_spec] determine the file's chunks and
+/// pages.
+///
+/// This assumes the file's footer has no metadata, as written by
+/// [`FileCompressor::write_footer`][crate::standalone::FileCompressor::write_footer].
+///
+/// Will return an invalid argument error if the chunking or paging spec is
+/// invalid.
+pub fn file_size<L: Latent>(n: usize, config: &ChunkConfig) -> PcoResult<usize> {
+  let mut res = header_size();
+  for chunk_n in config.chunking_spec.n_per_chunk(n)? {
+    res += chunk_size::<L>(chunk_n, &config.paging_spec)?;
+  }
+  // the footer is a termination byte and a 1-byte empty footer size
+  Ok(res + 2)
 }
 
 #[cfg(test)]
@@ -60,7 +89,7 @@ mod tests {
 
   fn check_file_guarantee<T: NumberLike>(nums: &[T], config: &ChunkConfig) -> PcoResult<()> {
     let compressed = simple_compress(nums, config)?;
-    assert!(compressed.len() <= file_size::<T::L>(nums.len(), &config.paging_spec)?);
+    assert!(compressed.len() <= file_size::<T::L>(nums.len(), config)?);
     Ok(())
   }
 
@@ -74,13 +103,14 @@ mod tests {
   fn test_file_guarantee_antagonistic() -> PcoResult<()> {
     let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
     let mut nums = Vec::new();
-    for _i in 0..300 {
+    for _i in 0..3000 {
       nums.push(rng.gen_range(-1.0_f32..1.0));
     }
     let config = ChunkConfig {
       float_mult_spec: FloatMultSpec::Provided(0.1),
       delta_encoding_order: Some(5),
       paging_spec: PagingSpec::EqualPagesUpTo(10),
+      chunking_spec: PagingSpec::EqualPagesUpTo(1000),
       ..Default::default()
     };
     check_file_guarantee(&nums, &config)
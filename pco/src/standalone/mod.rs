@@ -1,11 +1,22 @@
-pub use compressor::{ChunkCompressor, FileCompressor};
-pub use decompressor::{ChunkDecompressor, FileDecompressor, MaybeChunkDecompressor};
+pub use appender::FileAppender;
+pub use compressor::{ChunkCompressor, FileCompressor, NullableChunkCompressor};
+pub use decompressor::{
+  ChunkDecompressor, ChunkSpan, FileDecompressor, MaybeChunkDecompressor, NullableChunk,
+};
 pub use dtype_or_termination::DataTypeOrTermination;
-pub use simple::{simple_compress, simple_decompress, simple_decompress_into, simpler_compress};
+pub use footer::FileMetadata;
+#[cfg(feature = "parallel")]
+pub use simple::simple_decompress_parallel;
+pub use simple::{
+  decompress_range, simple_compress, simple_compress_nullable, simple_decompress,
+  simple_decompress_into, simple_decompress_nullable, simpler_compress,
+};
 
+mod appender;
 mod compressor;
 mod constants;
 mod decompressor;
 mod dtype_or_termination;
+pub(crate) mod footer;
 pub mod guarantee;
 mod simple;
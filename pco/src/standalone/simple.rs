@@ -1,46 +1,71 @@
 use std::cmp::min;
+use std::ops::Range;
 
 use crate::chunk_config::ChunkConfig;
 use crate::data_types::NumberLike;
-use crate::errors::PcoResult;
+use crate::errors::{PcoError, PcoResult};
 use crate::progress::Progress;
-use crate::standalone::compressor::FileCompressor;
-use crate::standalone::decompressor::{FileDecompressor, MaybeChunkDecompressor};
-use crate::{PagingSpec, FULL_BATCH_N};
+use crate::standalone::compressor::{ChunkCompressor, FileCompressor};
+use crate::standalone::decompressor::{FileDecompressor, MaybeChunkDecompressor, NullableChunk};
+use crate::FULL_BATCH_N;
 
 /// Takes in a slice of numbers and an exact configuration and returns
 /// compressed bytes.
 ///
 /// Will return an error if the compressor config is invalid.
-/// This will use the `PagingSpec` in `ChunkConfig` to decide where to split
-/// chunks.
-/// For standalone, the concepts of chunk and page are conflated since each
-/// chunk has exactly one page.
+/// This will use the config's
+/// [`chunking_spec`][ChunkConfig::chunking_spec] to decide where to split
+/// chunks and its [`paging_spec`][ChunkConfig::paging_spec] to split each
+/// chunk into pages.
+/// With the `parallel` feature, chunks are compressed on a thread pool; the
+/// output is the same either way.
 pub fn simple_compress<T: NumberLike>(nums: &[T], config: &ChunkConfig) -> PcoResult<Vec<u8>> {
   let mut dst = Vec::new();
   let file_compressor = FileCompressor::default().with_n_hint(nums.len());
   file_compressor.write_header(&mut dst)?;
 
-  // here we use the paging spec to determine chunks; each chunk has 1 page
-  let n_per_page = config.paging_spec.n_per_page(nums.len())?;
+  let n_per_chunk = config.chunking_spec.n_per_chunk(nums.len())?;
+  let mut chunk_nums = Vec::with_capacity(n_per_chunk.len());
   let mut start = 0;
-  let mut this_chunk_config = config.clone();
-  let mut hinted_size = false;
-  for &page_n in &n_per_page {
-    let end = start + page_n;
-    this_chunk_config.paging_spec = PagingSpec::Exact(vec![page_n]);
-    let chunk_compressor =
-      file_compressor.chunk_compressor(&nums[start..end], &this_chunk_config)?;
+  for &chunk_n in &n_per_chunk {
+    let end = start + chunk_n;
+    chunk_nums.push(&nums[start..end]);
+    start = end;
+  }
 
+  let mut hinted_size = false;
+  let mut write_chunk = |chunk_compressor: ChunkCompressor<T::L>, chunk_n: usize| {
     if !hinted_size {
       let file_size_hint =
-        chunk_compressor.chunk_size_hint() as f64 * nums.len() as f64 / page_n as f64;
+        chunk_compressor.chunk_size_hint() as f64 * nums.len() as f64 / chunk_n as f64;
       dst.reserve_exact(file_size_hint as usize + 10);
       hinted_size = true;
     }
 
-    chunk_compressor.write_chunk(&mut dst)?;
-    start = end;
+    chunk_compressor.write_chunk(&mut dst).map(|_| ())
+  };
+
+  #[cfg(not(feature = "parallel"))]
+  for &chunk in &chunk_nums {
+    write_chunk(
+      file_compressor.chunk_compressor(chunk, config)?,
+      chunk.len(),
+    )?;
+  }
+
+  // We compress one chunk per thread at a time and write them in order, so
+  // the output is identical to the serial path.
+  #[cfg(feature = "parallel")]
+  for chunk_group in chunk_nums.chunks(rayon::current_num_threads()) {
+    use rayon::prelude::*;
+
+    let chunk_compressors = chunk_group
+      .par_iter()
+      .map(|&chunk| file_compressor.chunk_compressor(chunk, config))
+      .collect::<PcoResult<Vec<_>>>()?;
+    for (chunk_compressor, chunk) in chunk_compressors.into_iter().zip(chunk_group) {
+      write_chunk(chunk_compressor, chunk.len())?;
+    }
   }
 
   file_compressor.write_footer(&mut dst)?;
@@ -94,7 +119,7 @@ pub fn simple_decompress_into<T: NumberLike>(src: &[u8], mut dst: &mut [T]) -> P
       break;
     }
 
-    src = chunk_decompressor.into_src();
+    src = chunk_decompressor.into_src()?;
   }
   Ok(progress)
 }
@@ -126,14 +151,251 @@ pub fn simple_decompress<T: NumberLike>(src: &[u8]) -> PcoResult<Vec<T>> {
     file_decompressor.chunk_decompressor(src)?
   {
     chunk_decompressor.decompress_remaining_extend(&mut res)?;
-    src = chunk_decompressor.into_src();
+    src = chunk_decompressor.into_src()?;
+  }
+  Ok(res)
+}
+
+/// Takes in compressed bytes and returns only the numbers in the given range
+/// of indices.
+///
+/// Chunks before the range are skipped without decompressing when the file
+/// records chunk sizes (see [`FileDecompressor::has_chunk_sizes`]).
+/// Within a chunk, batches before the range are skipped using the chunk's
+/// seek index if it has one, or otherwise decoded and discarded.
+/// Will return an error if the range is invalid or extends past the end of
+/// the file, or if there are any compatibility, corruption, or insufficient
+/// data issues.
+pub fn decompress_range<T: NumberLike>(src: &[u8], range: Range<usize>) -> PcoResult<Vec<T>> {
+  let Range { start, end } = range;
+  if start > end {
+    return Err(PcoError::invalid_argument(format!(
+      "range start must not exceed end ({} > {})",
+      start, end,
+    )));
+  }
+
+  let (file_decompressor, mut src) = FileDecompressor::new(src)?;
+  file_decompressor.check_not_nullable()?;
+  let mut res = Vec::with_capacity(min(end - start, file_decompressor.n_hint()));
+  let mut scratch = vec![T::default(); FULL_BATCH_N];
+  // the index of the first number in the current chunk
+  let mut chunk_start = 0;
+  while chunk_start < end {
+    if file_decompressor.has_chunk_sizes() {
+      match file_decompressor.peek_chunk_span(src)? {
+        Some(span) if chunk_start + span.n <= start => {
+          src = &src[span.end..];
+          chunk_start += span.n;
+          continue;
+        }
+        Some(_) => (),
+        None => break,
+      }
+    }
+
+    let mut chunk_decompressor = match file_decompressor.chunk_decompressor::<T, _>(src)? {
+      MaybeChunkDecompressor::Some(cd) => cd,
+      MaybeChunkDecompressor::EndOfData(_) => break,
+    };
+    let chunk_end = chunk_start + chunk_decompressor.n();
+    let stop = min(end, chunk_end);
+
+    // skip whole batches before the range, or the whole chunk if it ends
+    // before the range
+    let mut pos = chunk_start;
+    if start > chunk_start {
+      let batch_idx = min(
+        (start - chunk_start) / FULL_BATCH_N,
+        chunk_decompressor.n().div_ceil(FULL_BATCH_N),
+      );
+      chunk_decompressor.skip_to_batch(batch_idx)?;
+      pos += batch_idx * FULL_BATCH_N;
+    }
+
+    while pos < stop {
+      if pos >= start && (stop == chunk_end || stop - pos >= FULL_BATCH_N) {
+        // we can decompress straight into the result
+        let n_to_decompress = if stop == chunk_end {
+          chunk_end - pos
+        } else {
+          (stop - pos) / FULL_BATCH_N * FULL_BATCH_N
+        };
+        let initial_len = res.len();
+        res.resize(initial_len + n_to_decompress, T::default());
+        chunk_decompressor.decompress(&mut res[initial_len..])?;
+        pos += n_to_decompress;
+      } else {
+        // this batch is only partly in the range
+        let progress = chunk_decompressor.decompress(&mut scratch)?;
+        let batch_end = pos + progress.n_processed;
+        let lo = start.saturating_sub(pos);
+        let hi = min(stop, batch_end) - pos;
+        if lo < hi {
+          res.extend_from_slice(&scratch[lo..hi]);
+        }
+        pos = batch_end;
+      }
+    }
+
+    // if we haven't hit the end yet, we must have finished this chunk
+    chunk_start = chunk_end;
+    if stop == end {
+      break;
+    }
+    src = chunk_decompressor.into_src()?;
+  }
+
+  if chunk_start < end {
+    return Err(PcoError::invalid_argument(format!(
+      "range end {} exceeds the count of numbers in the file",
+      end,
+    )));
   }
   Ok(res)
 }
 
+/// Takes in compressed bytes and returns a vector of numbers, decompressing
+/// chunks on a thread pool.
+///
+/// This uses [`FileDecompressor::chunk_spans`] to find chunk boundaries up
+/// front, so each chunk decompresses into its own disjoint part of the
+/// output.
+/// Files written before chunk sizes were recorded fall back to
+/// [`simple_decompress`].
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+#[cfg(feature = "parallel")]
+pub fn simple_decompress_parallel<T: NumberLike>(src: &[u8]) -> PcoResult<Vec<T>> {
+  use rayon::prelude::*;
+
+  let (file_decompressor, rest) = FileDecompressor::new(src)?;
+  file_decompressor.check_not_nullable()?;
+  if !file_decompressor.has_chunk_sizes() {
+    return simple_decompress(src);
+  }
+
+  let spans = file_decompressor.chunk_spans(rest)?;
+  let total_n = spans.iter().map(|span| span.n).sum();
+  let mut res = vec![T::default(); total_n];
+  let mut chunk_dsts = Vec::with_capacity(spans.len());
+  let mut remaining_dst = res.as_mut_slice();
+  for span in &spans {
+    let (chunk_dst, new_remaining_dst) = remaining_dst.split_at_mut(span.n);
+    chunk_dsts.push(chunk_dst);
+    remaining_dst = new_remaining_dst;
+  }
+
+  spans
+    .par_iter()
+    .zip(chunk_dsts)
+    .try_for_each(|(span, chunk_dst)| {
+      let chunk_src = &rest[span.start..span.end];
+      let mut chunk_decompressor = match file_decompressor.chunk_decompressor(chunk_src)? {
+        MaybeChunkDecompressor::Some(cd) => cd,
+        MaybeChunkDecompressor::EndOfData(_) => {
+          return Err(PcoError::corruption(
+            "chunk span did not contain a chunk",
+          ))
+        }
+      };
+      chunk_decompressor.decompress(chunk_dst)?;
+      if !chunk_decompressor.into_src()?.is_empty() {
+        return Err(PcoError::corruption(
+          "chunk did not end where its size indicated",
+        ));
+      }
+      Ok(())
+    })?;
+  Ok(res)
+}
+
+/// Takes in a slice of numbers, a slice of their validity, and an exact
+/// configuration and returns compressed bytes.
+///
+/// `validity` must have the same length as `nums`, and is `false` for each
+/// null number.
+/// Null numbers are skipped entirely, so their values don't matter and don't
+/// affect compression.
+/// Will return an error if the compressor config is invalid.
+/// Like [`simple_compress`], this uses the config's
+/// [`chunking_spec`][ChunkConfig::chunking_spec] to decide where to split
+/// chunks.
+pub fn simple_compress_nullable<T: NumberLike>(
+  nums: &[T],
+  validity: &[bool],
+  config: &ChunkConfig,
+) -> PcoResult<Vec<u8>> {
+  if nums.len() != validity.len() {
+    return Err(PcoError::invalid_argument(format!(
+      "validity length must match numbers length ({} != {})",
+      validity.len(),
+      nums.len(),
+    )));
+  }
+
+  let mut dst = Vec::new();
+  let file_compressor = FileCompressor::default()
+    .with_n_hint(nums.len())
+    .with_nullable(true);
+  file_compressor.write_header(&mut dst)?;
+
+  let n_per_chunk = config.chunking_spec.n_per_chunk(nums.len())?;
+  let mut start = 0;
+  for &chunk_n in &n_per_chunk {
+    let end = start + chunk_n;
+    file_compressor
+      .nullable_chunk_compressor(
+        &nums[start..end],
+        &validity[start..end],
+        config,
+      )?
+      .write_chunk(&mut dst)?;
+    start = end;
+  }
+
+  file_compressor.write_footer(&mut dst)?;
+  Ok(dst)
+}
+
+/// Takes in compressed bytes written by [`simple_compress_nullable`] and
+/// returns a vector of numbers and a vector of their validity.
+///
+/// Each null number is decompressed as the data type's default value.
+/// Will return an error if there are any compatibility, corruption,
+/// or insufficient data issues.
+pub fn simple_decompress_nullable<T: NumberLike>(src: &[u8]) -> PcoResult<(Vec<T>, Vec<bool>)> {
+  let (file_decompressor, mut src) = FileDecompressor::new(src)?;
+
+  let mut nums = Vec::with_capacity(file_decompressor.n_hint());
+  let mut validity = Vec::with_capacity(file_decompressor.n_hint());
+  loop {
+    let (maybe_chunk, rest) = file_decompressor.decompress_nullable_chunk::<T, _>(src)?;
+    let Some(chunk) = maybe_chunk else {
+      break;
+    };
+    let NullableChunk {
+      nums: chunk_nums,
+      validity: chunk_validity,
+    } = chunk;
+    nums.extend(chunk_nums);
+    validity.extend(chunk_validity);
+    src = rest;
+  }
+  Ok((nums, validity))
+}
+
 #[cfg(test)]
 mod tests {
+  use better_io::BetterBufReader;
+
   use super::*;
+  use crate::constants::MAX_ENTRIES;
+  use crate::errors::ErrorKind;
+  use crate::standalone::constants::{MAGIC_HEADER, MAGIC_TERMINATION_BYTE};
+  use crate::standalone::footer;
+  use crate::standalone::{ChunkSpan, FileMetadata};
+  use crate::{wrapped, Agg, AggValue, PagingSpec, ValueBoundsSpec};
 
   #[test]
   fn test_simple_decompress_into() -> PcoResult<()> {
@@ -144,7 +406,7 @@ mod tests {
       &ChunkConfig {
         compression_level: 0,
         delta_encoding_order: Some(0),
-        paging_spec: PagingSpec::Exact(vec![300, 300]),
+        chunking_spec: PagingSpec::Exact(vec![300, 300]),
         ..Default::default()
       },
     )?;
@@ -165,4 +427,605 @@ mod tests {
 
     Ok(())
   }
+
+  #[test]
+  fn test_nullable() -> PcoResult<()> {
+    let validity = (0..700).map(|i| i % 3 != 0 && i < 600).collect::<Vec<_>>();
+    let nums = (0..700)
+      .map(|i| if validity[i] { i as i64 } else { -999 })
+      .collect::<Vec<_>>();
+    let config = ChunkConfig {
+      // the last chunk is entirely null
+      chunking_spec: PagingSpec::Exact(vec![300, 300, 100]),
+      ..Default::default()
+    };
+    let src = simple_compress_nullable(&nums, &validity, &config)?;
+
+    let (recovered, recovered_validity) = simple_decompress_nullable::<i64>(&src)?;
+    assert_eq!(recovered_validity, validity);
+    let expected = nums
+      .iter()
+      .zip(&validity)
+      .map(|(&num, &is_valid)| if is_valid { num } else { 0 })
+      .collect::<Vec<_>>();
+    assert_eq!(recovered, expected);
+
+    // null numbers don't affect compression
+    let wild_nums = nums
+      .iter()
+      .zip(&validity)
+      .map(|(&num, &is_valid)| if is_valid { num } else { i64::MIN })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      simple_compress_nullable(&wild_nums, &validity, &config)?,
+      src
+    );
+
+    assert!(simple_compress_nullable(&nums, &validity[1..], &config).is_err());
+
+    let floats = vec![1.5_f32, f32::NAN, -0.0, 7.0];
+    let validity = vec![true, false, true, true];
+    let src = simple_compress_nullable(&floats, &validity, &ChunkConfig::default())?;
+    let (recovered, recovered_validity) = simple_decompress_nullable::<f32>(&src)?;
+    assert_eq!(recovered_validity, validity);
+    assert_eq!(
+      recovered.iter().map(|x| x.to_bits()).collect::<Vec<_>>(),
+      [1.5_f32, 0.0, -0.0, 7.0]
+        .iter()
+        .map(|x| x.to_bits())
+        .collect::<Vec<_>>(),
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_nullable_and_plain_files_dont_mix() -> PcoResult<()> {
+    fn is_invalid_argument<T>(res: PcoResult<T>) -> bool {
+      matches!(res, Err(e) if matches!(e.kind, ErrorKind::InvalidArgument))
+    }
+    let nums = (0..300).collect::<Vec<i32>>();
+    let validity = vec![true; nums.len()];
+    let nullable = simple_compress_nullable(&nums, &validity, &ChunkConfig::default())?;
+    let plain = simple_compress(&nums, &ChunkConfig::default())?;
+
+    let (file_decompressor, rest) = FileDecompressor::new(nullable.as_slice())?;
+    assert!(file_decompressor.is_nullable());
+    assert!(is_invalid_argument(
+      simple_decompress::<i32>(&nullable)
+    ));
+    assert!(is_invalid_argument(decompress_range::<i32>(
+      &nullable,
+      0..1
+    )));
+    assert!(is_invalid_argument(
+      file_decompressor.decompress_filtered(rest, 0..=1)
+    ));
+
+    let (file_decompressor, rest) = FileDecompressor::new(plain.as_slice())?;
+    assert!(!file_decompressor.is_nullable());
+    assert!(is_invalid_argument(
+      file_decompressor.decompress_nullable_chunk::<i32, _>(rest)
+    ));
+
+    let plain_fc = FileCompressor::default();
+    assert!(is_invalid_argument(
+      plain_fc.nullable_chunk_compressor(&nums, &validity, &ChunkConfig::default())
+    ));
+    let nullable_fc = FileCompressor::default().with_nullable(true);
+    assert!(is_invalid_argument(
+      nullable_fc.chunk_compressor(&nums, &ChunkConfig::default())
+    ));
+    Ok(())
+  }
+
+  #[test]
+  fn test_simple_compress_matches_chunk_by_chunk() -> PcoResult<()> {
+    let nums = (0..5000).map(|i| (i * i) % 7919).collect::<Vec<u32>>();
+    let n_per_chunk = vec![1000, 1, 999, 1500, 1500];
+    let config = ChunkConfig {
+      chunking_spec: PagingSpec::Exact(n_per_chunk.clone()),
+      ..Default::default()
+    };
+
+    let mut expected = Vec::new();
+    let file_compressor = FileCompressor::default().with_n_hint(nums.len());
+    file_compressor.write_header(&mut expected)?;
+    let mut start = 0;
+    for chunk_n in n_per_chunk {
+      file_compressor
+        .chunk_compressor(&nums[start..start + chunk_n], &config)?
+        .write_chunk(&mut expected)?;
+      start += chunk_n;
+    }
+    file_compressor.write_footer(&mut expected)?;
+
+    assert_eq!(simple_compress(&nums, &config)?, expected);
+    Ok(())
+  }
+
+  #[cfg(feature = "parallel")]
+  #[test]
+  fn test_parallel_simple_compress_matches_serial() -> PcoResult<()> {
+    // more chunks than threads, so chunks get compressed in several groups
+    let n_chunks = 4 * rayon::current_num_threads() + 3;
+    let nums = (0..n_chunks * 700)
+      .map(|i| ((i * i) % 7919) as f64 * 0.25)
+      .collect::<Vec<f64>>();
+    let config = ChunkConfig {
+      chunking_spec: PagingSpec::EqualPagesUpTo(700),
+      paging_spec: PagingSpec::EqualPagesUpTo(256),
+      ..Default::default()
+    };
+
+    let mut expected = Vec::new();
+    let file_compressor = FileCompressor::default().with_n_hint(nums.len());
+    file_compressor.write_header(&mut expected)?;
+    for chunk in nums.chunks(700) {
+      file_compressor
+        .chunk_compressor(chunk, &config)?
+        .write_chunk(&mut expected)?;
+    }
+    file_compressor.write_footer(&mut expected)?;
+
+    assert_eq!(simple_compress(&nums, &config)?, expected);
+    Ok(())
+  }
+
+  // writes a file in standalone version 2, which has no chunk sizes
+  fn compress_standalone_v2(chunks: &[&[u32]]) -> PcoResult<Vec<u8>> {
+    // the magic header, version, and a 0 n_hint
+    let mut dst = [MAGIC_HEADER.as_slice(), &[2, 0]].concat();
+    let file_compressor = wrapped::FileCompressor::default();
+    dst = file_compressor.write_header(dst)?;
+    for chunk in chunks {
+      let chunk_compressor = file_compressor.chunk_compressor(chunk, &ChunkConfig::default())?;
+      dst.push(u32::DTYPE_BYTE);
+      dst.extend(&(chunk.len() - 1).to_le_bytes()[..3]);
+      dst = chunk_compressor.write_chunk_meta(dst)?;
+      dst = chunk_compressor.write_page(0, dst)?;
+    }
+    dst.push(MAGIC_TERMINATION_BYTE);
+    Ok(dst)
+  }
+
+  #[test]
+  fn test_decompress_range_without_chunk_sizes() -> PcoResult<()> {
+    let nums = (0..2000).map(|i| (i * 7) % 1001).collect::<Vec<u32>>();
+    let src = compress_standalone_v2(&[&nums[..600], &nums[600..900], &nums[900..]])?;
+    let (file_decompressor, _) = FileDecompressor::new(src.as_slice())?;
+    assert!(!file_decompressor.has_chunk_sizes());
+    assert_eq!(simple_decompress::<u32>(&src)?, nums);
+
+    for (start, end) in [
+      (600, 600),
+      (700, 1000),
+      (1200, 1500),
+      (1999, 2000),
+      (0, 2000),
+    ] {
+      assert_eq!(
+        decompress_range::<u32>(&src, start..end)?,
+        &nums[start..end],
+        "{}..{}",
+        start,
+        end
+      );
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn test_decompress_range() -> PcoResult<()> {
+    let nums = (0..2000).map(|i| (i * 7) % 1001).collect::<Vec<u32>>();
+    let config = ChunkConfig {
+      chunking_spec: PagingSpec::Exact(vec![700, 1, 1299]),
+      ..Default::default()
+    };
+    let src = simple_compress(&nums, &config)?;
+
+    for (start, end) in [
+      (0, 0),
+      (0, 2000),
+      (0, 1),
+      (1999, 2000),
+      (256, 512),
+      (255, 513),
+      (600, 702),
+      (700, 701),
+      (701, 701),
+      (1000, 1555),
+      (512, 2000),
+    ] {
+      assert_eq!(
+        decompress_range::<u32>(&src, start..end)?,
+        &nums[start..end],
+        "start={} end={}",
+        start,
+        end,
+      );
+    }
+
+    assert!(decompress_range::<u32>(&src, 1500..2001).is_err());
+    assert!(decompress_range::<u32>(&src, 2001..2001).is_err());
+    assert!(decompress_range::<u32>(&src, 0..usize::MAX).is_err());
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = 5..4;
+    assert!(decompress_range::<u32>(&src, reversed).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_chunk_spans() -> PcoResult<()> {
+    let nums = (0..1000).map(|i| i as f64 / 7.0).collect::<Vec<_>>();
+    let config = ChunkConfig {
+      chunking_spec: PagingSpec::Exact(vec![300, 1, 699]),
+      ..Default::default()
+    };
+    let src = simple_compress(&nums, &config)?;
+
+    let (file_decompressor, rest) = FileDecompressor::new(src.as_slice())?;
+    assert!(file_decompressor.has_chunk_sizes());
+    let spans = file_decompressor.chunk_spans(rest)?;
+    assert_eq!(
+      spans.iter().map(|span| span.n).collect::<Vec<_>>(),
+      vec![300, 1, 699],
+    );
+    assert_eq!(spans[0].start, 0);
+    assert_eq!(spans[1].start, spans[0].end);
+    assert_eq!(spans[2].start, spans[1].end);
+    // only the termination byte and empty footer size remain
+    assert_eq!(spans[2].end, rest.len() - 2);
+
+    // each chunk can be decompressed on its own
+    let mut start = 0;
+    for span in &spans {
+      let mut chunk_decompressor =
+        match file_decompressor.chunk_decompressor::<f64, _>(&rest[span.start..span.end])? {
+          MaybeChunkDecompressor::Some(cd) => cd,
+          MaybeChunkDecompressor::EndOfData(_) => panic!("expected a chunk"),
+        };
+      let mut chunk_nums = Vec::new();
+      chunk_decompressor.decompress_remaining_extend(&mut chunk_nums)?;
+      assert_eq!(chunk_nums, &nums[start..start + span.n]);
+      assert!(chunk_decompressor.into_src()?.is_empty());
+      start += span.n;
+    }
+
+    // a truncated file is detected without decompressing
+    assert!(file_decompressor
+      .chunk_spans(&rest[..spans[2].end - 1])
+      .is_err());
+
+    // a corrupt chunk size is detected without overflowing
+    let mut corrupt = Vec::new();
+    FileCompressor::default().write_header(&mut corrupt)?;
+    corrupt.push(f64::DTYPE_BYTE);
+    footer::write_varint_aligned(1000, &mut corrupt)?;
+    footer::write_varint_aligned(usize::MAX - 2, &mut corrupt)?;
+    corrupt.extend([0; 10]);
+    let (file_decompressor, rest) = FileDecompressor::new(corrupt.as_slice())?;
+    assert!(matches!(
+      file_decompressor.chunk_spans(rest).unwrap_err().kind,
+      ErrorKind::Corruption
+    ));
+    assert!(matches!(
+      decompress_range::<f64>(&corrupt, 0..10).unwrap_err().kind,
+      ErrorKind::Corruption
+    ));
+    Ok(())
+  }
+
+  #[test]
+  fn test_chunk_with_multiple_pages() -> PcoResult<()> {
+    // too many numbers for one page
+    let n = MAX_ENTRIES + 1000;
+    let nums = (0..n).map(|i| (i % 7) as u16).collect::<Vec<_>>();
+    let config = ChunkConfig::default().with_compression_level(0);
+    let file_compressor = FileCompressor::default();
+    let mut src = file_compressor.write_header(Vec::new())?;
+    src = file_compressor
+      .chunk_compressor(&nums, &config)?
+      .write_chunk(src)?;
+    src = file_compressor.write_footer(src)?;
+
+    let (file_decompressor, rest) = FileDecompressor::new(src.as_slice())?;
+    let spans = file_decompressor.chunk_spans(rest)?;
+    assert_eq!(spans.len(), 1);
+    assert_eq!(spans[0].n, n);
+    assert_eq!(simple_decompress::<u16>(&src)?, nums);
+
+    let range = MAX_ENTRIES - 300..MAX_ENTRIES + 300;
+    assert_eq!(
+      decompress_range::<u16>(&src, range.clone())?,
+      &nums[range]
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn test_failed_page_start_errors_not_panics() -> PcoResult<()> {
+    let nums = (0..1000).map(|i| (i * 13) % 1009).collect::<Vec<u32>>();
+    let config = ChunkConfig::default().with_paging_spec(PagingSpec::Exact(vec![512, 488]));
+    let src = simple_compress(&nums, &config)?;
+
+    // Truncating the file partway through the second page makes starting it
+    // fail after the first page is done.
+    let mut n_failed_starts = 0;
+    for len in 0..src.len() {
+      let Ok((file_decompressor, rest)) = FileDecompressor::new(&src[..len]) else {
+        continue;
+      };
+      let Ok(MaybeChunkDecompressor::Some(mut cd)) =
+        file_decompressor.chunk_decompressor::<u32, _>(rest)
+      else {
+        continue;
+      };
+      let mut dst = vec![0; 512];
+      if cd.decompress(&mut dst).is_err() && dst == nums[..512] {
+        n_failed_starts += 1;
+        assert!(cd.decompress(&mut dst).is_err());
+        assert!(cd.aggregate(Agg::Count).is_err());
+        assert!(cd.skip_to_batch(3).is_err());
+        assert!(cd.into_src().is_err());
+      }
+    }
+    assert!(n_failed_starts > 0);
+    Ok(())
+  }
+
+  #[test]
+  fn test_paging_within_chunks() -> PcoResult<()> {
+    let nums = (0..5000).map(|i| (i * 13) % 1009).collect::<Vec<u32>>();
+    let config = ChunkConfig::default()
+      .with_chunking_spec(PagingSpec::Exact(vec![3000, 2000]))
+      .with_paging_spec(PagingSpec::EqualPagesUpTo(1000));
+    let src = simple_compress(&nums, &config)?;
+    assert_eq!(simple_decompress::<u32>(&src)?, nums);
+
+    let mut dst = vec![0; 3500];
+    let progress = simple_decompress_into(&src, &mut dst)?;
+    assert_eq!(progress.n_processed, 3500);
+    assert_eq!(dst, &nums[..3500]);
+
+    for (start, end) in [(900, 2100), (2999, 3001), (4100, 5000)] {
+      assert_eq!(
+        decompress_range::<u32>(&src, start..end)?,
+        &nums[start..end],
+      );
+    }
+
+    // aggregation and skipping both cross page boundaries
+    let (file_decompressor, rest) = FileDecompressor::new(src.as_slice())?;
+    let MaybeChunkDecompressor::Some(mut chunk_decompressor) =
+      file_decompressor.chunk_decompressor::<u32, _>(rest)?
+    else {
+      panic!("expected a chunk");
+    };
+    chunk_decompressor.skip_to_batch(5)?;
+    let mut dst = vec![0; 256];
+    chunk_decompressor.decompress(&mut dst)?;
+    assert_eq!(dst, &nums[1280..1536]);
+    let max = chunk_decompressor.aggregate(Agg::Max)?;
+    assert_eq!(
+      max,
+      AggValue::Max(nums[1536..3000].iter().max().copied()),
+    );
+
+    // pages other than the last must be whole batches
+    let invalid = config.with_paging_spec(PagingSpec::Exact(vec![1000, 2000]));
+    assert!(simple_compress(&nums, &invalid).is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_footer_metadata() -> PcoResult<()> {
+    let nums = (0..1000_i32).map(|i| i - 300).collect::<Vec<_>>();
+    let file_compressor = FileCompressor::default();
+    let mut src = Vec::new();
+    file_compressor.write_header(&mut src)?;
+    let header_size = src.len();
+    let mut metadata = FileMetadata::default();
+    for chunk in nums.chunks(400) {
+      let start = src.len() - header_size;
+      file_compressor
+        .chunk_compressor(chunk, &ChunkConfig::default())?
+        .write_chunk(&mut src)?;
+      metadata.chunk_spans.push(ChunkSpan {
+        n: chunk.len(),
+        start,
+        end: src.len() - header_size,
+      });
+    }
+    let without_footer = file_compressor.write_footer(src.clone())?;
+    metadata.key_values.insert(
+      "column".to_string(),
+      "temperature".to_string(),
+    );
+    metadata
+      .key_values
+      .insert("units".to_string(), "°C".to_string());
+    let src = file_compressor.write_footer_with_metadata(src, &metadata)?;
+
+    // the footer doesn't interfere with decompression
+    assert_eq!(simple_decompress::<i32>(&src)?, nums);
+    let (file_decompressor, rest) = FileDecompressor::new(src.as_slice())?;
+    assert_eq!(
+      file_decompressor.metadata(rest)?,
+      Some(metadata.clone())
+    );
+    assert_eq!(
+      file_decompressor.chunk_spans(rest)?,
+      metadata.chunk_spans
+    );
+    let mut rest = rest;
+    loop {
+      match file_decompressor.chunk_decompressor::<i32, _>(rest)? {
+        MaybeChunkDecompressor::Some(mut cd) => {
+          cd.decompress_remaining_extend(&mut Vec::new())?;
+          rest = cd.into_src()?;
+        }
+        MaybeChunkDecompressor::EndOfData(rest) => {
+          assert!(rest.is_empty());
+          break;
+        }
+      }
+    }
+
+    // truncated footers
+    let (_, rest) = FileDecompressor::new(&src[..src.len() - 1])?;
+    assert!(file_decompressor.metadata(rest).is_err());
+    assert!(simple_decompress::<i32>(&src[..src.len() - 1]).is_err());
+
+    // corrupt footer sizes
+    let decompress_stream = |src: &[u8]| -> PcoResult<()> {
+      let (file_decompressor, mut rest) =
+        FileDecompressor::new(BetterBufReader::from_read_simple(src))?;
+      while let MaybeChunkDecompressor::Some(mut cd) =
+        file_decompressor.chunk_decompressor::<i32, _>(rest)?
+      {
+        cd.decompress_remaining_extend(&mut Vec::new())?;
+        rest = cd.into_src()?;
+      }
+      Ok(())
+    };
+    let without_footer_size = &without_footer[..without_footer.len() - 1];
+    let mut overflowing = without_footer_size.to_vec();
+    footer::write_varint_aligned(usize::MAX, &mut overflowing)?;
+    for res in [
+      simple_decompress::<i32>(&overflowing).map(|_| ()),
+      decompress_stream(&overflowing),
+    ] {
+      assert!(matches!(
+        res.unwrap_err().kind,
+        ErrorKind::Corruption
+      ));
+    }
+    // a huge footer size must not allocate a huge buffer
+    let mut huge = without_footer_size.to_vec();
+    footer::write_varint_aligned(1 << 50, &mut huge)?;
+    huge.extend([0; 100]);
+    for res in [
+      simple_decompress::<i32>(&huge).map(|_| ()),
+      decompress_stream(&huge),
+    ] {
+      assert!(matches!(
+        res.unwrap_err().kind,
+        ErrorKind::InsufficientData
+      ));
+    }
+
+    // files without a footer
+    assert_eq!(
+      simple_decompress::<i32>(&without_footer)?,
+      nums
+    );
+    let (_, rest) = FileDecompressor::new(without_footer.as_slice())?;
+    assert_eq!(file_decompressor.metadata(rest)?, None);
+
+    // invalid chunk spans
+    let mut invalid = metadata.clone();
+    invalid.chunk_spans[1].start += 1;
+    assert!(file_compressor
+      .write_footer_with_metadata(Vec::new(), &invalid)
+      .is_err());
+    Ok(())
+  }
+
+  #[test]
+  fn test_decompress_filtered() -> PcoResult<()> {
+    // each chunk covers a different range of values
+    let nums = (0..4000_i64)
+      .map(|i| (i / 1000) * 10_000 + (i * 7919) % 1000)
+      .collect::<Vec<_>>();
+    let chunking_spec = PagingSpec::Exact(vec![1000; 4]);
+    let filter_matches = |src: &[u8], lower: i64, upper: i64| {
+      let (file_decompressor, rest) = FileDecompressor::new(src)?;
+      file_decompressor.decompress_filtered(rest, lower..=upper)
+    };
+    let expected_matches = |lower: i64, upper: i64| {
+      nums
+        .iter()
+        .enumerate()
+        .filter(|(_, &x)| lower <= x && x <= upper)
+        .map(|(i, &x)| (i, x))
+        .collect::<Vec<_>>()
+    };
+
+    for config in [
+      ChunkConfig::default().with_value_bounds_spec(ValueBoundsSpec::Enabled),
+      // bounds are derived from the bins
+      ChunkConfig::default().with_delta_encoding_order(Some(0)),
+      // no bounds, so nothing gets skipped
+      ChunkConfig::default().with_delta_encoding_order(Some(1)),
+    ] {
+      let src = simple_compress(
+        &nums,
+        &config.with_chunking_spec(chunking_spec.clone()),
+      )?;
+      for (lower, upper) in [
+        (10_100, 10_200),
+        (999, 20_000),
+        (-5, -1),
+        (i64::MIN, i64::MAX),
+      ] {
+        assert_eq!(
+          filter_matches(&src, lower, upper)?,
+          expected_matches(lower, upper),
+        );
+      }
+      assert!(filter_matches(&src, 1, 0).is_err());
+    }
+
+    // chunks ruled out by their bounds are never decompressed
+    let config = ChunkConfig::default()
+      .with_value_bounds_spec(ValueBoundsSpec::Enabled)
+      .with_chunking_spec(chunking_spec);
+    let mut src = simple_compress(&nums, &config)?;
+    let (file_decompressor, rest) = FileDecompressor::new(src.as_slice())?;
+    let header_size = src.len() - rest.len();
+    let spans = file_decompressor.chunk_spans(rest)?;
+    for span in &spans[..3] {
+      src[header_size + span.end - 4..header_size + span.end].fill(0xff);
+    }
+    assert_eq!(
+      filter_matches(&src, 30_000, 30_500)?,
+      expected_matches(30_000, 30_500),
+    );
+
+    // floats compare by their latent order
+    let floats = vec![f32::NAN, -0.0, 0.0, f32::NEG_INFINITY, 1.5, -f32::NAN];
+    let src = simple_compress(&floats, &ChunkConfig::default())?;
+    let (file_decompressor, rest) = FileDecompressor::new(src.as_slice())?;
+    let matches = file_decompressor.decompress_filtered(rest, -1.0..=f32::INFINITY)?;
+    assert_eq!(
+      matches
+        .iter()
+        .map(|&(i, x)| (i, x.to_bits()))
+        .collect::<Vec<_>>(),
+      vec![(1, (-0.0_f32).to_bits()), (2, 0), (4, 1.5_f32.to_bits())],
+    );
+    Ok(())
+  }
+
+  #[cfg(feature = "parallel")]
+  #[test]
+  fn test_simple_decompress_parallel() -> PcoResult<()> {
+    let nums = (0..5000).map(|i| (i * i) % 7919).collect::<Vec<i64>>();
+    let config = ChunkConfig {
+      chunking_spec: PagingSpec::Exact(vec![1000, 1, 999, 1500, 1500]),
+      ..Default::default()
+    };
+    let src = simple_compress(&nums, &config)?;
+    assert_eq!(
+      simple_decompress_parallel::<i64>(&src)?,
+      nums
+    );
+
+    let src = simple_compress(&Vec::<i64>::new(), &ChunkConfig::default())?;
+    assert_eq!(
+      simple_decompress_parallel::<i64>(&src)?,
+      vec![]
+    );
+    Ok(())
+  }
 }
@@ -41,6 +41,10 @@ fn assert_compatible<T: NumberLike>(version: &str, name: &str, expected: &[T]) -
   let decompressed = standalone::simple_decompress::<T>(&compressed)?;
 
   assert_nums_eq(&decompressed, expected);
+
+  let range = expected.len() / 3..expected.len() / 2;
+  let decompressed = standalone::decompress_range::<T>(&compressed, range.clone())?;
+  assert_nums_eq(&decompressed, &expected[range]);
   Ok(())
 }
 
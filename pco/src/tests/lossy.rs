@@ -0,0 +1,174 @@
+use half::f16;
+use rand::Rng;
+use rand_xoshiro::rand_core::SeedableRng;
+use rand_xoshiro::Xoroshiro128PlusPlus;
+
+use crate::chunk_config::{ChunkConfig, FloatMultSpec, LossySpec};
+use crate::data_types::FloatLike;
+use crate::errors::{ErrorKind, PcoResult};
+use crate::standalone::{simple_compress, simple_decompress};
+
+fn random_floats<F: FloatLike>(n: usize, scale: f64, seed: u64) -> Vec<F> {
+  let mut rng = Xoroshiro128PlusPlus::seed_from_u64(seed);
+  let mut nums = Vec::with_capacity(n);
+  for _ in 0..n {
+    // mix of magnitudes, signs, and a few special values
+    let x = match rng.gen_range(0..100) {
+      0 => f64::NAN,
+      1 => f64::INFINITY,
+      2 => 0.0,
+      _ => rng.gen_range(-1.0..1.0) * scale * 2.0_f64.powi(rng.gen_range(-4..4)),
+    };
+    nums.push(F::from_f64(x));
+  }
+  nums
+}
+
+fn assert_within_tolerance<F: FloatLike>(
+  nums: &[F],
+  spec: LossySpec,
+  name: &str,
+) -> PcoResult<usize> {
+  assert_within_tolerance_with_config(nums, spec, ChunkConfig::default(), name)
+}
+
+fn assert_within_tolerance_with_config<F: FloatLike>(
+  nums: &[F],
+  spec: LossySpec,
+  config: ChunkConfig,
+  name: &str,
+) -> PcoResult<usize> {
+  let config = config.with_lossy_spec(spec);
+  let compressed = simple_compress(nums, &config)?;
+  let decompressed = simple_decompress::<F>(&compressed)?;
+  assert_eq!(decompressed.len(), nums.len(), "{}", name);
+  for (i, (&x, &y)) in nums.iter().zip(&decompressed).enumerate() {
+    let x = x.to_f64();
+    let y = y.to_f64();
+    if x.is_nan() {
+      assert!(y.is_nan(), "at {}; {}", i, name);
+      continue;
+    }
+    let bound = match spec {
+      LossySpec::None => 0.0,
+      LossySpec::AbsError(tolerance) => tolerance,
+      LossySpec::RelError(tolerance) => tolerance * x.abs(),
+    };
+    let ok = if x.is_infinite() {
+      x == y
+    } else {
+      (y - x).abs() <= bound
+    };
+    assert!(
+      ok,
+      "{} decompressed to {} outside {:?} at {}; {}",
+      x, y, spec, i, name
+    );
+  }
+  Ok(compressed.len())
+}
+
+fn check_all_tolerances<F: FloatLike>(scale: f64, name: &str) -> PcoResult<()> {
+  let nums = random_floats::<F>(2000, scale, 0);
+  let lossless_size = assert_within_tolerance(&nums, LossySpec::None, name)?;
+  for tolerance in [0.0, 1E-6, 1E-3, 0.1, 10.0] {
+    let abs_size = assert_within_tolerance(
+      &nums,
+      LossySpec::AbsError(tolerance * scale),
+      name,
+    )?;
+    let rel_size = assert_within_tolerance(&nums, LossySpec::RelError(tolerance), name)?;
+    if tolerance >= 0.1 {
+      assert!(
+        abs_size < lossless_size,
+        "{} {}",
+        name,
+        tolerance
+      );
+      assert!(
+        rel_size < lossless_size,
+        "{} {}",
+        name,
+        tolerance
+      );
+    }
+  }
+  Ok(())
+}
+
+#[test]
+fn test_lossy_f16() -> PcoResult<()> {
+  check_all_tolerances::<f16>(1.0, "f16")
+}
+
+#[test]
+fn test_lossy_f32() -> PcoResult<()> {
+  check_all_tolerances::<f32>(1.0, "f32")?;
+  check_all_tolerances::<f32>(1E20, "big f32")
+}
+
+#[test]
+fn test_lossy_f64() -> PcoResult<()> {
+  check_all_tolerances::<f64>(1.0, "f64")?;
+  check_all_tolerances::<f64>(1E-200, "tiny f64")
+}
+
+#[test]
+fn test_lossy_time_series() -> PcoResult<()> {
+  let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut nums = Vec::new();
+  let mut x = 0.0_f64;
+  for _ in 0..2000 {
+    x += rng.gen_range(-1.0..1.0);
+    nums.push(x);
+  }
+  for spec in [
+    LossySpec::AbsError(1E-6),
+    LossySpec::AbsError(0.01),
+    LossySpec::RelError(1E-6),
+  ] {
+    assert_within_tolerance(&nums, spec, "time series")?;
+  }
+  Ok(())
+}
+
+#[test]
+fn test_lossy_with_lossy_float_mult() -> PcoResult<()> {
+  // multiples of 0.1 with small noise, so lossy float mult finds a base
+  let mut rng = Xoroshiro128PlusPlus::seed_from_u64(0);
+  let nums = (0..2000)
+    .map(|_| rng.gen_range(0..1000) as f64 * 0.1 + rng.gen_range(-0.01..0.01))
+    .collect::<Vec<_>>();
+  for float_mult_spec in [
+    FloatMultSpec::LossyEnabled,
+    FloatMultSpec::LossyProvided(0.1),
+  ] {
+    let config = ChunkConfig::default().with_float_mult_spec(float_mult_spec);
+    for spec in [LossySpec::AbsError(1E-3), LossySpec::RelError(1E-4)] {
+      assert_within_tolerance_with_config(
+        &nums,
+        spec,
+        config.clone(),
+        "lossy float mult",
+      )?;
+    }
+  }
+  Ok(())
+}
+
+#[test]
+fn test_lossy_invalid_tolerance() {
+  for spec in [
+    LossySpec::AbsError(-1.0),
+    LossySpec::AbsError(f64::NAN),
+    LossySpec::RelError(f64::INFINITY),
+  ] {
+    let config = ChunkConfig::default().with_lossy_spec(spec);
+    let res = simple_compress(&[1.0_f32, 2.0], &config);
+    assert!(
+      matches!(res, Err(e) if matches!(e.kind, ErrorKind::InvalidArgument)),
+      "{:?}",
+      spec
+    );
+  }
+}
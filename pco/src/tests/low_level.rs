@@ -1,13 +1,19 @@
 use std::cmp::min;
+use std::fmt::Debug;
 use std::fs::File;
 use std::io::Write;
 
 use better_io::{BetterBufRead, BetterBufReader};
+use half::f16;
 
 use crate::chunk_config::ChunkConfig;
+use crate::data_types::NumberLike;
 use crate::errors::PcoResult;
 use crate::wrapped::{FileCompressor, FileDecompressor, PageDecompressor};
-use crate::{PagingSpec, FULL_BATCH_N};
+use crate::{
+  Agg, AggValue, DeltaLagSpec, ExceptionSpec, LossySpec, LpcSpec, PagingSpec, ValueBoundsSpec,
+  FULL_BATCH_N,
+};
 
 struct Chunk {
   nums: Vec<u32>,
@@ -114,6 +120,19 @@ fn test_low_level_wrapped() -> PcoResult<()> {
         ..Default::default()
       },
     },
+    Chunk {
+      // a few outliers, which get patched in as exceptions across pages
+      nums: (0..3000)
+        .map(|i| match i % 700 {
+          0 | 699 => u32::MAX - i,
+          _ => 3 * i + (i * 7919) % 10,
+        })
+        .collect::<Vec<_>>(),
+      config: ChunkConfig {
+        paging_spec: PagingSpec::Exact(vec![700, 1, 2299]),
+        ..Default::default()
+      },
+    },
     Chunk {
       nums: vec![1, 2, 3],
       config: ChunkConfig::default(),
@@ -127,3 +146,445 @@ fn test_low_level_wrapped() -> PcoResult<()> {
     },
   ])
 }
+
+fn assert_skip_to_batch_recovers<T: NumberLike>(nums: &[T], config: &ChunkConfig) -> PcoResult<()> {
+  let fc = FileCompressor::default();
+  let cc = fc.chunk_compressor(nums, config)?;
+  let n_per_page = cc.n_per_page();
+  let mut compressed = fc.write_header(Vec::new())?;
+  compressed = cc.write_chunk_meta(compressed)?;
+  for page_idx in 0..n_per_page.len() {
+    compressed = cc.write_page(page_idx, compressed)?;
+  }
+
+  let assert_recovered = |recovered: &[T], expected: &[T], context: &str| {
+    let recovered = recovered
+      .iter()
+      .map(|x| x.to_latent_ordered())
+      .collect::<Vec<_>>();
+    let expected = expected
+      .iter()
+      .map(|x| x.to_latent_ordered())
+      .collect::<Vec<_>>();
+    assert_eq!(recovered, expected, "{}", context);
+  };
+
+  let (fd, src) = FileDecompressor::new(compressed.as_slice())?;
+  let (cd, mut src) = fd.chunk_decompressor::<T, _>(src)?;
+  assert_eq!(
+    cd.meta().seek_index_interval,
+    cc.meta().seek_index_interval
+  );
+  let mut page_start = 0;
+  for page_n in n_per_page {
+    let page_nums = &nums[page_start..page_start + page_n];
+    let n_batches = page_n.div_ceil(FULL_BATCH_N);
+    for batch_idx in 0..=n_batches {
+      let mut pd = cd.page_decompressor(src, page_n)?;
+      pd.skip_to_batch(batch_idx)?;
+      let start = min(batch_idx * FULL_BATCH_N, page_n);
+      let mut recovered = vec![T::default(); page_n - start];
+      let progress = pd.decompress(&mut recovered)?;
+      assert!(progress.finished);
+      assert_recovered(
+        &recovered,
+        &page_nums[start..],
+        &format!("batch {}", batch_idx),
+      );
+    }
+
+    // skipping repeatedly, interleaved with decompression
+    let mut pd = cd.page_decompressor(src, page_n)?;
+    let mut batch = vec![T::default(); FULL_BATCH_N];
+    pd.decompress(&mut batch)?;
+    let mid_batch_idx = n_batches / 2;
+    if mid_batch_idx > 0 {
+      pd.skip_to_batch(mid_batch_idx)?;
+      let progress = pd.decompress(&mut batch)?;
+      let start = mid_batch_idx * FULL_BATCH_N;
+      assert_recovered(
+        &batch[..progress.n_processed],
+        &page_nums[start..start + progress.n_processed],
+        "mid batch",
+      );
+      assert!(pd.skip_to_batch(mid_batch_idx).is_err());
+    }
+    pd.skip_to_batch(n_batches)?;
+    assert!(pd.decompress(&mut batch)?.finished);
+    assert!(pd.skip_to_batch(n_batches + 1).is_err());
+    src = pd.into_src();
+    page_start += page_n;
+  }
+  assert!(src.is_empty());
+  Ok(())
+}
+
+#[test]
+fn test_skip_to_batch() -> PcoResult<()> {
+  let noise = |i: usize| ((i * 7919) % 1000) as u32;
+  let pagings = [
+    PagingSpec::Exact(vec![3000]),
+    PagingSpec::Exact(vec![1, 256, 2743]),
+  ];
+  for seek_index_interval in [None, Some(1), Some(3), Some(100)] {
+    for paging_spec in &pagings {
+      let config = ChunkConfig {
+        seek_index_interval,
+        paging_spec: paging_spec.clone(),
+        ..Default::default()
+      };
+
+      // high order deltas
+      let nums = (0..3000)
+        .map(|i| (i * i) as u32 + noise(i))
+        .collect::<Vec<_>>();
+      assert_skip_to_batch_recovers(
+        &nums,
+        &config.clone().with_delta_encoding_order(Some(2)),
+      )?;
+
+      // lagged deltas
+      let nums = (0..3000)
+        .map(|i| (i % 7) as u32 * 100_000 + noise(i))
+        .collect::<Vec<_>>();
+      assert_skip_to_batch_recovers(
+        &nums,
+        &config
+          .clone()
+          .with_delta_encoding_order(Some(1))
+          .with_delta_lag_spec(DeltaLagSpec::Provided(7)),
+      )?;
+
+      // exceptions
+      let nums = (0..3000)
+        .map(|i| match i % 1000 {
+          999 => -9999,
+          _ => 20 * i as i64 + noise(i) as i64,
+        })
+        .collect::<Vec<_>>();
+      assert_skip_to_batch_recovers(&nums, &config)?;
+
+      // float mult, which has 2 latent variables
+      let nums = (0..3000).map(|i| noise(i) as f64 * 0.1).collect::<Vec<_>>();
+      assert_skip_to_batch_recovers(&nums, &config)?;
+
+      // linear prediction
+      let nums = (0..3000)
+        .map(|i| ((i as f64 * 0.01).sin() * 1e6) as i32 + noise(i) as i32)
+        .collect::<Vec<_>>();
+      assert_skip_to_batch_recovers(
+        &nums,
+        &config.clone().with_lpc_spec(LpcSpec::Enabled),
+      )?;
+
+      // run length, which doesn't support a seek index but can still skip
+      let nums = (0..3000).map(|i| (i / 500) as u64).collect::<Vec<_>>();
+      assert_skip_to_batch_recovers(&nums, &config)?;
+    }
+  }
+  Ok(())
+}
+
+#[test]
+fn test_seek_index_size() -> PcoResult<()> {
+  let nums = (0..10_000).map(|i| (i * 7919) % 1000).collect::<Vec<u32>>();
+  let compress = |seek_index_interval| {
+    let config = ChunkConfig::default()
+      .with_delta_encoding_order(Some(0))
+      .with_seek_index_interval(seek_index_interval);
+    let cc = FileCompressor::default().chunk_compressor(&nums, &config)?;
+    let page = cc.write_page(0, Vec::new())?;
+    PcoResult::Ok((
+      cc.meta().per_latent_var[0].ans_size_log,
+      page.len(),
+    ))
+  };
+
+  // Each of the 39 batches after the first gets a position and 4 ANS states.
+  let (ans_size_log, size) = compress(None)?;
+  let (_, size_w_index) = compress(Some(1))?;
+  let index_bits = 39 * (36 + 4 * ans_size_log as usize);
+  assert!((size_w_index - size) * 8 >= index_bits);
+  assert!((size_w_index - size) * 8 < index_bits + 8);
+  assert!(compress(Some(0)).is_err());
+  assert!(compress(Some(1 << 17)).is_err());
+  Ok(())
+}
+
+fn read_value_bounds<T: NumberLike>(nums: &[T], config: &ChunkConfig) -> PcoResult<Option<(T, T)>> {
+  let fc = FileCompressor::default();
+  let cc = fc.chunk_compressor(nums, config)?;
+  let mut compressed = fc.write_header(Vec::new())?;
+  compressed = cc.write_chunk_meta(compressed)?;
+
+  let (fd, src) = FileDecompressor::new(compressed.as_slice())?;
+  let (cd, _) = fd.chunk_decompressor::<T, _>(src)?;
+  assert_eq!(cd.meta(), cc.meta());
+  Ok(cd.meta().value_bounds::<T>())
+}
+
+fn assert_value_bounds<T: NumberLike>(nums: &[T], config: &ChunkConfig) -> PcoResult<()> {
+  let latents = nums
+    .iter()
+    .map(|x| x.to_latent_ordered())
+    .collect::<Vec<_>>();
+  let lower = *latents.iter().min().unwrap();
+  let upper = *latents.iter().max().unwrap();
+
+  // stored bounds are exact
+  let config = config
+    .clone()
+    .with_value_bounds_spec(ValueBoundsSpec::Enabled);
+  let (stored_lower, stored_upper) = read_value_bounds(nums, &config)?.unwrap();
+  assert_eq!(stored_lower.to_latent_ordered(), lower);
+  assert_eq!(stored_upper.to_latent_ordered(), upper);
+
+  // derived bounds, if any, contain every number
+  let config = config.with_value_bounds_spec(ValueBoundsSpec::Disabled);
+  if let Some((derived_lower, derived_upper)) = read_value_bounds(nums, &config)? {
+    assert!(derived_lower.to_latent_ordered() <= lower);
+    assert!(derived_upper.to_latent_ordered() >= upper);
+  }
+  Ok(())
+}
+
+#[test]
+fn test_value_bounds() -> PcoResult<()> {
+  let noise = |i: usize| ((i * 7919) % 1000) as u32;
+  let config = ChunkConfig::default();
+
+  // classic, which can derive bounds
+  let nums = (0..3000).map(|i| noise(i) + 77).collect::<Vec<_>>();
+  let no_delta = config.clone().with_delta_encoding_order(Some(0));
+  assert_value_bounds(&nums, &no_delta)?;
+  assert!(read_value_bounds(&nums, &no_delta)?.is_some());
+
+  // deltas, which can't derive bounds
+  let nums = (0..3000)
+    .map(|i| (i * i) as u32 + noise(i))
+    .collect::<Vec<_>>();
+  let delta = config.clone().with_delta_encoding_order(Some(2));
+  assert_value_bounds(&nums, &delta)?;
+  assert!(read_value_bounds(&nums, &delta)?.is_none());
+
+  // exceptions
+  let nums = (0..3000)
+    .map(|i| match i % 1000 {
+      999 => -9999,
+      _ => 20 * i as i64 + noise(i) as i64,
+    })
+    .collect::<Vec<_>>();
+  assert_value_bounds(&nums, &config)?;
+
+  // float mult, with extreme floats
+  let mut nums = (0..3000).map(|i| noise(i) as f32 * 0.1).collect::<Vec<_>>();
+  nums[5] = f32::NEG_INFINITY;
+  nums[6] = f32::NAN;
+  assert_value_bounds(&nums, &config)?;
+
+  // dictionary-like data
+  let nums = (0..3000)
+    .map(|i| [-1_000_000_i32, 3, 777_777][noise(i) as usize % 3])
+    .collect::<Vec<_>>();
+  assert_value_bounds(&nums, &config)?;
+
+  // lossy compression doesn't store bounds
+  let nums = (0..3000).map(|i| noise(i) as f64 * 0.1).collect::<Vec<_>>();
+  let lossy = config
+    .with_lossy_spec(LossySpec::AbsError(0.5))
+    .with_value_bounds_spec(ValueBoundsSpec::Enabled);
+  assert_eq!(read_value_bounds(&nums, &lossy)?, None);
+  Ok(())
+}
+
+#[test]
+fn test_approx_quantiles() -> PcoResult<()> {
+  // a skewed distribution of numbers
+  let nums = (0..10_000_u64)
+    .map(|i| {
+      let x = ((i * 7919) % 10_000) as f64 / 10_000.0;
+      (x * x * x * 1e6) as u64
+    })
+    .collect::<Vec<_>>();
+  let config = ChunkConfig::default()
+    .with_delta_encoding_order(Some(0))
+    .with_exception_spec(ExceptionSpec::Disabled);
+  let cc = FileCompressor::default().chunk_compressor(&nums, &config)?;
+  let meta = cc.meta();
+  let histogram = meta.approx_histogram::<u64>().unwrap();
+  let latent_var = &meta.per_latent_var[0];
+  let error_bound = 3.0 * latent_var.bins.len() as f64 / (1 << latent_var.ans_size_log) as f64;
+
+  let true_fraction = |lower: u64, upper: u64| {
+    nums.iter().filter(|&&x| lower <= x && x <= upper).count() as f64 / nums.len() as f64
+  };
+  let total_error = histogram
+    .iter()
+    .map(|bin| (bin.fraction - true_fraction(bin.lower, bin.upper)).abs())
+    .sum::<f64>();
+  assert!(total_error <= error_bound);
+
+  for q in [0.0, 0.1, 0.5, 0.9, 0.99, 1.0] {
+    let quantile = meta.approx_quantile::<u64>(q)?.unwrap();
+    let bin = histogram
+      .iter()
+      .find(|bin| bin.lower <= quantile && quantile <= bin.upper)
+      .unwrap();
+    let rank = true_fraction(0, quantile);
+    assert!(
+      (rank - q).abs() <= error_bound + bin.fraction,
+      "q={}",
+      q
+    );
+  }
+  Ok(())
+}
+
+fn naive_aggregate<T: NumberLike>(nums: &[T], agg: Agg) -> AggValue<T> {
+  let by_latent = |x: &&T| x.to_latent_ordered();
+  match agg {
+    Agg::Count => AggValue::Count(nums.len()),
+    Agg::Sum => AggValue::Sum(nums.iter().map(|x| x.to_f64_lossy()).sum()),
+    Agg::Min => AggValue::Min(nums.iter().min_by_key(by_latent).copied()),
+    Agg::Max => AggValue::Max(nums.iter().max_by_key(by_latent).copied()),
+  }
+}
+
+fn assert_aggregates<T: NumberLike>(nums: &[T], config: &ChunkConfig) -> PcoResult<()> {
+  let fc = FileCompressor::default();
+  let cc = fc.chunk_compressor(nums, config)?;
+  let n_per_page = cc.n_per_page();
+  let mut compressed = fc.write_header(Vec::new())?;
+  compressed = cc.write_chunk_meta(compressed)?;
+  for page_idx in 0..n_per_page.len() {
+    compressed = cc.write_page(page_idx, compressed)?;
+  }
+
+  let (fd, src) = FileDecompressor::new(compressed.as_slice())?;
+  let (cd, src) = fd.chunk_decompressor::<T, _>(src)?;
+  for agg in [Agg::Count, Agg::Sum, Agg::Min, Agg::Max] {
+    let mut src = src;
+    let mut page_start = 0;
+    for &page_n in &n_per_page {
+      let page_nums = &nums[page_start..page_start + page_n];
+
+      // after decompressing the first batch, only the rest gets aggregated
+      let mut pd = cd.page_decompressor(src, page_n)?;
+      let mut batch = vec![T::default(); FULL_BATCH_N];
+      let progress = pd.decompress(&mut batch)?;
+      let res = pd.aggregate(agg)?;
+      let expected = naive_aggregate(&page_nums[progress.n_processed..], agg);
+      // sums may be accumulated in a different order
+      match (res, expected) {
+        (AggValue::Sum(sum), AggValue::Sum(expected_sum)) => {
+          assert!((sum - expected_sum).abs() <= 1e-9 * expected_sum.abs())
+        }
+        _ => assert_eq!(
+          format!("{:?}", res),
+          format!("{:?}", expected)
+        ),
+      }
+      assert!(pd.decompress(&mut batch)?.finished);
+      src = pd.into_src();
+      page_start += page_n;
+    }
+    assert!(src.is_empty());
+  }
+  Ok(())
+}
+
+#[test]
+fn test_aggregate() -> PcoResult<()> {
+  let noise = |i: usize| ((i * 7919) % 1000) as u32;
+  let config = ChunkConfig::default().with_paging_spec(PagingSpec::EqualPagesUpTo(1000));
+
+  // constant, which needs no decoding
+  assert_aggregates(&vec![-7_i64; 3000], &config)?;
+  assert_aggregates(&vec![1.5_f32; 3000], &config)?;
+
+  // classic
+  let nums = (0..3000).map(|i| noise(i) + 77).collect::<Vec<_>>();
+  assert_aggregates(&nums, &config)?;
+
+  // deltas
+  let nums = (0..3000)
+    .map(|i| (i * i) as u32 + noise(i))
+    .collect::<Vec<_>>();
+  assert_aggregates(
+    &nums,
+    &config.clone().with_delta_encoding_order(Some(2)),
+  )?;
+
+  // exceptions
+  let nums = (0..3000)
+    .map(|i| match i % 1000 {
+      999 => -9999,
+      _ => 20 * i as i64 + noise(i) as i64,
+    })
+    .collect::<Vec<_>>();
+  assert_aggregates(&nums, &config)?;
+
+  // floats, including -0.0 and +0.0
+  let mut nums = (0..3000).map(|i| noise(i) as f32 * 0.1).collect::<Vec<_>>();
+  nums[5] = -0.0;
+  nums[6] = f32::NEG_INFINITY;
+  assert_aggregates(&nums, &config)?;
+
+  // dictionary-like data
+  let nums = (0..3000)
+    .map(|i| [-1_000_000_i32, 3, 777_777][noise(i) as usize % 3])
+    .collect::<Vec<_>>();
+  assert_aggregates(&nums, &config)?;
+  Ok(())
+}
+
+fn assert_decompress_as<T: NumberLike, D: From<T> + Copy + Debug + Default + PartialEq>(
+  nums: &[T],
+) -> PcoResult<()> {
+  let fc = FileCompressor::default();
+  let cc = fc.chunk_compressor(nums, &ChunkConfig::default())?;
+  let mut compressed = fc.write_header(Vec::new())?;
+  compressed = cc.write_chunk_meta(compressed)?;
+  compressed = cc.write_page(0, compressed)?;
+
+  let (fd, src) = FileDecompressor::new(compressed.as_slice())?;
+  let (cd, src) = fd.chunk_decompressor::<T, _>(src)?;
+  let mut pd = cd.page_decompressor(src, nums.len())?;
+  let mut recovered = vec![D::default(); nums.len()];
+  // a multiple of the batch size, followed by a destination with spare room
+  let progress = pd.decompress_as(&mut recovered[..2 * FULL_BATCH_N])?;
+  assert_eq!(progress.n_processed, 2 * FULL_BATCH_N);
+  assert!(!progress.finished);
+  assert!(pd.decompress_as(&mut [D::default(); 3]).is_err());
+  let mut rest = vec![D::default(); nums.len()];
+  let progress = pd.decompress_as(&mut rest)?;
+  assert!(progress.finished);
+  recovered[2 * FULL_BATCH_N..].copy_from_slice(&rest[..progress.n_processed]);
+
+  let expected = nums.iter().map(|&x| D::from(x)).collect::<Vec<_>>();
+  assert_eq!(recovered, expected);
+  assert!(pd.into_src().is_empty());
+  Ok(())
+}
+
+#[test]
+fn test_decompress_as() -> PcoResult<()> {
+  let ints = (0..1000)
+    .map(|i| match i % 100 {
+      99 => i32::MIN,
+      _ => (i * 7919) % 1000 - 500,
+    })
+    .collect::<Vec<_>>();
+  assert_decompress_as::<i32, i64>(&ints)?;
+  assert_decompress_as::<i32, f64>(&ints)?;
+
+  let floats = ints.iter().map(|&x| x as f32 * 0.1).collect::<Vec<_>>();
+  assert_decompress_as::<f32, f64>(&floats)?;
+
+  let halves = ints
+    .iter()
+    .map(|&x| f16::from_f32(x as f32 * 0.1))
+    .collect::<Vec<_>>();
+  assert_decompress_as::<f16, f32>(&halves)?;
+  Ok(())
+}
@@ -1,4 +1,5 @@
 mod compatibility;
+mod lossy;
 mod low_level;
 mod recovery;
 mod stability;
@@ -1,3 +1,4 @@
+use half::f16;
 use rand::Rng;
 use rand_xoshiro::rand_core::SeedableRng;
 
@@ -6,7 +7,10 @@ use crate::constants::Bitlen;
 use crate::data_types::NumberLike;
 use crate::errors::PcoResult;
 use crate::standalone::{simple_compress, simple_decompress, FileCompressor};
-use crate::{ChunkMeta, Mode};
+use crate::{
+  ChunkMeta, DecimalSpec, DeltaLagSpec, DictionarySpec, ExceptionSpec, FloatMultSpec, LpcSpec,
+  Mode, PagingSpec, RunLengthSpec,
+};
 
 fn compress_w_meta<T: NumberLike>(
   nums: &[T],
@@ -102,6 +106,11 @@ fn test_sparse() -> PcoResult<()> {
   assert_recovers(&v, 1, "sparse")
 }
 
+#[test]
+fn test_u16_codec() -> PcoResult<()> {
+  assert_recovers(&[0_u16, u16::MAX, 3, 4, 5], 1, "u16s")
+}
+
 #[test]
 fn test_u32_codec() -> PcoResult<()> {
   assert_recovers(&[0_u32, u32::MAX, 3, 4, 5], 1, "u32s")
@@ -112,6 +121,24 @@ fn test_u64_codec() -> PcoResult<()> {
   assert_recovers(&[0_u64, u64::MAX, 3, 4, 5], 1, "u64s")
 }
 
+#[test]
+fn test_u128_codec() -> PcoResult<()> {
+  assert_recovers(
+    &[0_u128, u128::MAX, 3, 4, 5, 1 << 100],
+    1,
+    "u128s",
+  )
+}
+
+#[test]
+fn test_i16_codec() -> PcoResult<()> {
+  assert_recovers(
+    &[0_i16, -1, i16::MAX, i16::MIN, 7],
+    1,
+    "i16s",
+  )
+}
+
 #[test]
 fn test_i32_codec() -> PcoResult<()> {
   assert_recovers(
@@ -130,6 +157,33 @@ fn test_i64_codec() -> PcoResult<()> {
   )
 }
 
+#[test]
+fn test_i128_codec() -> PcoResult<()> {
+  assert_recovers(
+    &[0_i128, -1, i128::MAX, i128::MIN, 7, -(1 << 100)],
+    1,
+    "i128s",
+  )
+}
+
+#[test]
+fn test_f16_codec() -> PcoResult<()> {
+  assert_recovers(
+    &[
+      f16::MAX,
+      f16::MIN,
+      f16::NAN,
+      f16::NEG_INFINITY,
+      f16::INFINITY,
+      f16::NEG_ZERO,
+      f16::ZERO,
+      f16::from_f32(77.7),
+    ],
+    1,
+    "f16s",
+  )
+}
+
 #[test]
 fn test_f32_codec() -> PcoResult<()> {
   assert_recovers(
@@ -190,6 +244,8 @@ fn recover_with_alternating_nums(offset_bits: Bitlen, name: &str) -> PcoResult<(
     &ChunkConfig {
       delta_encoding_order: Some(0),
       compression_level: 0,
+      // wide offsets are what we're testing here
+      dictionary_spec: DictionarySpec::Disabled,
       ..Default::default()
     },
   )?;
@@ -217,6 +273,28 @@ fn test_64_bit_offsets() -> PcoResult<()> {
   recover_with_alternating_nums(64, "64 bit offsets")
 }
 
+#[test]
+fn test_128_bit_offsets() -> PcoResult<()> {
+  let nums = [0_u128, 1 << 127].repeat(50);
+  let (compressed, meta) = compress_w_meta(
+    &nums,
+    &ChunkConfig {
+      delta_encoding_order: Some(0),
+      compression_level: 0,
+      // wide offsets are what we're testing here
+      dictionary_spec: DictionarySpec::Disabled,
+      ..Default::default()
+    },
+  )?;
+  assert_eq!(meta.per_latent_var[0].bins.len(), 1);
+  assert_eq!(
+    meta.per_latent_var[0].bins[0].offset_bits,
+    128
+  );
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "128 bit offsets")
+}
+
 #[test]
 fn test_with_int_mult() -> PcoResult<()> {
   let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
@@ -237,6 +315,226 @@ fn test_with_int_mult() -> PcoResult<()> {
   Ok(())
 }
 
+#[test]
+fn test_with_int_mult_i128() -> PcoResult<()> {
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut nums = Vec::new();
+  for _ in 0..300 {
+    nums.push(rng.gen_range(-1000_i128..1000) * (3 << 80) - 1);
+  }
+  let (compressed, meta) = compress_w_meta(
+    &nums,
+    &ChunkConfig {
+      delta_encoding_order: Some(0),
+      ..Default::default()
+    },
+  )?;
+  assert_eq!(meta.mode, Mode::IntMult(3_u128 << 80));
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "i128 w gcd")?;
+  Ok(())
+}
+
+#[test]
+fn test_with_float_mult_f16() -> PcoResult<()> {
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut nums = Vec::new();
+  for _ in 0..300 {
+    nums.push(f16::from_f32(
+      rng.gen_range(-100..100) as f32 * 0.25,
+    ));
+  }
+  let (compressed, meta) = compress_w_meta(
+    &nums,
+    &ChunkConfig {
+      delta_encoding_order: Some(0),
+      ..Default::default()
+    },
+  )?;
+  assert!(matches!(meta.mode, Mode::FloatMult(_)));
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "f16 float mult")?;
+  Ok(())
+}
+
+#[test]
+fn test_dictionary() -> PcoResult<()> {
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let distinct = (0..2000)
+    .map(|_| rng.gen_range(0..u64::MAX))
+    .collect::<Vec<_>>();
+  let mut nums = Vec::new();
+  for _ in 0..20000 {
+    // skew the distribution a bit toward the first values
+    let idx = rng.gen_range(0..distinct.len()) % rng.gen_range(1..distinct.len());
+    nums.push(distinct[idx]);
+  }
+
+  let config = ChunkConfig {
+    delta_encoding_order: Some(0),
+    ..Default::default()
+  };
+  let (compressed, meta) = compress_w_meta(&nums, &config)?;
+  assert_eq!(meta.mode, Mode::Dictionary);
+  assert!(meta.dictionary.len() <= distinct.len());
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "dictionary")?;
+
+  let (classic_compressed, classic_meta) = compress_w_meta(
+    &nums,
+    &config.with_dictionary_spec(DictionarySpec::Disabled),
+  )?;
+  assert_eq!(classic_meta.mode, Mode::Classic);
+  assert!(compressed.len() < classic_compressed.len());
+
+  assert_recovers(&nums, 4, "dictionary")
+}
+
+#[test]
+fn test_dictionary_floats() -> PcoResult<()> {
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let distinct = (0..1000)
+    .map(|_| rng.gen_range(-1.0..1.0) * 10.0_f32.powi(rng.gen_range(-20..20)))
+    .collect::<Vec<_>>();
+  let nums = (0..10000)
+    .map(|_| distinct[rng.gen_range(0..distinct.len())])
+    .collect::<Vec<_>>();
+  let (_, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert_eq!(meta.mode, Mode::Dictionary);
+  assert_recovers(&nums, 8, "float dictionary")
+}
+
+#[test]
+fn test_run_length() -> PcoResult<()> {
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut nums = Vec::new();
+  while nums.len() < 100000 {
+    let value = rng.gen_range(0_i64..1000000);
+    let run_len = rng.gen_range(1..2000);
+    nums.extend(std::iter::repeat(value).take(run_len));
+  }
+
+  let config = ChunkConfig::default();
+  let (compressed, meta) = compress_w_meta(&nums, &config)?;
+  assert_eq!(meta.mode, Mode::RunLength);
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "run length")?;
+
+  let (other_compressed, other_meta) = compress_w_meta(
+    &nums,
+    &config.with_run_length_spec(RunLengthSpec::Disabled),
+  )?;
+  assert_ne!(other_meta.mode, Mode::RunLength);
+  assert!(compressed.len() < other_compressed.len());
+
+  assert_recovers(&nums, 4, "run length")
+}
+
+#[test]
+fn test_run_length_longer_than_latent_max() -> PcoResult<()> {
+  // run lengths don't fit in a u16, so runs must get split up
+  let mut nums = vec![7_u16; 100000];
+  nums.extend(vec![3_u16; 70000]);
+  nums.push(5);
+  let (compressed, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert_eq!(meta.mode, Mode::RunLength);
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "long runs")?;
+  assert_recovers(&nums, 4, "long runs")
+}
+
+#[test]
+fn test_delta_lag_periodic() -> PcoResult<()> {
+  // like metering data with a daily period of 96 samples
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let daily_profile = (0..96)
+    .map(|_| rng.gen_range(0_i64..100000))
+    .collect::<Vec<_>>();
+  let nums = (0..20000)
+    .map(|i| daily_profile[i % 96] + rng.gen_range(-10..10))
+    .collect::<Vec<_>>();
+
+  let config = ChunkConfig::default();
+  let (compressed, meta) = compress_w_meta(&nums, &config)?;
+  assert_eq!(meta.delta_encoding_order, 1);
+  assert_eq!(meta.delta_lag, 96);
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "delta lag")?;
+
+  let (unlagged_compressed, unlagged_meta) = compress_w_meta(
+    &nums,
+    &config.with_delta_lag_spec(DeltaLagSpec::Disabled),
+  )?;
+  assert_eq!(unlagged_meta.delta_lag, 1);
+  assert!(compressed.len() < unlagged_compressed.len());
+
+  assert_recovers(&nums, 4, "delta lag")
+}
+
+#[test]
+fn test_delta_lag_provided() -> PcoResult<()> {
+  // 3 interleaved channels, each a smooth time series
+  let nums = (0..3000)
+    .map(|i| ((i % 3) as f32 * 100.0) + ((i / 3) as f32 * 0.01).sin())
+    .collect::<Vec<_>>();
+  for (lag, delta_encoding_order) in [(3, None), (3, Some(2)), (700, Some(1))] {
+    let config = ChunkConfig {
+      delta_encoding_order,
+      delta_lag_spec: DeltaLagSpec::Provided(lag),
+      ..Default::default()
+    };
+    let (compressed, meta) = compress_w_meta(&nums, &config)?;
+    assert_eq!(meta.delta_lag, lag);
+    let decompressed = simple_decompress(&compressed)?;
+    assert_nums_eq(
+      &decompressed,
+      &nums,
+      &format!("provided lag={}", lag),
+    )?;
+  }
+
+  let invalid_config = ChunkConfig::default().with_delta_lag_spec(DeltaLagSpec::Provided(0));
+  assert!(simple_compress(&nums, &invalid_config).is_err());
+  Ok(())
+}
+
+#[test]
+fn test_lpc() -> PcoResult<()> {
+  // like audio: a noisy oscillation with a period of about 20 samples
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let nums = (0..19970)
+    .map(|i| {
+      let x = i as f64 * 0.3;
+      (1_000_000.0 * x.sin() + 300_000.0 * (2.7 * x).sin()) as i32 + rng.gen_range(-50..50)
+    })
+    .collect::<Vec<_>>();
+
+  // multiple pages, including a page shorter than the predictor history
+  let config =
+    ChunkConfig::default().with_paging_spec(PagingSpec::Exact(vec![8960, 256, 10752, 2]));
+  let (compressed, meta) = compress_w_meta(&nums, &config)?;
+  assert_eq!(meta.mode, Mode::Lpc);
+  assert_eq!(meta.delta_encoding_order, 0);
+  assert!(!meta.lpc_coefficients.is_empty());
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "lpc")?;
+
+  let (unpredicted_compressed, unpredicted_meta) = compress_w_meta(
+    &nums,
+    &config.clone().with_lpc_spec(LpcSpec::Disabled),
+  )?;
+  assert_eq!(unpredicted_meta.mode, Mode::Classic);
+  assert!(compressed.len() < unpredicted_compressed.len());
+
+  // an explicit delta encoding order opts out of linear prediction
+  let (_, delta_meta) = compress_w_meta(
+    &nums,
+    &config.with_delta_encoding_order(Some(2)),
+  )?;
+  assert_eq!(delta_meta.mode, Mode::Classic);
+  Ok(())
+}
+
 #[test]
 fn test_sparse_islands() -> PcoResult<()> {
   let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
@@ -279,6 +577,118 @@ fn test_decimals() -> PcoResult<()> {
   assert_recovers(&nums, 2, "decimals")
 }
 
+#[test]
+fn test_decimal() -> PcoResult<()> {
+  // prices with varying precision, which float mult can't reconstruct exactly
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut nums = (0..3000)
+    .map(|_| {
+      let cents = rng.gen_range(0..1_000_000) as f64;
+      match rng.gen_range(0..3) {
+        0 => (cents / 10.0).round() / 10.0,
+        1 => cents / 100.0,
+        _ => (cents * 10.0 + rng.gen_range(0..10) as f64) / 1000.0,
+      }
+    })
+    .collect::<Vec<_>>();
+  // a few exceptions
+  nums[7] = f64::NAN;
+  nums[77] = 1.0 / 3.0;
+  nums[777] = f64::NEG_INFINITY;
+
+  let (compressed, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert_eq!(meta.mode, Mode::Decimal(3));
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "decimal")?;
+
+  let (nondecimal_compressed, nondecimal_meta) = compress_w_meta(
+    &nums,
+    &ChunkConfig::default().with_decimal_spec(DecimalSpec::Disabled),
+  )?;
+  assert!(!matches!(
+    nondecimal_meta.mode,
+    Mode::Decimal(_)
+  ));
+  assert!(compressed.len() < nondecimal_compressed.len());
+
+  assert_recovers(&nums, 4, "decimal")
+}
+
+#[test]
+fn test_exceptions() -> PcoResult<()> {
+  // temperatures in tenths of a degree, with sentinels for missing readings
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut temperature = 150_i32;
+  let mut nums = Vec::new();
+  for i in 0..20000 {
+    temperature = (temperature + rng.gen_range(-3..4)).clamp(-300, 400);
+    nums.push(match i % 997 {
+      13 => -9999,
+      500 => i32::MAX,
+      _ => temperature + rng.gen_range(-20..20),
+    });
+  }
+  nums[0] = -9999;
+
+  let (compressed, meta) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert!(meta.has_exceptions);
+  let decompressed = simple_decompress(&compressed)?;
+  assert_nums_eq(&decompressed, &nums, "exceptions")?;
+
+  let (unexceptional_compressed, unexceptional_meta) = compress_w_meta(
+    &nums,
+    &ChunkConfig::default().with_exception_spec(ExceptionSpec::Disabled),
+  )?;
+  assert!(!unexceptional_meta.has_exceptions);
+  assert!(compressed.len() < unexceptional_compressed.len());
+
+  assert_recovers(&nums, 4, "exceptions")
+}
+
+#[test]
+fn test_lossy_float_mult() -> PcoResult<()> {
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut nums = Vec::new();
+  for _ in 0..300 {
+    let unadjusted_num = (rng.gen_range(-100..100) as f64) * 0.01;
+    let adj = rng.gen_range(-1..2);
+    nums.push(f64::from_latent_ordered(
+      unadjusted_num.to_latent_ordered().wrapping_add(adj as u64),
+    ));
+  }
+  let config = ChunkConfig::default().with_float_mult_spec(FloatMultSpec::LossyEnabled);
+  let (compressed, meta) = compress_w_meta(&nums, &config)?;
+  assert_eq!(
+    meta.mode,
+    Mode::lossy_float_mult(1.0 / 100.0)
+  );
+  assert_eq!(meta.per_latent_var.len(), 1);
+  let (lossless_compressed, _) = compress_w_meta(&nums, &ChunkConfig::default())?;
+  assert!(compressed.len() < lossless_compressed.len());
+  let decompressed = simple_decompress::<f64>(&compressed)?;
+  let expected = nums
+    .iter()
+    .map(|x| (x * 100.0).round() * 0.01)
+    .collect::<Vec<_>>();
+  assert_nums_eq(&decompressed, &expected, "lossy float mult")?;
+
+  // provided bases don't need to be detectable
+  let config = ChunkConfig::default().with_float_mult_spec(FloatMultSpec::LossyProvided(0.5));
+  let (compressed, meta) = compress_w_meta(&nums, &config)?;
+  assert_eq!(meta.mode, Mode::lossy_float_mult(0.5));
+  let decompressed = simple_decompress::<f64>(&compressed)?;
+  let expected = nums
+    .iter()
+    .map(|x| (x * 2.0).round() * 0.5)
+    .collect::<Vec<_>>();
+  assert_nums_eq(
+    &decompressed,
+    &expected,
+    "lossy provided float mult",
+  )?;
+  Ok(())
+}
+
 #[test]
 fn test_trivial_first_latent_var() -> PcoResult<()> {
   let mut nums = Vec::new();
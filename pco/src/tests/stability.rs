@@ -1,18 +1,30 @@
+use rand::Rng;
+use rand_xoshiro::rand_core::SeedableRng;
+
 use crate::chunk_config::ChunkConfig;
 use crate::chunk_meta::ChunkMeta;
 use crate::data_types::NumberLike;
 use crate::errors::{ErrorKind, PcoResult};
 use crate::standalone::{simple_decompress, FileCompressor};
-use crate::IntMultSpec;
+use crate::{ExceptionSpec, IntMultSpec, Mode, RunLengthSpec};
 
 fn assert_panic_safe<T: NumberLike>(nums: Vec<T>) -> PcoResult<ChunkMeta<T::L>> {
-  let fc = FileCompressor::default();
   let config = ChunkConfig {
     int_mult_spec: IntMultSpec::Disabled,
+    run_length_spec: RunLengthSpec::Disabled,
+    exception_spec: ExceptionSpec::Disabled,
     delta_encoding_order: Some(0),
     ..Default::default()
   };
-  let cc = fc.chunk_compressor(&nums, &config)?;
+  assert_panic_safe_w_config(nums, &config)
+}
+
+fn assert_panic_safe_w_config<T: NumberLike>(
+  nums: Vec<T>,
+  config: &ChunkConfig,
+) -> PcoResult<ChunkMeta<T::L>> {
+  let fc = FileCompressor::default();
+  let cc = fc.chunk_compressor(&nums, config)?;
   let meta = cc.meta().clone();
   let mut compressed = Vec::new();
   fc.write_header(&mut compressed)?;
@@ -62,6 +74,20 @@ fn test_insufficient_data_sparse() -> PcoResult<()> {
   Ok(())
 }
 
+#[test]
+fn test_insufficient_data_exceptions() -> PcoResult<()> {
+  let mut rng = rand_xoshiro::Xoroshiro128PlusPlus::seed_from_u64(0);
+  let mut nums = (0..2000)
+    .map(|_| rng.gen_range(0..1000))
+    .collect::<Vec<u32>>();
+  nums[0] = u32::MAX;
+  nums[1500] = u32::MAX - 77;
+
+  let meta = assert_panic_safe_w_config(nums, &ChunkConfig::default())?;
+  assert!(meta.has_exceptions);
+  Ok(())
+}
+
 #[test]
 fn test_insufficient_data_long_offsets() -> PcoResult<()> {
   let n = 1000;
@@ -79,3 +105,15 @@ fn test_insufficient_data_long_offsets() -> PcoResult<()> {
   );
   Ok(())
 }
+
+#[test]
+fn test_insufficient_data_run_length() -> PcoResult<()> {
+  let mut nums = Vec::new();
+  for i in 0..1000 {
+    nums.push(i / 100);
+  }
+
+  let meta = assert_panic_safe_w_config(nums, &ChunkConfig::default())?;
+  assert_eq!(meta.mode, Mode::RunLength);
+  Ok(())
+}
@@ -6,20 +6,24 @@ use crate::bit_writer::BitWriter;
 use crate::compression_intermediates::{DissectedPage, DissectedPageVar, PageInfo};
 use crate::compression_table::CompressionTable;
 use crate::constants::{
-  Bitlen, Weight, ANS_INTERLEAVING, LIMITED_UNOPTIMIZED_BINS_LOG, MAX_COMPRESSION_LEVEL,
-  MAX_DELTA_ENCODING_ORDER, MAX_ENTRIES, OVERSHOOT_PADDING, PAGE_PADDING,
+  Bitlen, Weight, ANS_INTERLEAVING, LIMITED_UNOPTIMIZED_BINS_LOG, MAX_CHUNK_ENTRIES,
+  MAX_COMPRESSION_LEVEL, MAX_DELTA_ENCODING_ORDER, MAX_DELTA_LAG, MAX_SEEK_INTERVAL,
+  OVERSHOOT_PADDING, PAGE_PADDING,
 };
 use crate::data_types::{Latent, NumberLike};
 use crate::delta::DeltaMoments;
 use crate::errors::{PcoError, PcoResult};
+use crate::exception_utils::Exception;
 use crate::histograms::histogram;
 use crate::latent_batch_dissector::LatentBatchDissector;
-use crate::page_meta::{PageLatentVarMeta, PageMeta};
+use crate::page_meta::{PageLatentVarMeta, PageMeta, SeekPoint};
 use crate::read_write_uint::ReadWriteUint;
 use crate::wrapped::guarantee;
 use crate::{
-  ans, bin_optimization, bit_reader, bit_writer, data_types, delta, read_write_uint, Bin,
-  ChunkConfig, ChunkLatentVarMeta, ChunkMeta, Mode, PagingSpec, FULL_BATCH_N,
+  ans, bin_optimization, bit_reader, bit_writer, data_types, delta, delta_lag_utils,
+  dictionary_utils, exception_utils, lpc_utils, read_write_uint, run_len_utils, Bin, ChunkConfig,
+  ChunkLatentVarMeta, ChunkMeta, DeltaLagSpec, DictionarySpec, ExceptionSpec, LossySpec, LpcSpec,
+  Mode, PagingSpec, RunLengthSpec, ValueBoundsSpec, FULL_BATCH_N,
 };
 
 // if it looks like the average page of size n will use k bits, hint that it
@@ -234,6 +238,8 @@ pub struct ChunkCompressor<L: Latent> {
   deltas: Vec<Vec<L>>,
   // n_pages x n_latent_vars
   delta_moments: Vec<Vec<DeltaMoments<L>>>,
+  // n_pages x n_exceptions, empty unless the chunk has exceptions
+  exceptions: Vec<Vec<Exception<L>>>,
 }
 
 fn bins_from_compression_infos<L: Latent>(infos: &[BinCompressionInfo<L>]) -> Vec<Bin<L>> {
@@ -258,6 +264,36 @@ fn validate_config(config: &ChunkConfig) -> PcoResult<()> {
     }
   }
 
+  if let DeltaLagSpec::Provided(lag) = config.delta_lag_spec {
+    if lag == 0 || lag > MAX_DELTA_LAG {
+      return Err(PcoError::invalid_argument(format!(
+        "delta lag must be between 1 and {} (was {})",
+        MAX_DELTA_LAG, lag,
+      )));
+    }
+  }
+
+  if let Some(interval) = config.seek_index_interval {
+    if interval == 0 || interval > MAX_SEEK_INTERVAL {
+      return Err(PcoError::invalid_argument(format!(
+        "seek index interval must be between 1 and {} (was {})",
+        MAX_SEEK_INTERVAL, interval,
+      )));
+    }
+  }
+
+  match config.lossy_spec {
+    LossySpec::AbsError(tolerance) | LossySpec::RelError(tolerance)
+      if !(tolerance.is_finite() && tolerance >= 0.0) =>
+    {
+      return Err(PcoError::invalid_argument(format!(
+        "lossy error tolerance must be finite and non-negative (was {})",
+        tolerance,
+      )));
+    }
+    _ => (),
+  }
+
   Ok(())
 }
 
@@ -267,10 +303,10 @@ fn validate_chunk_size(n: usize) -> PcoResult<()> {
       "cannot compress empty chunk",
     ));
   }
-  if n > MAX_ENTRIES {
+  if n > MAX_CHUNK_ENTRIES {
     return Err(PcoError::invalid_argument(format!(
       "count may not exceed {} per chunk (was {})",
-      MAX_ENTRIES, n,
+      MAX_CHUNK_ENTRIES, n,
     )));
   }
 
@@ -293,7 +329,10 @@ fn collect_contiguous_deltas<L: Latent>(
 fn build_page_infos_and_delta_moments<L: Latent>(
   mode: Mode<L>,
   delta_order: usize,
+  delta_lag: usize,
+  lpc_coefficients: &[i16],
   n_per_page: &[usize],
+  n_latents_per_page: &[usize],
   latents: &mut [Vec<L>],
 ) -> (Vec<PageInfo>, Vec<Vec<DeltaMoments<L>>>) {
   let n_pages = n_per_page.len();
@@ -302,23 +341,38 @@ fn build_page_infos_and_delta_moments<L: Latent>(
 
   // delta encoding
   let mut start_idx = 0;
-  for (&page_n, delta_moments) in n_per_page.iter().zip(delta_moments.iter_mut()) {
+  for ((&page_n, &n_latents), delta_moments) in n_per_page
+    .iter()
+    .zip(n_latents_per_page)
+    .zip(delta_moments.iter_mut())
+  {
     let mut end_idx_per_var = Vec::new();
     for (latent_var_idx, latents) in latents.iter_mut().enumerate() {
-      let var_delta_order = mode.delta_order_for_latent_var(latent_var_idx, delta_order);
-      delta_moments.push(delta::encode_in_place(
-        &mut latents[start_idx..start_idx + page_n],
-        var_delta_order,
-      ));
-      end_idx_per_var.push(start_idx + page_n.saturating_sub(var_delta_order));
+      let page_latents = &mut latents[start_idx..start_idx + n_latents];
+      let (var_delta_moments, n_var_delta_moments) = match (mode, latent_var_idx) {
+        (Mode::Lpc, 0) => (
+          lpc_utils::encode_in_place(lpc_coefficients, page_latents),
+          lpc_coefficients.len() + 1,
+        ),
+        _ => {
+          let var_delta_order = mode.delta_order_for_latent_var(latent_var_idx, delta_order);
+          (
+            delta::encode_in_place(page_latents, var_delta_order, delta_lag),
+            var_delta_order * delta_lag,
+          )
+        }
+      };
+      delta_moments.push(var_delta_moments);
+      end_idx_per_var.push(start_idx + n_latents.saturating_sub(n_var_delta_moments));
     }
     page_infos.push(PageInfo {
       page_n,
+      n_latents,
       start_idx,
       end_idx_per_var,
     });
 
-    start_idx += page_n;
+    start_idx += n_latents;
   }
 
   (page_infos, delta_moments)
@@ -329,14 +383,33 @@ fn new_candidate_w_split_and_delta_order<L: Latent>(
   paging_spec: &PagingSpec,
   mode: Mode<L>,
   delta_order: usize,
+  delta_lag: usize,
+  lpc_coefficients: &[i16],
   unoptimized_bins_log: Bitlen,
 ) -> PcoResult<(ChunkCompressor<L>, Vec<Vec<Weight>>)> {
+  // the lag doesn't mean anything without delta encoding
+  let delta_lag = if delta_order == 0 { 1 } else { delta_lag };
   let chunk_n = latents[0].len();
   let n_per_page = paging_spec.n_per_page(chunk_n)?;
   let n_latent_vars = mode.n_latent_vars();
 
-  let (page_infos, delta_moments) =
-    build_page_infos_and_delta_moments(mode, delta_order, &n_per_page, &mut latents);
+  let n_latents_per_page = if let Mode::RunLength = mode {
+    let (run_latents, n_runs_per_page) = run_len_utils::split_latents(&latents[0], &n_per_page);
+    latents = run_latents;
+    n_runs_per_page
+  } else {
+    n_per_page.clone()
+  };
+
+  let (page_infos, delta_moments) = build_page_infos_and_delta_moments(
+    mode,
+    delta_order,
+    delta_lag,
+    lpc_coefficients,
+    &n_per_page,
+    &n_latents_per_page,
+    &mut latents,
+  );
   let deltas = latents;
 
   // training bins
@@ -385,13 +458,16 @@ fn new_candidate_w_split_and_delta_order<L: Latent>(
     bin_counts.push(trained.counts);
   }
 
-  let meta = ChunkMeta::new(mode, delta_order, var_metas);
+  let mut meta = ChunkMeta::new(mode, delta_order, delta_lag, var_metas);
+  meta.lpc_coefficients = lpc_coefficients.to_vec();
+  let exceptions = vec![Vec::new(); page_infos.len()];
   let chunk_compressor = ChunkCompressor {
     meta,
     latent_var_policies: var_policies,
     page_infos,
     deltas,
     delta_moments,
+    exceptions,
   };
 
   Ok((chunk_compressor, bin_counts))
@@ -444,6 +520,8 @@ fn choose_delta_encoding_order<L: Latent>(
       &PagingSpec::Exact(vec![sample.len()]),
       Mode::Classic,
       delta_encoding_order,
+      1,
+      &[],
       unoptimized_bins_log,
     )?;
     let size_estimate = sample_cc.chunk_meta_size_hint() + sample_cc.page_size_hint_inner(0, 1.0);
@@ -481,19 +559,119 @@ fn new_candidate_w_split<L: Latent>(
 ) -> PcoResult<(ChunkCompressor<L>, Vec<Vec<Weight>>)> {
   let unoptimized_bins_log =
     choose_unoptimized_bins_log(config.compression_level, latents[0].len());
+
+  // Lags are mainly useful with first order delta encoding, so we default to
+  // that instead of searching over orders.
+  let lagged_delta_order = config.delta_encoding_order.unwrap_or(1);
+  let maybe_lag = match config.delta_lag_spec {
+    DeltaLagSpec::Provided(lag) => {
+      return new_candidate_w_split_and_delta_order(
+        latents,
+        &config.paging_spec,
+        mode,
+        lagged_delta_order,
+        lag,
+        &[],
+        unoptimized_bins_log,
+      );
+    }
+    // run values are spaced irregularly, so lags don't make sense for them
+    DeltaLagSpec::Enabled if lagged_delta_order > 0 && !matches!(mode, Mode::RunLength) => {
+      delta_lag_utils::choose_lag(&latents[0])
+    }
+    _ => None,
+  };
+  let lagged_candidate = match maybe_lag {
+    Some(lag) => Some(new_candidate_w_split_and_delta_order(
+      latents.clone(),
+      &config.paging_spec,
+      mode,
+      lagged_delta_order,
+      lag,
+      &[],
+      unoptimized_bins_log,
+    )?),
+    None => None,
+  };
+
   let delta_order = if let Some(delta_order) = config.delta_encoding_order {
     delta_order
+  } else if let Mode::RunLength = mode {
+    // run values are what get delta encoded
+    let run_values = run_len_utils::run_values(&latents[0]);
+    choose_delta_encoding_order(&run_values, unoptimized_bins_log)?
   } else {
     choose_delta_encoding_order(&latents[0], unoptimized_bins_log)?
   };
 
-  new_candidate_w_split_and_delta_order(
+  let candidate = new_candidate_w_split_and_delta_order(
     latents,
     &config.paging_spec,
     mode,
     delta_order,
+    1,
+    &[],
     unoptimized_bins_log,
-  )
+  )?;
+
+  match lagged_candidate {
+    Some(lagged) if lagged.0.size_estimate() < candidate.0.size_estimate() => Ok(lagged),
+    _ => Ok(candidate),
+  }
+}
+
+// Dictionary, run length, and linear prediction modes are alternatives to
+// classic mode that work on the same latents, so we only consider them when
+// the data type chose classic mode, and only use them if they look better.
+fn new_candidate_w_split_or_alternatives<L: Latent>(
+  latents: Vec<Vec<L>>,
+  config: &ChunkConfig,
+) -> PcoResult<(ChunkCompressor<L>, Vec<Vec<Weight>>)> {
+  let mut alternatives = Vec::new();
+  if let DictionarySpec::Enabled = config.dictionary_spec {
+    if let Some(dictionary) = dictionary_utils::choose_dictionary(&latents[0]) {
+      let dictionary_latents = dictionary_utils::split_latents(&dictionary, &latents[0]);
+      let (mut candidate, bin_counts) =
+        new_candidate_w_split(Mode::Dictionary, dictionary_latents, config)?;
+      candidate.meta.dictionary = dictionary;
+      alternatives.push((candidate, bin_counts));
+    }
+  }
+  if let RunLengthSpec::Enabled = config.run_length_spec {
+    if run_len_utils::has_long_runs(&latents[0]) {
+      alternatives.push(new_candidate_w_split(
+        Mode::RunLength,
+        vec![latents[0].clone()],
+        config,
+      )?);
+    }
+  }
+  // linear prediction replaces delta encoding, so we respect an explicitly
+  // requested delta encoding order
+  if let (LpcSpec::Enabled, None) = (config.lpc_spec, config.delta_encoding_order) {
+    if let Some(lpc_coefficients) = lpc_utils::choose_coefficients(&latents[0]) {
+      alternatives.push(new_candidate_w_split_and_delta_order(
+        vec![latents[0].clone()],
+        &config.paging_spec,
+        Mode::Lpc,
+        0,
+        1,
+        &lpc_coefficients,
+        choose_unoptimized_bins_log(config.compression_level, latents[0].len()),
+      )?);
+    }
+  }
+
+  let mut best = new_candidate_w_split(Mode::Classic, latents, config)?;
+  let mut best_size = best.0.size_estimate();
+  for alternative in alternatives {
+    let size = alternative.0.size_estimate();
+    if size < best_size {
+      best = alternative;
+      best_size = size;
+    }
+  }
+  Ok(best)
 }
 
 fn should_fallback<L: Latent>(
@@ -502,12 +680,20 @@ fn should_fallback<L: Latent>(
   bin_counts_per_latent_var: Vec<Vec<Weight>>,
 ) -> bool {
   let meta = &candidate.meta;
-  if meta.delta_encoding_order == 0 && matches!(meta.mode, Mode::Classic) {
+  if meta.delta_encoding_order == 0 && matches!(meta.mode, Mode::Classic) && !meta.has_exceptions {
     // we already have a size guarantee in this case
     return false;
   }
 
   let n_pages = candidate.page_infos.len();
+  let page_meta_size = candidate
+    .exceptions
+    .iter()
+    .zip(&candidate.page_infos)
+    .map(|(page_exceptions, page_info)| {
+      meta.exact_page_meta_size(page_exceptions.len(), page_info.n_latents)
+    })
+    .sum::<usize>();
 
   // worst case trailing bytes after bit packing
   let mut worst_case_body_bit_size = 7 * n_pages;
@@ -522,9 +708,7 @@ fn should_fallback<L: Latent>(
     }
   }
 
-  let worst_case_size = meta.exact_size()
-    + n_pages * meta.exact_page_meta_size()
-    + worst_case_body_bit_size.div_ceil(8);
+  let worst_case_size = meta.exact_size() + page_meta_size + worst_case_body_bit_size.div_ceil(8);
   let baseline_size = guarantee::chunk_size::<L>(n);
   worst_case_size > baseline_size
 }
@@ -534,8 +718,16 @@ fn fallback_chunk_compressor<L: Latent>(
   config: &ChunkConfig,
 ) -> PcoResult<ChunkCompressor<L>> {
   let n_per_page = config.paging_spec.n_per_page(latents[0].len())?;
-  let (page_infos, delta_moments) =
-    build_page_infos_and_delta_moments(Mode::Classic, 0, &n_per_page, &mut latents);
+  let exceptions = vec![Vec::new(); n_per_page.len()];
+  let (page_infos, delta_moments) = build_page_infos_and_delta_moments(
+    Mode::Classic,
+    0,
+    1,
+    &[],
+    &n_per_page,
+    &n_per_page,
+    &mut latents,
+  );
   let infos = vec![BinCompressionInfo::<L> {
     weight: 1,
     symbol: 0,
@@ -562,9 +754,39 @@ fn fallback_chunk_compressor<L: Latent>(
     page_infos,
     deltas: latents,
     delta_moments,
+    exceptions,
   })
 }
 
+type CandidateWBinCounts<L> = (ChunkCompressor<L>, Vec<Vec<Weight>>);
+
+fn new_candidate<T: NumberLike>(
+  nums: &[T],
+  config: &ChunkConfig,
+) -> PcoResult<CandidateWBinCounts<T::L>> {
+  let (mode, latents) = T::choose_mode_and_split_latents(nums, config);
+
+  match mode {
+    Mode::Classic => new_candidate_w_split_or_alternatives(latents, config),
+    _ => new_candidate_w_split(mode, latents, config),
+  }
+}
+
+// We choose the mode and everything else based on the patched numbers, so
+// outliers can't throw them off.
+fn new_candidate_w_exceptions<T: NumberLike>(
+  nums: &[T],
+  exception_idxs: &[usize],
+  config: &ChunkConfig,
+) -> PcoResult<CandidateWBinCounts<T::L>> {
+  let n_per_page = config.paging_spec.n_per_page(nums.len())?;
+  let patched = exception_utils::patch(nums, exception_idxs);
+  let (mut candidate, bin_counts) = new_candidate(&patched, config)?;
+  candidate.meta.has_exceptions = true;
+  candidate.exceptions = exception_utils::exceptions_per_page(nums, exception_idxs, &n_per_page);
+  Ok((candidate, bin_counts))
+}
+
 // Should this take nums as a slice of slices instead of having a config.paging_spec?
 pub(crate) fn new<T: NumberLike>(
   nums: &[T],
@@ -574,17 +796,52 @@ pub(crate) fn new<T: NumberLike>(
   let n = nums.len();
   validate_chunk_size(n)?;
 
-  let (mode, latents) = T::choose_mode_and_split_latents(nums, config);
-
-  let (candidate, bin_counts) = new_candidate_w_split(mode, latents, config)?;
+  let (mut candidate, mut bin_counts) = new_candidate(nums, config)?;
+  if let ExceptionSpec::Enabled = config.exception_spec {
+    let latents = nums
+      .iter()
+      .map(|num| num.to_latent_ordered())
+      .collect::<Vec<_>>();
+    if let Some(exception_idxs) = exception_utils::choose_exception_idxs(&latents) {
+      let alternative = new_candidate_w_exceptions(nums, &exception_idxs, config)?;
+      if alternative.0.size_estimate() < candidate.size_estimate() {
+        (candidate, bin_counts) = alternative;
+      }
+    }
+  }
   if should_fallback(n, &candidate, bin_counts) {
     let latents = data_types::split_latents_classic(nums);
-    return fallback_chunk_compressor(latents, config);
+    candidate = fallback_chunk_compressor(latents, config)?;
+  }
+
+  // run length mode's batches are of runs rather than numbers, so we can't
+  // seek by them
+  if !matches!(candidate.meta.mode, Mode::RunLength) {
+    candidate.meta.seek_index_interval = config.seek_index_interval;
+  }
+
+  // lossy quantization can move numbers past the original ones' bounds
+  if matches!(
+    config.value_bounds_spec,
+    ValueBoundsSpec::Enabled
+  ) && matches!(config.lossy_spec, LossySpec::None)
+  {
+    candidate.meta.stored_value_bounds = latent_bounds(nums);
   }
 
   Ok(candidate)
 }
 
+fn latent_bounds<T: NumberLike>(nums: &[T]) -> Option<(T::L, T::L)> {
+  let mut latents = nums.iter().map(|num| num.to_latent_ordered());
+  let first = latents.next()?;
+  Some(
+    latents.fold((first, first), |(lower, upper), latent| {
+      (lower.min(latent), upper.max(latent))
+    }),
+  )
+}
+
 impl<L: Latent> ChunkCompressor<L> {
   fn page_moments(&self, page_idx: usize, latent_var_idx: usize) -> &DeltaMoments<L> {
     &self.delta_moments[page_idx][latent_var_idx]
@@ -628,6 +885,9 @@ impl<L: Latent> ChunkCompressor<L> {
       ..
     } = self;
 
+    let page_info = &page_infos[page_idx];
+    let seek_batch_idxs = self.seek_batch_idxs(page_info.n_latents);
+
     let uninit_dissected_page_var = |n, ans_default_state| {
       let ans_final_states = [ans_default_state; ANS_INTERLEAVING];
       DissectedPageVar {
@@ -636,10 +896,10 @@ impl<L: Latent> ChunkCompressor<L> {
         offsets: uninit_vec(n),
         offset_bits: uninit_vec(n),
         ans_final_states,
+        seek_ans_states: vec![ans_final_states; seek_batch_idxs.len()],
       }
     };
 
-    let page_info = &page_infos[page_idx];
     let mut per_var = Vec::new();
 
     for ((var_policy, &delta_end), var_deltas) in latent_var_policies
@@ -661,20 +921,140 @@ impl<L: Latent> ChunkCompressor<L> {
         uninit_dissected_page_var(page_deltas.len(), encoder.default_state());
 
       // we go through in reverse for ANS!
+      // Seek batches past the last delta batch keep the default state.
       let mut lbd = LatentBatchDissector::new(table, encoder);
       for (batch_idx, batch) in page_deltas.chunks(FULL_BATCH_N).enumerate().rev() {
         let base_i = batch_idx * FULL_BATCH_N;
-        lbd.dissect_latent_batch(batch, base_i, &mut dissected_page_var)
+        lbd.dissect_latent_batch(batch, base_i, &mut dissected_page_var);
+        if let Ok(seek_idx) = seek_batch_idxs.binary_search(&batch_idx) {
+          dissected_page_var.seek_ans_states[seek_idx] = dissected_page_var.ans_final_states;
+        }
       }
       per_var.push(dissected_page_var);
     }
 
     Ok(DissectedPage {
-      page_n: page_info.page_n,
+      n_latents: page_info.n_latents,
       per_var,
     })
   }
 
+  fn seek_batch_idxs(&self, n_latents: usize) -> Vec<usize> {
+    match self.meta.seek_index_interval {
+      Some(interval) => (1..=self.meta.n_seek_points(n_latents))
+        .map(|seek_idx| seek_idx * interval)
+        .collect(),
+      None => Vec::new(),
+    }
+  }
+
+  // To find the delta moments at each seek batch, we replay delta decoding
+  // exactly as the page decompressor will.
+  fn seek_delta_moments(
+    &self,
+    page_idx: usize,
+    latent_var_idx: usize,
+    seek_batch_idxs: &[usize],
+  ) -> Vec<DeltaMoments<L>> {
+    let page_info = &self.page_infos[page_idx];
+    let page_deltas =
+      &self.deltas[latent_var_idx][page_info.start_idx..page_info.end_idx_per_var[latent_var_idx]];
+    let lpc_coefficients = if latent_var_idx == 0 {
+      self.meta.lpc_coefficients.as_slice()
+    } else {
+      &[]
+    };
+
+    let mut delta_moments = self.page_moments(page_idx, latent_var_idx).clone();
+    let mut res = Vec::with_capacity(seek_batch_idxs.len());
+    let mut batch = [L::ZERO; FULL_BATCH_N];
+    for batch_idx in 0..seek_batch_idxs.last().copied().unwrap_or(0) {
+      let batch_start = batch_idx * FULL_BATCH_N;
+      let batch_n = min(
+        FULL_BATCH_N,
+        page_info.n_latents - batch_start,
+      );
+      let pre_delta_len = min(
+        batch_n,
+        page_deltas.len().saturating_sub(batch_start),
+      );
+      batch[..pre_delta_len]
+        .copy_from_slice(&page_deltas[batch_start..batch_start + pre_delta_len]);
+      batch[pre_delta_len..batch_n].fill(L::ZERO);
+      if lpc_coefficients.is_empty() {
+        delta::decode_in_place(
+          &mut delta_moments,
+          self.meta.delta_lag,
+          &mut batch[..batch_n],
+        );
+      } else {
+        lpc_utils::decode_in_place(
+          lpc_coefficients,
+          &mut delta_moments,
+          &mut batch[..batch_n],
+        );
+      }
+
+      if seek_batch_idxs.binary_search(&(batch_idx + 1)).is_ok() {
+        res.push(delta_moments.clone());
+      }
+    }
+    res
+  }
+
+  fn seek_points(&self, page_idx: usize, dissected_page: &DissectedPage<L>) -> Vec<SeekPoint<L>> {
+    let seek_batch_idxs = self.seek_batch_idxs(dissected_page.n_latents);
+    let mut seek_points = seek_batch_idxs
+      .iter()
+      .map(|_| SeekPoint {
+        bit_idx: 0,
+        per_var: Vec::with_capacity(dissected_page.per_var.len()),
+      })
+      .collect::<Vec<_>>();
+
+    for (latent_idx, (dissected, policy)) in dissected_page
+      .per_var
+      .iter()
+      .zip(&self.latent_var_policies)
+      .enumerate()
+    {
+      let base_state = policy.encoder.default_state();
+      let delta_momentss = self.seek_delta_moments(page_idx, latent_idx, &seek_batch_idxs);
+      // each batch writes this latent variable's ANS bits, then offset bits
+      let mut bit_idx = 0;
+      let mut n_written = 0;
+      for (seek_idx, (&batch_idx, delta_moments)) in
+        seek_batch_idxs.iter().zip(delta_momentss).enumerate()
+      {
+        let end = min(
+          batch_idx * FULL_BATCH_N,
+          dissected.offsets.len(),
+        );
+        if policy.needs_ans {
+          bit_idx += dissected.ans_bits[n_written..end]
+            .iter()
+            .map(|&bits| bits as usize)
+            .sum::<usize>();
+        }
+        if policy.max_u64s_per_offset > 0 {
+          bit_idx += dissected.offset_bits[n_written..end]
+            .iter()
+            .map(|&bits| bits as usize)
+            .sum::<usize>();
+        }
+        n_written = end;
+
+        let seek_point = &mut seek_points[seek_idx];
+        seek_point.bit_idx += bit_idx;
+        seek_point.per_var.push(PageLatentVarMeta {
+          delta_moments,
+          ans_final_state_idxs: dissected.seek_ans_states[seek_idx].map(|state| state - base_state),
+        });
+      }
+    }
+    seek_points
+  }
+
   /// Returns an estimate of the overall size of a specific page.
   ///
   /// This can be useful when building the file as a `Vec<u8>` in memory;
@@ -683,6 +1063,13 @@ impl<L: Latent> ChunkCompressor<L> {
     self.page_size_hint_inner(page_idx, PAGE_SIZE_OVERESTIMATION)
   }
 
+  fn size_estimate(&self) -> usize {
+    self.chunk_meta_size_hint()
+      + (0..self.page_infos.len())
+        .map(|page_idx| self.page_size_hint_inner(page_idx, 1.0))
+        .sum::<usize>()
+  }
+
   fn page_size_hint_inner(&self, page_idx: usize, page_size_overestimation: f64) -> usize {
     let page_info = &self.page_infos[page_idx];
     let mut body_bit_size = 0;
@@ -695,7 +1082,10 @@ impl<L: Latent> ChunkCompressor<L> {
       let nums_bit_size = page_n_deltas as f64 * var_policy.avg_bits_per_delta;
       body_bit_size += (nums_bit_size * page_size_overestimation).ceil() as usize;
     }
-    self.meta.exact_page_meta_size() + body_bit_size.div_ceil(8)
+    self.meta.exact_page_meta_size(
+      self.exceptions[page_idx].len(),
+      page_info.n_latents,
+    ) + body_bit_size.div_ceil(8)
   }
 
   #[inline(never)]
@@ -705,10 +1095,10 @@ impl<L: Latent> ChunkCompressor<L> {
     writer: &mut BitWriter<W>,
   ) -> PcoResult<()> {
     let mut batch_start = 0;
-    while batch_start < dissected_page.page_n {
+    while batch_start < dissected_page.n_latents {
       let batch_end = min(
         batch_start + FULL_BATCH_N,
-        dissected_page.page_n,
+        dissected_page.n_latents,
       );
       for (dissected_page_var, policy) in
         dissected_page.per_var.iter().zip(&self.latent_var_policies)
@@ -757,15 +1147,27 @@ impl<L: Latent> ChunkCompressor<L> {
         ans_final_state_idxs,
       });
     }
+    let n_runs = match self.meta.mode {
+      Mode::RunLength => Some(self.page_infos[page_idx].n_latents),
+      _ => None,
+    };
+    let exceptions = self
+      .meta
+      .has_exceptions
+      .then(|| self.exceptions[page_idx].clone());
+    let seek_points = self.seek_points(page_idx, &dissected_page);
     let page_meta = PageMeta {
+      n_runs,
+      exceptions,
       per_var: latent_metas,
+      seek_points,
     };
     let ans_size_logs = self
       .latent_var_policies
       .iter()
       .map(|config| config.encoder.size_log());
 
-    unsafe { page_meta.write_to(ans_size_logs, &mut writer) };
+    unsafe { page_meta.write_to(ans_size_logs, &mut writer)? };
 
     self.write_dissected_page(dissected_page, &mut writer)?;
 
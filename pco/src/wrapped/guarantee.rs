@@ -10,6 +10,12 @@ pub(crate) fn baseline_chunk_meta<L: Latent>() -> ChunkMeta<L> {
   ChunkMeta {
     mode: Mode::Classic,
     delta_encoding_order: 0,
+    delta_lag: 1,
+    dictionary: vec![],
+    lpc_coefficients: vec![],
+    has_exceptions: false,
+    seek_index_interval: None,
+    stored_value_bounds: None,
     per_latent_var: vec![ChunkLatentVarMeta {
       ans_size_log: 0,
       bins: vec![Bin {
@@ -23,10 +29,16 @@ pub(crate) fn baseline_chunk_meta<L: Latent>() -> ChunkMeta<L> {
 
 /// Returns the maximum possible byte size of a wrapped chunk for a given
 /// latent type (e.g. u32 or u64) and count of numbers.
+///
+/// This assumes the chunk has no seek index; see
+/// [`ChunkConfig::seek_index_interval`][crate::ChunkConfig::seek_index_interval].
 pub fn chunk_size<L: Latent>(n: usize) -> usize {
   // TODO if we ever add NumberLikes that are smaller than their Latents, we
   // may want to make this more generic
-  baseline_chunk_meta::<L>().exact_size() + n * L::BITS.div_ceil(8) as usize
+  let mut meta = baseline_chunk_meta::<L>();
+  // any chunk may store its value bounds
+  meta.stored_value_bounds = Some((L::ZERO, L::MAX));
+  meta.exact_size() + n * L::BITS.div_ceil(8) as usize
 }
 
 #[cfg(test)]
@@ -4,34 +4,53 @@ use std::marker::PhantomData;
 
 use better_io::BetterBufRead;
 
+use crate::aggregation::Aggregator;
 use crate::bit_reader::{BitReader, BitReaderBuilder};
 use crate::constants::{FULL_BATCH_N, PAGE_PADDING};
 use crate::data_types::{Latent, NumberLike};
 use crate::delta;
 use crate::delta::DeltaMoments;
 use crate::errors::{PcoError, PcoResult};
+use crate::exception_utils::Exception;
 use crate::latent_batch_decompressor::LatentBatchDecompressor;
-use crate::page_meta::PageMeta;
+use crate::page_meta::{PageMeta, SeekPoint};
 use crate::progress::Progress;
-use crate::{bit_reader, ChunkMeta, Mode};
+use crate::{bit_reader, dictionary_utils, lpc_utils, Agg, AggValue, ChunkMeta, Mode};
 
 const PERFORMANT_BUF_READ_CAPACITY: usize = 8192;
 
 #[derive(Clone, Debug)]
 pub struct State<L: Latent> {
   n_processed: usize,
+  delta_lag: usize,
+  // only used in linear prediction mode, for the primary latents
+  lpc_coefficients: Vec<i16>,
   latent_batch_decompressors: Vec<LatentBatchDecompressor<L>>,
   delta_momentss: Vec<DeltaMoments<L>>, // one per latent variable
   primary_latents: [L; FULL_BATCH_N],
   secondary_latents: [L; FULL_BATCH_N],
+  // only used in run length mode, where the latents are batches of runs
+  n_runs_processed: usize,
+  run_batch_n: usize,
+  run_idx: usize,
+  run_n_remaining: usize,
+  // index of the next exception to patch in
+  exception_idx: usize,
 }
 
 /// Holds metadata about a page and supports decompression.
 pub struct PageDecompressor<T: NumberLike, R: BetterBufRead> {
   // immutable
   n: usize,
+  n_runs: usize,
   mode: Mode<T::L>,
+  dictionary: Vec<T::L>,
+  exceptions: Vec<Exception<T::L>>,
   maybe_constant_secondary: Option<T::L>,
+  seek_index_interval: Option<usize>,
+  seek_points: Vec<SeekPoint<T::L>>,
+  // where the page body starts, as counted by the reader builder
+  body_start_bit_idx: usize,
   phantom: PhantomData<T>,
 
   // mutable
@@ -42,11 +61,13 @@ pub struct PageDecompressor<T: NumberLike, R: BetterBufRead> {
 unsafe fn decompress_latents_w_delta<L: Latent>(
   reader: &mut BitReader,
   delta_moments: &mut DeltaMoments<L>,
+  delta_lag: usize,
+  lpc_coefficients: &[i16],
   lbd: &mut LatentBatchDecompressor<L>,
   dst: &mut [L],
   n_remaining: usize,
 ) -> PcoResult<()> {
-  let n_remaining_pre_delta = n_remaining.saturating_sub(delta_moments.order());
+  let n_remaining_pre_delta = n_remaining.saturating_sub(delta_moments.n_moments());
   let pre_delta_len = if dst.len() <= n_remaining_pre_delta {
     dst.len()
   } else {
@@ -57,7 +78,11 @@ unsafe fn decompress_latents_w_delta<L: Latent>(
     n_remaining_pre_delta
   };
   lbd.decompress_latent_batch(reader, &mut dst[..pre_delta_len])?;
-  delta::decode_in_place(delta_moments, dst);
+  if lpc_coefficients.is_empty() {
+    delta::decode_in_place(delta_moments, delta_lag, dst);
+  } else {
+    lpc_utils::decode_in_place(lpc_coefficients, delta_moments, dst);
+  }
   Ok(())
 }
 
@@ -80,23 +105,48 @@ impl<T: NumberLike, R: BetterBufRead> PageDecompressor<T, R> {
     bit_reader::ensure_buf_read_capacity(&mut src, PERFORMANT_BUF_READ_CAPACITY);
     let mut reader_builder = BitReaderBuilder::new(src, PAGE_PADDING, 0);
 
-    let page_meta = reader_builder
-      .with_reader(|reader| unsafe { PageMeta::<T::L>::parse_from(reader, chunk_meta) })?;
+    let page_meta = unsafe { PageMeta::<T::L>::parse_from(&mut reader_builder, chunk_meta, n)? };
+    let body_start_bit_idx = reader_builder.bits_consumed();
 
     let mode = chunk_meta.mode;
+    let n_runs = page_meta.n_runs.unwrap_or(0);
+    if n_runs > n {
+      return Err(PcoError::corruption(format!(
+        "page has more runs than numbers ({} > {})",
+        n_runs, n,
+      )));
+    }
+
+    let exceptions = page_meta.exceptions.unwrap_or_default();
+    if let Some(exception) = exceptions.last() {
+      if exception.idx >= n {
+        return Err(PcoError::corruption(format!(
+          "exception position exceeds page size ({} >= {})",
+          exception.idx, n,
+        )));
+      }
+    }
+
     let delta_momentss = page_meta
       .per_var
       .iter()
       .map(|latent| latent.delta_moments.clone())
       .collect::<Vec<_>>();
 
+    // in run length mode, each latent is a run
+    let n_latents = match mode {
+      Mode::RunLength => n_runs,
+      _ => n,
+    };
     let mut latent_batch_decompressors = Vec::new();
     for latent_idx in 0..mode.n_latent_vars() {
       let chunk_latent_meta = &chunk_meta.per_latent_var[latent_idx];
-      if chunk_latent_meta.bins.is_empty() && n > chunk_meta.delta_encoding_order {
+      let n_deltas =
+        n_latents.saturating_sub(chunk_meta.n_delta_moments_for_latent_var(latent_idx));
+      if chunk_latent_meta.bins.is_empty() && n_deltas > 0 {
         return Err(PcoError::corruption(format!(
           "unable to decompress chunk with no bins and {} deltas",
-          n - chunk_meta.delta_encoding_order,
+          n_deltas,
         )));
       }
 
@@ -107,7 +157,7 @@ impl<T: NumberLike, R: BetterBufRead> PageDecompressor<T, R> {
     }
 
     let maybe_constant_secondary =
-      if latent_batch_decompressors.len() >= 2 && delta_momentss[1].order() == 0 {
+      if latent_batch_decompressors.len() >= 2 && delta_momentss[1].n_moments() == 0 {
         latent_batch_decompressors[1].maybe_constant_value
       } else {
         None
@@ -117,21 +167,127 @@ impl<T: NumberLike, R: BetterBufRead> PageDecompressor<T, R> {
     let secondary_default = maybe_constant_secondary.unwrap_or(T::L::default());
     Ok(Self {
       n,
+      n_runs,
       mode,
+      dictionary: chunk_meta.dictionary.clone(),
+      exceptions,
       maybe_constant_secondary,
+      seek_index_interval: chunk_meta.seek_index_interval,
+      seek_points: page_meta.seek_points,
+      body_start_bit_idx,
       phantom: PhantomData,
       reader_builder,
       state: State {
         n_processed: 0,
+        delta_lag: chunk_meta.delta_lag,
+        lpc_coefficients: chunk_meta.lpc_coefficients.clone(),
         latent_batch_decompressors,
         delta_momentss,
         primary_latents: [T::L::default(); FULL_BATCH_N],
         secondary_latents: [secondary_default; FULL_BATCH_N],
+        n_runs_processed: 0,
+        run_batch_n: 0,
+        run_idx: 0,
+        run_n_remaining: 0,
+        exception_idx: 0,
       },
     })
   }
 
+  fn decompress_run_batch(&mut self) -> PcoResult<()> {
+    let n_runs = self.n_runs;
+    let State {
+      latent_batch_decompressors,
+      delta_momentss,
+      primary_latents,
+      secondary_latents,
+      n_runs_processed,
+      run_batch_n,
+      run_idx,
+      delta_lag,
+      ..
+    } = &mut self.state;
+
+    let n_runs_remaining = n_runs - *n_runs_processed;
+    if n_runs_remaining == 0 {
+      return Err(PcoError::corruption(
+        "run lengths add up to fewer numbers than the page contains",
+      ));
+    }
+
+    let batch_n = min(n_runs_remaining, FULL_BATCH_N);
+    for (latent_idx, dst) in [
+      &mut primary_latents[..batch_n],
+      &mut secondary_latents[..batch_n],
+    ]
+    .into_iter()
+    .enumerate()
+    {
+      self.reader_builder.with_reader(|reader| unsafe {
+        decompress_latents_w_delta(
+          reader,
+          &mut delta_momentss[latent_idx],
+          *delta_lag,
+          &[],
+          &mut latent_batch_decompressors[latent_idx],
+          dst,
+          n_runs_remaining,
+        )
+      })?;
+    }
+
+    *n_runs_processed += batch_n;
+    *run_batch_n = batch_n;
+    *run_idx = 0;
+    Ok(())
+  }
+
+  fn decompress_batch_run_length(&mut self, dst: &mut [T]) -> PcoResult<()> {
+    let mut i = 0;
+    while i < dst.len() {
+      if self.state.run_n_remaining == 0 {
+        if self.state.run_idx == self.state.run_batch_n {
+          self.decompress_run_batch()?;
+        }
+        let len_minus_1 = self.state.secondary_latents[self.state.run_idx].to_u64() as usize;
+        self.state.run_n_remaining = len_minus_1.saturating_add(1);
+        self.state.run_idx += 1;
+      }
+
+      let value = T::from_latent_ordered(self.state.primary_latents[self.state.run_idx - 1]);
+      let fill_n = min(self.state.run_n_remaining, dst.len() - i);
+      dst[i..i + fill_n].fill(value);
+      self.state.run_n_remaining -= fill_n;
+      i += fill_n;
+    }
+
+    self.state.n_processed += dst.len();
+    if self.state.n_processed == self.n {
+      let State {
+        n_runs_processed,
+        run_batch_n,
+        run_idx,
+        run_n_remaining,
+        ..
+      } = self.state;
+      if n_runs_processed != self.n_runs || run_idx != run_batch_n || run_n_remaining != 0 {
+        return Err(PcoError::corruption(
+          "run lengths do not add up to the count of numbers in the page",
+        ));
+      }
+      self.reader_builder.with_reader(|reader| {
+        reader.drain_empty_byte("expected trailing bits at end of page to be empty")
+      })?;
+    }
+
+    Ok(())
+  }
+
   fn decompress_batch(&mut self, dst: &mut [T]) -> PcoResult<()> {
+    if let Mode::RunLength = self.mode {
+      return self.decompress_batch_run_length(dst);
+    }
+
     let batch_n = dst.len();
     let n = self.n;
     let mode = self.mode;
@@ -141,6 +297,8 @@ impl<T: NumberLike, R: BetterBufRead> PageDecompressor<T, R> {
       primary_latents,
       secondary_latents,
       n_processed,
+      delta_lag,
+      lpc_coefficients,
       ..
     } = &mut self.state;
 
@@ -157,6 +315,8 @@ impl<T: NumberLike, R: BetterBufRead> PageDecompressor<T, R> {
         decompress_latents_w_delta(
           reader,
           &mut delta_momentss[0],
+          *delta_lag,
+          lpc_coefficients,
           &mut latent_batch_decompressors[0],
           primary_dst,
           n - *n_processed,
@@ -169,6 +329,8 @@ impl<T: NumberLike, R: BetterBufRead> PageDecompressor<T, R> {
         decompress_latents_w_delta(
           reader,
           &mut delta_momentss[1],
+          *delta_lag,
+          &[],
           &mut latent_batch_decompressors[1],
           secondary_latents,
           n - *n_processed,
@@ -176,6 +338,15 @@ impl<T: NumberLike, R: BetterBufRead> PageDecompressor<T, R> {
       })?;
     }
 
+    if let Mode::Dictionary = mode {
+      let primary = if T::TRANSMUTABLE_TO_LATENT {
+        T::transmute_to_latents(dst)
+      } else {
+        &mut primary_latents[..batch_n]
+      };
+      dictionary_utils::join_latents(&self.dictionary, primary)?;
+    }
+
     if T::TRANSMUTABLE_TO_LATENT {
       T::join_latents(
         mode,
@@ -207,17 +378,10 @@ impl<T: NumberLike, R: BetterBufRead> PageDecompressor<T, R> {
   /// `dst` must have length either a multiple of 256 or be at least the count
   /// of numbers remaining in the page.
   pub fn decompress(&mut self, num_dst: &mut [T]) -> PcoResult<Progress> {
-    if num_dst.len() % FULL_BATCH_N != 0 && num_dst.len() < self.n_remaining() {
-      return Err(PcoError::invalid_argument(format!(
-        "num_dst's length must either be a multiple of {} or be \
-         at least the count of numbers remaining ({} < {})",
-        FULL_BATCH_N,
-        num_dst.len(),
-        self.n_remaining(),
-      )));
-    }
+    self.validate_dst_len(num_dst.len())?;
 
     let n_to_process = min(num_dst.len(), self.n_remaining());
+    let page_start = self.state.n_processed;
 
     let mut n_processed = 0;
     while n_processed < n_to_process {
@@ -225,6 +389,7 @@ impl<T: NumberLike, R: BetterBufRead> PageDecompressor<T, R> {
       self.decompress_batch(&mut num_dst[n_processed..dst_batch_end])?;
       n_processed = dst_batch_end;
     }
+    self.patch_exceptions(page_start, &mut num_dst[..n_to_process]);
 
     Ok(Progress {
       n_processed,
@@ -232,6 +397,191 @@ impl<T: NumberLike, R: BetterBufRead> PageDecompressor<T, R> {
     })
   }
 
+  /// Reads the next decompressed numbers into a destination of another
+  /// type, converting each one losslessly, e.g. from `i32` to `i64` or from
+  /// `f32` to `f64`.
+  ///
+  /// This otherwise behaves like
+  /// [`decompress`][PageDecompressor::decompress], with the same requirements
+  /// on `dst`'s length.
+  /// Numbers are converted one batch at a time as they are decoded.
+  pub fn decompress_as<D: From<T>>(&mut self, dst: &mut [D]) -> PcoResult<Progress> {
+    self.validate_dst_len(dst.len())?;
+
+    let n_to_process = min(dst.len(), self.n_remaining());
+    let mut scratch = [T::default(); FULL_BATCH_N];
+    let mut n_processed = 0;
+    while n_processed < n_to_process {
+      let batch_n = min(FULL_BATCH_N, n_to_process - n_processed);
+      self.decompress(&mut scratch[..batch_n])?;
+      for (d, &x) in dst[n_processed..n_processed + batch_n]
+        .iter_mut()
+        .zip(&scratch[..batch_n])
+      {
+        *d = D::from(x);
+      }
+      n_processed += batch_n;
+    }
+
+    Ok(Progress {
+      n_processed,
+      finished: self.n_remaining() == 0,
+    })
+  }
+
+  /// Computes the aggregate over the numbers remaining in the page,
+  /// advancing to the end of the page without writing them all out.
+  ///
+  /// Numbers are decoded one batch at a time, except when the page metadata
+  /// shows the remaining numbers are all equal, in which case no decoding
+  /// is needed.
+  ///
+  /// Will return an error if corruptions or insufficient data are found.
+  pub fn aggregate(&mut self, agg: Agg) -> PcoResult<AggValue<T>> {
+    let mut aggregator = Aggregator::new(agg);
+
+    if let Some(value) = self.constant_value() {
+      let n_remaining = self.n_remaining();
+      if n_remaining > 0 {
+        aggregator.update_constant(value, n_remaining);
+        self.state.n_processed = self.n;
+        self.reader_builder.with_reader(|reader| {
+          reader.drain_empty_byte("expected trailing bits at end of page to be empty")
+        })?;
+      }
+      return Ok(aggregator.finish());
+    }
+
+    let mut scratch = [T::default(); FULL_BATCH_N];
+    while self.n_remaining() > 0 {
+      let progress = self.decompress(&mut scratch)?;
+      aggregator.update(&scratch[..progress.n_processed]);
+    }
+    Ok(aggregator.finish())
+  }
+
+  // Returns the value of every remaining number if it can be determined
+  // from metadata alone, in which case the page body contains no bits.
+  fn constant_value(&self) -> Option<T> {
+    let has_exceptions = self.state.exception_idx < self.exceptions.len();
+    if has_exceptions || self.state.delta_momentss[0].n_moments() > 0 {
+      return None;
+    }
+
+    let latent = self.state.latent_batch_decompressors[0].maybe_constant_value?;
+    match self.mode {
+      Mode::Classic => Some(T::from_latent_ordered(latent)),
+      Mode::Dictionary => self
+        .dictionary
+        .get(latent.to_u64() as usize)
+        .map(|&l| T::from_latent_ordered(l)),
+      _ => None,
+    }
+  }
+
+  /// Advances to the start of the given batch, so the next call to
+  /// [`decompress`][PageDecompressor::decompress] begins at number
+  /// `batch_idx * 256`.
+  ///
+  /// If the chunk has a seek index (see
+  /// [`ChunkConfig::seek_index_interval`][crate::ChunkConfig::seek_index_interval]),
+  /// this jumps to the nearest preceding entry without decoding the batches
+  /// before it.
+  /// Otherwise, it decodes and discards the batches in between.
+  ///
+  /// Will return an error if the batch is before the current position or
+  /// past the end of the page, or if corruptions or insufficient data are
+  /// found.
+  pub fn skip_to_batch(&mut self, batch_idx: usize) -> PcoResult<()> {
+    let n_batches = self.n.div_ceil(FULL_BATCH_N);
+    if batch_idx > n_batches {
+      return Err(PcoError::invalid_argument(format!(
+        "batch idx exceeds num batches ({} > {})",
+        batch_idx, n_batches,
+      )));
+    }
+    let target = min(batch_idx * FULL_BATCH_N, self.n);
+    if target < self.state.n_processed {
+      return Err(PcoError::invalid_argument(format!(
+        "unable to skip backward to number {} from {}",
+        target, self.state.n_processed,
+      )));
+    }
+
+    if let Some(interval) = self.seek_index_interval {
+      // seek point i is at the start of batch (i + 1) * interval
+      let n_usable_seek_points = min(batch_idx / interval, self.seek_points.len());
+      if n_usable_seek_points > 0 {
+        let seek_batch_idx = n_usable_seek_points * interval;
+        if seek_batch_idx * FULL_BATCH_N > self.state.n_processed {
+          self.seek(n_usable_seek_points - 1, seek_batch_idx)?;
+        }
+      }
+    }
+
+    let mut scratch = [T::default(); FULL_BATCH_N];
+    while self.state.n_processed < target {
+      let batch_n = min(FULL_BATCH_N, target - self.state.n_processed);
+      self.decompress_batch(&mut scratch[..batch_n])?;
+    }
+    let n_processed = self.state.n_processed;
+    self.state.exception_idx = self
+      .exceptions
+      .partition_point(|exception| exception.idx < n_processed);
+    Ok(())
+  }
+
+  fn seek(&mut self, seek_idx: usize, batch_idx: usize) -> PcoResult<()> {
+    let seek_point = &self.seek_points[seek_idx];
+    let bit_idx = self.reader_builder.bits_consumed() - self.body_start_bit_idx;
+    if seek_point.bit_idx < bit_idx {
+      return Err(PcoError::corruption(format!(
+        "seek index position {} is before the current position {}",
+        seek_point.bit_idx, bit_idx,
+      )));
+    }
+    self
+      .reader_builder
+      .skip_bits(seek_point.bit_idx - bit_idx)?;
+
+    for ((lbd, delta_moments), var_meta) in self
+      .state
+      .latent_batch_decompressors
+      .iter_mut()
+      .zip(self.state.delta_momentss.iter_mut())
+      .zip(&seek_point.per_var)
+    {
+      lbd.set_ans_state_idxs(var_meta.ans_final_state_idxs);
+      *delta_moments = var_meta.delta_moments.clone();
+    }
+    self.state.n_processed = batch_idx * FULL_BATCH_N;
+    Ok(())
+  }
+
+  fn patch_exceptions(&mut self, page_start: usize, num_dst: &mut [T]) {
+    let page_end = page_start + num_dst.len();
+    while let Some(exception) = self.exceptions.get(self.state.exception_idx) {
+      if exception.idx >= page_end {
+        break;
+      }
+      num_dst[exception.idx - page_start] = T::from_latent_ordered(exception.latent);
+      self.state.exception_idx += 1;
+    }
+  }
+
+  fn validate_dst_len(&self, dst_len: usize) -> PcoResult<()> {
+    if dst_len % FULL_BATCH_N != 0 && dst_len < self.n_remaining() {
+      return Err(PcoError::invalid_argument(format!(
+        "num_dst's length must either be a multiple of {} or be \
+         at least the count of numbers remaining ({} < {})",
+        FULL_BATCH_N,
+        dst_len,
+        self.n_remaining(),
+      )));
+    }
+    Ok(())
+  }
+
   fn n_remaining(&self) -> usize {
     self.n - self.state.n_processed
   }
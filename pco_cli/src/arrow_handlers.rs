@@ -26,6 +26,7 @@ pub fn from_dtype(dtype: &DataType) -> Result<Box<dyn ArrowHandler>> {
   }
 
   match_dtype!(
+    Float16 => Float16Type,
     Float32 => Float32Type,
     Float64 => Float64Type,
     Int16 => Int16Type,
@@ -38,6 +39,7 @@ pub fn from_dtype(dtype: &DataType) -> Result<Box<dyn ArrowHandler>> {
     Timestamp(TimeUnit::Millisecond, _) => TimestampMillisecondType,
     Timestamp(TimeUnit::Microsecond, _) => TimestampMicrosecondType,
     Timestamp(TimeUnit::Nanosecond, _) => TimestampNanosecondType,
+    Decimal128(_, 0) => Decimal128Type,
   )
 }
 
@@ -108,7 +108,7 @@ impl CodecInternal for ParquetConfig {
       let mut col_writer = row_group_writer.next_column().unwrap().unwrap();
       let typed = col_writer.typed::<T::Parquet>();
       typed
-        .write_batch(T::nums_to_parquet(col_chunk), None, None)
+        .write_batch(&T::nums_to_parquet(col_chunk), None, None)
         .unwrap();
       col_writer.close().unwrap();
       row_group_writer.close().unwrap();
@@ -129,10 +129,9 @@ impl CodecInternal for ParquetConfig {
       n += row_group_meta.num_rows();
     }
 
-    let mut res = Vec::with_capacity(n as usize);
-    unsafe {
-      res.set_len(n as usize);
-    }
+    // Parquet values may own heap data (e.g. fixed length byte arrays), so we
+    // can't leave them uninitialized.
+    let mut res = vec![Default::default(); n as usize];
     let mut start = 0;
     for i in 0..parquet_meta.num_row_groups() {
       let row_group_reader = reader.get_row_group(i).unwrap();
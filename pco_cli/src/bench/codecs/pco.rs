@@ -1,9 +1,10 @@
 use anyhow::{anyhow, Result};
 
-use pco::{FloatMultSpec, IntMultSpec, PagingSpec};
+use pco::{IntMultSpec, PagingSpec};
 
 use crate::bench::codecs::CodecInternal;
 use crate::dtypes::PcoNumberLike;
+use crate::parse;
 
 #[derive(Clone, Debug, Default)]
 pub struct PcoConfig {
@@ -29,6 +30,10 @@ impl CodecInternal for PcoConfig {
           .map(|order| order.to_string())
           .unwrap_or("auto".to_string()),
       ),
+      (
+        "delta_lag",
+        format!("{:?}", self.chunk_config.delta_lag_spec),
+      ),
       (
         "int_mult",
         format!("{:?}", self.chunk_config.int_mult_spec),
@@ -37,8 +42,35 @@ impl CodecInternal for PcoConfig {
         "float_mult",
         format!("{:?}", self.chunk_config.float_mult_spec),
       ),
+      (
+        "decimal",
+        format!("{:?}", self.chunk_config.decimal_spec),
+      ),
+      (
+        "dictionary",
+        format!("{:?}", self.chunk_config.dictionary_spec),
+      ),
+      (
+        "run_length",
+        format!("{:?}", self.chunk_config.run_length_spec),
+      ),
+      (
+        "lpc",
+        format!("{:?}", self.chunk_config.lpc_spec),
+      ),
+      (
+        "exceptions",
+        format!("{:?}", self.chunk_config.exception_spec),
+      ),
       (
         "chunk_n",
+        match self.chunk_config.chunking_spec {
+          PagingSpec::EqualPagesUpTo(chunk_size) => chunk_size.to_string(),
+          _ => panic!("unexpected chunking spec"),
+        },
+      ),
+      (
+        "page_n",
         match self.chunk_config.paging_spec {
           PagingSpec::EqualPagesUpTo(page_size) => page_size.to_string(),
           _ => panic!("unexpected paging spec"),
@@ -63,6 +95,9 @@ impl CodecInternal for PcoConfig {
           ));
         }
       }
+      "delta_lag" => {
+        self.chunk_config.delta_lag_spec = parse::delta_lag(&value)?;
+      }
       "int_mult" => {
         self.chunk_config.int_mult_spec = match value.as_str() {
           "enabled" => IntMultSpec::Enabled,
@@ -74,16 +109,27 @@ impl CodecInternal for PcoConfig {
         }
       }
       "float_mult" => {
-        self.chunk_config.float_mult_spec = match value.as_str() {
-          "enabled" => FloatMultSpec::Enabled,
-          "disabled" => FloatMultSpec::Disabled,
-          other => match other.parse::<f64>() {
-            Ok(mult) => FloatMultSpec::Provided(mult),
-            _ => return Err(anyhow!("cannot parse float mult: {}", other)),
-          },
-        }
+        self.chunk_config.float_mult_spec = parse::float_mult(&value)?;
+      }
+      "decimal" => {
+        self.chunk_config.decimal_spec = parse::decimal(&value)?;
+      }
+      "dictionary" => {
+        self.chunk_config.dictionary_spec = parse::dictionary(&value)?;
+      }
+      "run_length" => {
+        self.chunk_config.run_length_spec = parse::run_length(&value)?;
+      }
+      "lpc" => {
+        self.chunk_config.lpc_spec = parse::lpc(&value)?;
+      }
+      "exceptions" => {
+        self.chunk_config.exception_spec = parse::exceptions(&value)?;
       }
       "chunk_n" => {
+        self.chunk_config.chunking_spec = PagingSpec::EqualPagesUpTo(value.parse().unwrap())
+      }
+      "page_n" => {
         self.chunk_config.paging_spec = PagingSpec::EqualPagesUpTo(value.parse().unwrap())
       }
       _ => return Err(anyhow!("unknown conf: {}", key)),
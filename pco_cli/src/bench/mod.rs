@@ -1,6 +1,5 @@
 #![allow(clippy::uninit_vec)]
 
-use std::any::type_name;
 use std::collections::HashMap;
 use std::ops::AddAssign;
 use std::path::PathBuf;
@@ -20,7 +19,7 @@ use pco::with_core_dtypes;
 
 use crate::bench::codecs::CodecConfig;
 use crate::input::{Format, InputColumnOpt, InputFileOpt};
-use crate::{arrow_handlers, dtypes, input, parse};
+use crate::{arrow_handlers, dtypes, input, parse, utils};
 
 mod codecs;
 pub mod handler;
@@ -192,13 +191,12 @@ fn core_dtype_to_str(dtype: CoreDataType) -> String {
   macro_rules! to_str {
     {$($name:ident($lname:ident) => $t:ty,)+} => {
       match dtype {
-        $(CoreDataType::$name => type_name::<$t>(),)+
+        $(CoreDataType::$name => utils::dtype_name::<$t>(),)+
       }
     }
   }
 
-  let name = with_core_dtypes!(to_str);
-  name.to_string()
+  with_core_dtypes!(to_str)
 }
 
 fn handle_column(
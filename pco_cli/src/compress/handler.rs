@@ -5,7 +5,7 @@ use anyhow::Result;
 use arrow::datatypes::Schema;
 
 use pco::standalone::FileCompressor;
-use pco::ChunkConfig;
+use pco::{ChunkConfig, PagingSpec};
 
 use crate::arrow_handlers::ArrowHandlerImpl;
 use crate::compress::CompressOpt;
@@ -31,9 +31,18 @@ impl<P: ArrowNumberLike> CompressHandler for ArrowHandlerImpl<P> {
     let config = ChunkConfig::default()
       .with_compression_level(opt.level)
       .with_delta_encoding_order(opt.delta_encoding_order)
+      .with_delta_lag_spec(opt.delta_lag)
       .with_int_mult_spec(opt.int_mult)
-      .with_float_mult_spec(opt.float_mult);
-    let fc = FileCompressor::default();
+      .with_float_mult_spec(opt.float_mult)
+      .with_decimal_spec(opt.decimal)
+      .with_dictionary_spec(opt.dictionary)
+      .with_run_length_spec(opt.run_length)
+      .with_lpc_spec(opt.lpc)
+      .with_exception_spec(opt.exceptions)
+      .with_seek_index_interval(opt.seek_index_interval)
+      .with_value_bounds_spec(opt.value_bounds)
+      .with_paging_spec(PagingSpec::EqualPagesUpTo(opt.page_size));
+    let fc = FileCompressor::default().with_nullable(opt.nullable);
     fc.write_header(&file)?;
 
     let col_idx = utils::find_col_idx(
@@ -43,33 +52,50 @@ impl<P: ArrowNumberLike> CompressHandler for ArrowHandlerImpl<P> {
     )?;
     let reader = input::new_column_reader(schema, col_idx, &opt.input_file)?;
     let mut num_buffer = Vec::<P::Pco>::new();
+    let mut validity_buffer = Vec::<bool>::new();
 
-    let write_chunks = |num_buffer: &mut Vec<P::Pco>, finish: bool| -> Result<()> {
-      let n = num_buffer.len();
-      let n_chunks = if finish {
-        n.div_ceil(opt.chunk_size)
-      } else {
-        n / opt.chunk_size
+    let write_chunks =
+      |num_buffer: &mut Vec<P::Pco>, validity_buffer: &mut Vec<bool>, finish: bool| -> Result<()> {
+        let n = num_buffer.len();
+        let n_chunks = if finish {
+          n.div_ceil(opt.chunk_size)
+        } else {
+          n / opt.chunk_size
+        };
+        let mut start = 0;
+        let mut end = 0;
+        for _ in 0..n_chunks {
+          end = min(start + opt.chunk_size, num_buffer.len());
+          if opt.nullable {
+            fc.nullable_chunk_compressor(
+              &num_buffer[start..end],
+              &validity_buffer[start..end],
+              &config,
+            )?
+            .write_chunk(&file)?;
+          } else {
+            fc.chunk_compressor(&num_buffer[start..end], &config)?
+              .write_chunk(&file)?;
+          }
+          start = end;
+        }
+        num_buffer.drain(..end);
+        if opt.nullable {
+          validity_buffer.drain(..end);
+        }
+        Ok(())
       };
-      let mut start = 0;
-      let mut end = 0;
-      for _ in 0..n_chunks {
-        end = min(start + opt.chunk_size, num_buffer.len());
-        fc.chunk_compressor(&num_buffer[start..end], &config)?
-          .write_chunk(&file)?;
-        start = end;
-      }
-      num_buffer.drain(..end);
-      Ok(())
-    };
 
     for array_result in reader {
       let array = array_result?;
+      if opt.nullable {
+        validity_buffer.extend(utils::arrow_to_validity(&array));
+      }
       num_buffer.extend(utils::arrow_to_nums::<P>(array));
-      write_chunks(&mut num_buffer, false)?;
+      write_chunks(&mut num_buffer, &mut validity_buffer, false)?;
     }
 
-    write_chunks(&mut num_buffer, true)?;
+    write_chunks(&mut num_buffer, &mut validity_buffer, true)?;
 
     fc.write_footer(&file)?;
     Ok(())
@@ -3,7 +3,10 @@ use std::path::PathBuf;
 use anyhow::Result;
 use clap::Parser;
 
-use pco::{FloatMultSpec, IntMultSpec};
+use pco::{
+  DecimalSpec, DeltaLagSpec, DictionarySpec, ExceptionSpec, FloatMultSpec, IntMultSpec, LpcSpec,
+  RunLengthSpec, ValueBoundsSpec,
+};
 
 use crate::input::{InputColumnOpt, InputFileOpt};
 use crate::{arrow_handlers, input};
@@ -20,16 +23,56 @@ pub struct CompressOpt {
   /// If specified, uses a fixed delta encoding order. Defaults to automatic detection.
   #[arg(long = "delta-order")]
   pub delta_encoding_order: Option<usize>,
+  /// Can be "Enabled", "Disabled", or a fixed lag to use for delta encoding
+  /// (e.g. 96 for a daily period in 15-minute samples).
+  #[arg(long, default_value = "Enabled", value_parser = parse::delta_lag)]
+  pub delta_lag: DeltaLagSpec,
   /// Can be "Enabled", "Disabled", or a fixed integer to use as the base in
   /// int mult mode.
   #[arg(long, default_value = "Enabled", value_parser = parse::int_mult)]
   pub int_mult: IntMultSpec,
   /// Can be "Enabled", "Disabled", or a fixed float to use as the base in
   /// float mult mode.
+  /// Can also be "LossyEnabled" or "Lossy" followed by a fixed float base
+  /// (e.g. "Lossy0.01") to drop the ULPs adjustments, rounding each float to
+  /// the nearest multiple of the base.
   #[arg(long, default_value = "Enabled", value_parser = parse::float_mult)]
   pub float_mult: FloatMultSpec,
-  #[arg(long, default_value_t=pco::DEFAULT_MAX_PAGE_N)]
+  /// Can be "Enabled" or "Disabled".
+  #[arg(long, default_value = "Enabled", value_parser = parse::decimal)]
+  pub decimal: DecimalSpec,
+  /// Can be "Enabled" or "Disabled".
+  #[arg(long, default_value = "Enabled", value_parser = parse::dictionary)]
+  pub dictionary: DictionarySpec,
+  /// Can be "Enabled" or "Disabled".
+  #[arg(long, default_value = "Enabled", value_parser = parse::run_length)]
+  pub run_length: RunLengthSpec,
+  /// Can be "Enabled" or "Disabled".
+  #[arg(long, default_value = "Enabled", value_parser = parse::lpc)]
+  pub lpc: LpcSpec,
+  /// Can be "Enabled" or "Disabled".
+  #[arg(long, default_value = "Enabled", value_parser = parse::exceptions)]
+  pub exceptions: ExceptionSpec,
+  /// If specified, stores a seek index in each page with an entry every
+  /// this many batches of 256 numbers, so readers can skip ahead within a
+  /// page.
+  #[arg(long)]
+  pub seek_index_interval: Option<usize>,
+  /// Can be "Enabled" or "Disabled". If enabled, stores each chunk's
+  /// minimum and maximum number in its metadata.
+  #[arg(long, default_value = "Disabled", value_parser = parse::value_bounds)]
+  pub value_bounds: ValueBoundsSpec,
+  /// Maximum count of numbers in each chunk.
+  #[arg(long, default_value_t=pco::DEFAULT_MAX_CHUNK_N)]
   pub chunk_size: usize,
+  /// Maximum count of numbers in each page within a chunk.
+  #[arg(long, default_value_t=pco::DEFAULT_MAX_PAGE_N)]
+  pub page_size: usize,
+  /// Store the column's validity alongside its numbers so that nulls
+  /// round-trip.
+  /// The resulting file must be decompressed with --nullable.
+  #[arg(long)]
+  pub nullable: bool,
   /// Overwrite the output path (if it exists) instead of failing.
   #[arg(long)]
   pub overwrite: bool,
@@ -38,19 +38,35 @@ impl<T: PcoNumberLike> DecompressHandler for CoreHandlerImpl<T> {
         break;
       }
 
-      if let MaybeChunkDecompressor::Some(mut cd) = fd.chunk_decompressor::<T, _>(src)? {
+      if opt.nullable {
+        let (maybe_chunk, rest) = fd.decompress_nullable_chunk::<T, _>(src)?;
+        src = rest;
+        let Some(chunk) = maybe_chunk else {
+          break;
+        };
+        let batch_size = min(chunk.nums.len(), remaining_limit);
+        let arrow_nums = chunk
+          .nums
+          .iter()
+          .zip(&chunk.validity)
+          .take(batch_size)
+          .map(|(&x, &is_valid)| is_valid.then(|| T::to_arrow_native(x)).transpose())
+          .collect::<Result<Vec<_>>>()?;
+        writer.write(arrow_nums)?;
+        remaining_limit -= batch_size;
+      } else if let MaybeChunkDecompressor::Some(mut cd) = fd.chunk_decompressor::<T, _>(src)? {
         let n = cd.n();
         let batch_size = min(n, remaining_limit);
         // how many pco should decompress
         let pco_size = (1 + batch_size / FULL_BATCH_N) * FULL_BATCH_N;
         nums.resize(pco_size, T::default());
         let _ = cd.decompress(&mut nums)?;
-        src = cd.into_src();
+        src = cd.into_src()?;
         let arrow_nums = nums
           .iter()
           .take(batch_size)
-          .map(|&x| T::to_arrow_native(x))
-          .collect::<Vec<_>>();
+          .map(|&x| T::to_arrow_native(x).map(Some))
+          .collect::<Result<Vec<_>>>()?;
         writer.write(arrow_nums)?;
         remaining_limit -= batch_size;
       } else {
@@ -72,8 +88,11 @@ fn new_column_writer<T: PcoNumberLike>(opt: &DecompressOpt) -> Result<Box<dyn Co
   Ok(writer)
 }
 
+// nulls are None
+type ArrowNatives<T> = Vec<Option<<<T as PcoNumberLike>::Arrow as ArrowPrimitiveType>::Native>>;
+
 trait ColumnWriter<T: PcoNumberLike> {
-  fn write(&mut self, nums: Vec<<T::Arrow as ArrowPrimitiveType>::Native>) -> Result<()>;
+  fn write(&mut self, nums: ArrowNatives<T>) -> Result<()>;
   fn close(&mut self) -> Result<()>;
 }
 
@@ -83,9 +102,9 @@ struct TxtWriter<T: PcoNumberLike> {
 }
 
 impl<T: PcoNumberLike> ColumnWriter<T> for TxtWriter<T> {
-  fn write(&mut self, arrow_natives: Vec<<T::Arrow as ArrowPrimitiveType>::Native>) -> Result<()> {
-    let schema = Schema::new(vec![Field::new("c0", T::ARROW_DTYPE, false)]);
-    let c0 = PrimitiveArray::<T::Arrow>::from_iter_values(arrow_natives);
+  fn write(&mut self, arrow_natives: ArrowNatives<T>) -> Result<()> {
+    let schema = Schema::new(vec![Field::new("c0", T::ARROW_DTYPE, true)]);
+    let c0 = PrimitiveArray::<T::Arrow>::from_iter(arrow_natives).with_data_type(T::ARROW_DTYPE);
     let batch = RecordBatch::try_new(Arc::new(schema), vec![Arc::new(c0)])?;
     let mut stdout_bytes = Vec::<u8>::new();
     {
@@ -109,10 +128,12 @@ struct BinaryWriter<T: PcoNumberLike> {
 }
 
 impl<T: PcoNumberLike> ColumnWriter<T> for BinaryWriter<T> {
-  fn write(&mut self, arrow_natives: Vec<<T::Arrow as ArrowPrimitiveType>::Native>) -> Result<()> {
+  fn write(&mut self, arrow_natives: ArrowNatives<T>) -> Result<()> {
     let mut out = std::io::stdout();
     for &x in &arrow_natives {
-      out.write_all(&T::arrow_native_to_bytes(x))?;
+      out.write_all(&T::arrow_native_to_bytes(
+        x.unwrap_or_default(),
+      ))?;
     }
     Ok(())
   }
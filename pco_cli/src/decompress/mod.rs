@@ -22,6 +22,11 @@ pub struct DecompressOpt {
   pub limit: Option<usize>,
   #[arg(short, long, default_value = "txt")]
   pub output: OutputKind,
+  /// Decompress a file written with compress --nullable.
+  /// Nulls are written as empty values for txt output and as 0s for binary
+  /// output.
+  #[arg(long)]
+  pub nullable: bool,
 
   pub path: PathBuf,
 }
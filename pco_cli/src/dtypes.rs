@@ -1,10 +1,13 @@
+use std::borrow::Cow;
 use std::mem;
 
 use anyhow::anyhow;
 use anyhow::Result;
 use arrow::datatypes as arrow_dtypes;
-use arrow::datatypes::{ArrowPrimitiveType, DataType};
-use arrow::datatypes::{DataType as ArrowDataType, Int16Type, UInt16Type};
+use arrow::datatypes::DataType as ArrowDataType;
+use arrow::datatypes::{ArrowPrimitiveType, DataType, DECIMAL128_MAX_PRECISION};
+use half::f16;
+use parquet::data_type::FixedLenByteArray;
 
 use pco::data_types::{CoreDataType, NumberLike};
 
@@ -15,7 +18,9 @@ pub trait Parquetable: Sized {
 
   type Parquet: parquet::data_type::DataType;
 
-  fn nums_to_parquet(nums: &[Self]) -> &[<Self::Parquet as parquet::data_type::DataType>::T];
+  fn nums_to_parquet(
+    nums: &[Self],
+  ) -> Cow<'_, [<Self::Parquet as parquet::data_type::DataType>::T]>;
   fn parquet_to_nums(vec: Vec<<Self::Parquet as parquet::data_type::DataType>::T>) -> Vec<Self>;
 }
 
@@ -33,7 +38,7 @@ pub trait PcoNumberLike: NumberLike + Parquetable + QCompressable {
 
   type Arrow: ArrowPrimitiveType;
 
-  fn to_arrow_native(self) -> <Self::Arrow as ArrowPrimitiveType>::Native;
+  fn to_arrow_native(self) -> Result<<Self::Arrow as ArrowPrimitiveType>::Native>;
   fn make_num_vec(nums: Vec<Self>) -> NumVec;
   fn arrow_native_to_bytes(x: <Self::Arrow as ArrowPrimitiveType>::Native) -> Vec<u8>;
 }
@@ -44,7 +49,7 @@ pub trait PcoNumberLike: NumberLike + Parquetable {
 
   type Arrow: ArrowPrimitiveType;
 
-  fn to_arrow_native(self) -> <Self::Arrow as ArrowPrimitiveType>::Native;
+  fn to_arrow_native(self) -> Result<<Self::Arrow as ArrowPrimitiveType>::Native>;
   fn make_num_vec(nums: Vec<Self>) -> NumVec;
   fn arrow_native_to_bytes(x: <Self::Arrow as ArrowPrimitiveType>::Native) -> Vec<u8>;
 }
@@ -64,8 +69,10 @@ macro_rules! parquetable {
 
       type Parquet = $parq;
 
-      fn nums_to_parquet(nums: &[Self]) -> &[<Self::Parquet as parquet::data_type::DataType>::T] {
-        nums
+      fn nums_to_parquet(
+        nums: &[Self],
+      ) -> Cow<'_, [<Self::Parquet as parquet::data_type::DataType>::T]> {
+        Cow::Borrowed(nums)
       }
       fn parquet_to_nums(
         vec: Vec<<Self::Parquet as parquet::data_type::DataType>::T>,
@@ -76,8 +83,31 @@ macro_rules! parquetable {
   };
 }
 
-macro_rules! trivial {
-  ($t: ty, $name: ident, $p: ty) => {
+// Parquet doesn't have 16-bit physical types, so we widen these to 32 bits,
+// just like Parquet's own INT(16, _) logical types do.
+macro_rules! widened_parquetable {
+  ($t: ty, $parq: ty, $parq_str: expr) => {
+    impl Parquetable for $t {
+      const PARQUET_DTYPE_STR: &'static str = $parq_str;
+
+      type Parquet = $parq;
+
+      fn nums_to_parquet(
+        nums: &[Self],
+      ) -> Cow<'_, [<Self::Parquet as parquet::data_type::DataType>::T]> {
+        Cow::Owned(nums.iter().map(|&x| x as i32).collect())
+      }
+      fn parquet_to_nums(
+        vec: Vec<<Self::Parquet as parquet::data_type::DataType>::T>,
+      ) -> Vec<Self> {
+        vec.into_iter().map(|x| x as Self).collect()
+      }
+    }
+  };
+}
+
+macro_rules! qcompressable {
+  ($t: ty) => {
     #[cfg(feature = "full_bench")]
     impl QCompressable for $t {
       type Qco = $t;
@@ -89,14 +119,18 @@ macro_rules! trivial {
         vec
       }
     }
+  };
+}
 
+macro_rules! trivial {
+  ($t: ty, $name: ident, $p: ty) => {
     impl PcoNumberLike for $t {
       const ARROW_DTYPE: DataType = <$p as ArrowPrimitiveType>::DATA_TYPE;
 
       type Arrow = $p;
 
-      fn to_arrow_native(self) -> <Self::Arrow as ArrowPrimitiveType>::Native {
-        self as Self
+      fn to_arrow_native(self) -> Result<<Self::Arrow as ArrowPrimitiveType>::Native> {
+        Ok(self)
       }
 
       fn make_num_vec(nums: Vec<Self>) -> NumVec {
@@ -122,6 +156,68 @@ macro_rules! trivial {
   };
 }
 
+// Arrow and Parquet only have signed 128-bit decimals, so we represent
+// 128-bit integers as decimals with scale 0. Only scale 0 is supported, since
+// pco doesn't store the scale, and u128s that don't fit in an i128 can't be
+// converted to arrow.
+const DECIMAL128_DTYPE: DataType = DataType::Decimal128(DECIMAL128_MAX_PRECISION, 0);
+
+fn u128_to_decimal(x: u128) -> Result<i128> {
+  i128::try_from(x).map_err(|_| {
+    anyhow!(
+      "u128 value {} is too large for an arrow decimal",
+      x
+    )
+  })
+}
+
+macro_rules! decimal {
+  ($t: ty, $name: ident, $to_native: path) => {
+    impl Parquetable for $t {
+      const PARQUET_DTYPE_STR: &'static str = "FIXED_LEN_BYTE_ARRAY(16)";
+
+      type Parquet = parquet::data_type::FixedLenByteArrayType;
+
+      fn nums_to_parquet(
+        nums: &[Self],
+      ) -> Cow<'_, [<Self::Parquet as parquet::data_type::DataType>::T]> {
+        Cow::Owned(
+          nums
+            .iter()
+            .map(|x| FixedLenByteArray::from(x.to_be_bytes().to_vec()))
+            .collect(),
+        )
+      }
+      fn parquet_to_nums(
+        vec: Vec<<Self::Parquet as parquet::data_type::DataType>::T>,
+      ) -> Vec<Self> {
+        vec
+          .into_iter()
+          .map(|x| Self::from_be_bytes(x.data().try_into().unwrap()))
+          .collect()
+      }
+    }
+
+    impl PcoNumberLike for $t {
+      const ARROW_DTYPE: DataType = DECIMAL128_DTYPE;
+
+      type Arrow = arrow_dtypes::Decimal128Type;
+
+      fn to_arrow_native(self) -> Result<<Self::Arrow as ArrowPrimitiveType>::Native> {
+        $to_native(self)
+      }
+
+      fn make_num_vec(nums: Vec<Self>) -> NumVec {
+        NumVec::$name(nums)
+      }
+
+      fn arrow_native_to_bytes(x: <Self::Arrow as ArrowPrimitiveType>::Native) -> Vec<u8> {
+        x.to_le_bytes().to_vec()
+      }
+    }
+  };
+}
+
 macro_rules! extra_arrow {
   ($t: ty, $p: ty) => {
     impl ArrowNumberLike for $p {
@@ -142,6 +238,24 @@ parquetable!(f32, parquet::data_type::FloatType, "FLOAT");
 parquetable!(f64, parquet::data_type::DoubleType, "DOUBLE");
 parquetable!(i32, parquet::data_type::Int32Type, "INT32");
 parquetable!(i64, parquet::data_type::Int64Type, "INT64");
+widened_parquetable!(i16, parquet::data_type::Int32Type, "INT32");
+widened_parquetable!(u16, parquet::data_type::Int32Type, "INT32");
+
+impl Parquetable for f16 {
+  const PARQUET_DTYPE_STR: &'static str = "FLOAT";
+  type Parquet = parquet::data_type::FloatType;
+
+  // Parquet doesn't have a 16-bit float physical type, so we losslessly widen
+  // to f32.
+  fn nums_to_parquet(
+    nums: &[Self],
+  ) -> Cow<'_, [<Self::Parquet as parquet::data_type::DataType>::T]> {
+    Cow::Owned(nums.iter().map(|x| x.to_f32()).collect())
+  }
+  fn parquet_to_nums(vec: Vec<<Self::Parquet as parquet::data_type::DataType>::T>) -> Vec<Self> {
+    vec.into_iter().map(f16::from_f32).collect()
+  }
+}
 
 impl Parquetable for u32 {
   const PARQUET_DTYPE_STR: &'static str = "INT32";
@@ -149,8 +263,10 @@ impl Parquetable for u32 {
 
   // Parquet doesn't have unsigned integer types, so the best zero-copy thing
   // we can do is transmute to signed ones.
-  fn nums_to_parquet(nums: &[Self]) -> &[<Self::Parquet as parquet::data_type::DataType>::T] {
-    unsafe { mem::transmute(nums) }
+  fn nums_to_parquet(
+    nums: &[Self],
+  ) -> Cow<'_, [<Self::Parquet as parquet::data_type::DataType>::T]> {
+    Cow::Borrowed(unsafe { mem::transmute::<&[u32], &[i32]>(nums) })
   }
   fn parquet_to_nums(vec: Vec<<Self::Parquet as parquet::data_type::DataType>::T>) -> Vec<Self> {
     unsafe { mem::transmute(vec) }
@@ -163,60 +279,78 @@ impl Parquetable for u64 {
 
   // Parquet doesn't have unsigned integer types, so the best zero-copy thing
   // we can do is transmute to signed ones.
-  fn nums_to_parquet(nums: &[Self]) -> &[<Self::Parquet as parquet::data_type::DataType>::T] {
-    unsafe { mem::transmute(nums) }
+  fn nums_to_parquet(
+    nums: &[Self],
+  ) -> Cow<'_, [<Self::Parquet as parquet::data_type::DataType>::T]> {
+    Cow::Borrowed(unsafe { mem::transmute::<&[u64], &[i64]>(nums) })
   }
   fn parquet_to_nums(vec: Vec<<Self::Parquet as parquet::data_type::DataType>::T>) -> Vec<Self> {
     unsafe { mem::transmute(vec) }
   }
 }
 
+qcompressable!(f32);
+qcompressable!(f64);
+qcompressable!(i16);
+qcompressable!(i32);
+qcompressable!(i64);
+qcompressable!(i128);
+qcompressable!(u16);
+qcompressable!(u32);
+qcompressable!(u64);
+qcompressable!(u128);
+
+// q_compress doesn't support f16, so we benchmark it on the bits instead.
+#[cfg(feature = "full_bench")]
+impl QCompressable for f16 {
+  type Qco = u16;
+
+  fn nums_to_qco(nums: &[Self]) -> &[Self::Qco] {
+    unsafe { mem::transmute(nums) }
+  }
+  fn qco_to_nums(vec: Vec<Self::Qco>) -> Vec<Self> {
+    unsafe { mem::transmute(vec) }
+  }
+}
+
+trivial!(f16, F16, arrow_dtypes::Float16Type);
 trivial!(f32, F32, arrow_dtypes::Float32Type);
 trivial!(f64, F64, arrow_dtypes::Float64Type);
+trivial!(i16, I16, arrow_dtypes::Int16Type);
 trivial!(i32, I32, arrow_dtypes::Int32Type);
 trivial!(i64, I64, arrow_dtypes::Int64Type);
+trivial!(u16, U16, arrow_dtypes::UInt16Type);
 trivial!(u32, U32, arrow_dtypes::UInt32Type);
 trivial!(u64, U64, arrow_dtypes::UInt64Type);
 
-impl ArrowNumberLike for Int16Type {
-  type Pco = i32;
-
-  fn native_to_pco(native: Self::Native) -> Self::Pco {
-    native as Self::Pco
-  }
-
-  fn native_vec_to_pco(native: Vec<Self::Native>) -> Vec<Self::Pco> {
-    native.into_iter().map(|x| x as Self::Pco).collect()
-  }
-}
-impl ArrowNumberLike for UInt16Type {
-  type Pco = u32;
-
-  fn native_to_pco(native: Self::Native) -> Self::Pco {
-    native as Self::Pco
-  }
-
-  fn native_vec_to_pco(native: Vec<Self::Native>) -> Vec<Self::Pco> {
-    native.into_iter().map(|x| x as Self::Pco).collect()
-  }
-}
+decimal!(i128, I128, Ok);
+decimal!(u128, U128, u128_to_decimal);
 
 extra_arrow!(i64, arrow_dtypes::TimestampSecondType);
 extra_arrow!(i64, arrow_dtypes::TimestampMillisecondType);
 extra_arrow!(i64, arrow_dtypes::TimestampMicrosecondType);
 extra_arrow!(i64, arrow_dtypes::TimestampNanosecondType);
+extra_arrow!(i128, arrow_dtypes::Decimal128Type);
 
 pub fn from_arrow(arrow_dtype: &ArrowDataType) -> Result<CoreDataType> {
   let res = match arrow_dtype {
+    ArrowDataType::Float16 => CoreDataType::F16,
     ArrowDataType::Float32 => CoreDataType::F32,
     ArrowDataType::Float64 => CoreDataType::F64,
-    ArrowDataType::Int16 => CoreDataType::I32,
+    ArrowDataType::Int16 => CoreDataType::I16,
     ArrowDataType::Int32 => CoreDataType::I32,
     ArrowDataType::Int64 => CoreDataType::I64,
-    ArrowDataType::UInt16 => CoreDataType::U32,
+    ArrowDataType::UInt16 => CoreDataType::U16,
     ArrowDataType::UInt32 => CoreDataType::U32,
     ArrowDataType::UInt64 => CoreDataType::U64,
     ArrowDataType::Timestamp(_, _) => CoreDataType::I64,
+    ArrowDataType::Decimal128(_, 0) => CoreDataType::I128,
+    ArrowDataType::Decimal128(_, scale) => {
+      return Err(anyhow!(
+        "unable to convert arrow decimal with non-zero scale {} to pco",
+        scale
+      ))
+    }
     _ => {
       return Err(anyhow!(
         "unable to convert arrow dtype {:?} to pco",
@@ -229,11 +363,15 @@ pub fn from_arrow(arrow_dtype: &ArrowDataType) -> Result<CoreDataType> {
 
 pub fn to_arrow(dtype: CoreDataType) -> ArrowDataType {
   match dtype {
+    CoreDataType::F16 => ArrowDataType::Float16,
     CoreDataType::F32 => ArrowDataType::Float32,
     CoreDataType::F64 => ArrowDataType::Float64,
+    CoreDataType::I16 => ArrowDataType::Int16,
     CoreDataType::I32 => ArrowDataType::Int32,
     CoreDataType::I64 => ArrowDataType::Int64,
+    CoreDataType::U16 => ArrowDataType::UInt16,
     CoreDataType::U32 => ArrowDataType::UInt32,
     CoreDataType::U64 => ArrowDataType::UInt64,
+    CoreDataType::I128 | CoreDataType::U128 => DECIMAL128_DTYPE,
   }
 }
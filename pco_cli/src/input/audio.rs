@@ -3,7 +3,7 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
-use arrow::array::{ArrayRef, Float32Array, Int32Array};
+use arrow::array::{ArrayRef, Float32Array, Int16Array, Int32Array};
 use arrow::datatypes::{DataType, Field, Schema};
 use wav::BitDepth;
 
@@ -12,7 +12,8 @@ pub fn get_wav_field(path: &Path) -> Result<Option<Field>> {
   let mut file = File::open(path)?;
   let (header, _) = wav::read(&mut file)?;
   let dtype = match header.bytes_per_sample {
-    1 | 2 | 3 => Ok(DataType::Int32),
+    1 | 2 => Ok(DataType::Int16),
+    3 => Ok(DataType::Int32),
     4 => Ok(DataType::Float32),
     _ => Err(anyhow!(
       "invalid number of bytes per wav file sample"
@@ -44,12 +45,12 @@ impl WavColumnReader {
   }
 }
 
-fn i32s_from_u8s(u8s: Vec<u8>) -> Vec<i32> {
-  u8s.into_iter().map(|x| x as i32).collect()
+fn i16s_from_u8s(u8s: Vec<u8>) -> Vec<i16> {
+  u8s.into_iter().map(|x| x as i16).collect()
 }
 
-fn i32s_from_i16s(i16s: Vec<i16>) -> Vec<i32> {
-  i16s.into_iter().map(|x| x as i32).collect()
+fn array_from_i16s(i16s: Vec<i16>) -> ArrayRef {
+  Arc::new(Int16Array::from(i16s))
 }
 
 fn array_from_i32s(i32s: Vec<i32>) -> ArrayRef {
@@ -66,22 +67,17 @@ impl WavColumnReader {
     let (_, data) = wav::read(&mut inp_file)?;
     let array = match data {
       BitDepth::Eight(u8s) => {
-        let i32s = i32s_from_u8s(u8s);
-        array_from_i32s(i32s)
-      }
-      BitDepth::Sixteen(i16s) => {
-        let i32s = i32s_from_i16s(i16s);
-        array_from_i32s(i32s)
+        let i16s = i16s_from_u8s(u8s);
+        array_from_i16s(i16s)
       }
+      BitDepth::Sixteen(i16s) => array_from_i16s(i16s),
       BitDepth::TwentyFour(i32s) => array_from_i32s(i32s),
       BitDepth::ThirtyTwoFloat(f32s) => array_from_f32s(f32s),
-      BitDepth::Empty => {
-        if self.dtype == DataType::Int32 {
-          array_from_i32s(vec![])
-        } else {
-          array_from_f32s(vec![])
-        }
-      }
+      BitDepth::Empty => match self.dtype {
+        DataType::Int16 => array_from_i16s(vec![]),
+        DataType::Int32 => array_from_i32s(vec![]),
+        _ => array_from_f32s(vec![]),
+      },
     };
     Ok(array)
   }
@@ -6,7 +6,8 @@ use std::sync::Arc;
 
 use anyhow::{anyhow, Context, Result};
 use arrow::array::{
-  ArrayData, ArrayRef, Float32Array, Float64Array, Int32Array, Int64Array, UInt32Array, UInt64Array,
+  ArrayData, ArrayRef, Decimal128Array, Float16Array, Float32Array, Float64Array, Int16Array,
+  Int32Array, Int64Array, UInt16Array, UInt32Array, UInt64Array,
 };
 use arrow::buffer::Buffer;
 use arrow::csv;
@@ -14,12 +15,14 @@ use arrow::csv::Reader as CsvReader;
 use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
 use arrow::record_batch::RecordBatchReader;
 use clap::Parser;
+use half::f16;
 use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
 use parquet::arrow::ProjectionMask;
 
 use pco::data_types::CoreDataType;
 use pco::standalone::simple_decompress;
 
+use crate::dtypes::PcoNumberLike;
 use crate::{dtypes, parse, utils};
 
 #[cfg(feature = "audio")]
@@ -176,8 +179,14 @@ fn infer_csv_schema(col_opt: &InputColumnOpt, file_opt: &InputFileOpt) -> Result
   let mut fields = Vec::new();
   for (col_idx, field) in inferred_schema.fields().iter().enumerate() {
     let new_field = match (&col_opt.col_name, &col_opt.col_idx) {
-      (Some(name), None) if name == field.name() => Field::new(name, dtype.clone(), false),
-      (None, Some(idx)) if *idx == col_idx => Field::new(field.name(), dtype.clone(), false),
+      (Some(name), None) if name == field.name() => {
+        Field::new(name, dtype.clone(), field.is_nullable())
+      }
+      (None, Some(idx)) if *idx == col_idx => Field::new(
+        field.name(),
+        dtype.clone(),
+        field.is_nullable(),
+      ),
       _ => field.as_ref().clone(),
     };
     fields.push(new_field);
@@ -412,24 +421,46 @@ impl PcoColumnReader {
 
     let compressed = fs::read(&self.col_path)?;
     let array: ArrayRef = match self.dtype {
+      F16 => Arc::new(Float16Array::from(simple_decompress::<f16>(
+        &compressed,
+      )?)),
       F32 => Arc::new(Float32Array::from(simple_decompress::<f32>(
         &compressed,
       )?)),
       F64 => Arc::new(Float64Array::from(simple_decompress::<f64>(
         &compressed,
       )?)),
+      I16 => Arc::new(Int16Array::from(simple_decompress::<i16>(
+        &compressed,
+      )?)),
       I32 => Arc::new(Int32Array::from(simple_decompress::<i32>(
         &compressed,
       )?)),
       I64 => Arc::new(Int64Array::from(simple_decompress::<i64>(
         &compressed,
       )?)),
+      U16 => Arc::new(UInt16Array::from(simple_decompress::<u16>(
+        &compressed,
+      )?)),
       U32 => Arc::new(UInt32Array::from(simple_decompress::<u32>(
         &compressed,
       )?)),
       U64 => Arc::new(UInt64Array::from(simple_decompress::<u64>(
         &compressed,
       )?)),
+      I128 => Arc::new(
+        Decimal128Array::from(simple_decompress::<i128>(&compressed)?)
+          .with_data_type(dtypes::to_arrow(I128)),
+      ),
+      U128 => Arc::new(
+        Decimal128Array::from(
+          simple_decompress::<u128>(&compressed)?
+            .into_iter()
+            .map(u128::to_arrow_native)
+            .collect::<Result<Vec<_>>>()?,
+        )
+        .with_data_type(dtypes::to_arrow(U128)),
+      ),
     };
     Ok(array)
   }
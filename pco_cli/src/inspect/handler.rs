@@ -50,7 +50,13 @@ pub struct ChunkSummary {
   n: usize,
   mode: String,
   delta_order: usize,
+  delta_lag: usize,
+  has_exceptions: bool,
+  seek_index_interval: Option<usize>,
+  value_min: Option<String>,
+  value_max: Option<String>,
   // using BTreeMaps to preserve ordering
+  approx_quantiles: Option<BTreeMap<String, String>>,
   latent_vars: BTreeMap<String, LatentVarSummary>,
 }
 
@@ -63,6 +69,7 @@ pub struct Output {
   pub n_chunks: usize,
   pub uncompressed_size: usize,
   pub compressed: CompressionSummary,
+  pub metadata: Option<BTreeMap<String, String>>,
   pub chunks: Vec<ChunkSummary>,
 }
 
@@ -84,6 +91,22 @@ fn build_latent_var_summary<T: NumberLike>(
       T::latent_to_string(base_latent, Mode::Classic, 0, 0)
     ),
     (Mode::FloatMult(_), 1) => "ULPs adjustment".to_string(),
+    (Mode::LossyFloatMult(base_latent), 0) => format!(
+      "lossy multiplier [x{}]",
+      T::latent_to_string(base_latent, Mode::Classic, 0, 0)
+    ),
+    (Mode::Decimal(exponent), 0) => format!("decimal [x10^-{}]", exponent),
+    (Mode::Decimal(_), 1) => "ULPs adjustment".to_string(),
+    (Mode::Dictionary, 0) => format!(
+      "dictionary index [{} entries]",
+      meta.dictionary.len()
+    ),
+    (Mode::RunLength, 0) => "run value".to_string(),
+    (Mode::RunLength, 1) => "run length".to_string(),
+    (Mode::Lpc, 0) => format!(
+      "prediction residual [coefficients {:?}]",
+      meta.lpc_coefficients
+    ),
     (Mode::IntMult(base), 0) => format!("multiplier [x{}]", base),
     (Mode::IntMult(_), 1) => "adjustment".to_string(),
     _ => panic!(
@@ -125,6 +148,7 @@ impl<T: PcoNumberLike> InspectHandler for CoreHandlerImpl<T> {
     let prev_src_len = &mut prev_src_len_val;
     let (fd, mut src) = FileDecompressor::new(src)?;
     let header_size = measure_bytes_read(src, prev_src_len);
+    let after_header = src;
 
     let mut meta_size = 0;
     let mut page_size = 0;
@@ -139,7 +163,7 @@ impl<T: PcoNumberLike> InspectHandler for CoreHandlerImpl<T> {
         MaybeChunkDecompressor::Some(cd) => {
           chunk_ns.push(cd.n());
           metas.push(cd.meta().clone());
-          meta_size += measure_bytes_read(cd.into_src(), prev_src_len);
+          meta_size += measure_bytes_read(cd.into_src()?, prev_src_len);
         }
         MaybeChunkDecompressor::EndOfData(rest) => {
           src = rest;
@@ -152,7 +176,7 @@ impl<T: PcoNumberLike> InspectHandler for CoreHandlerImpl<T> {
         MaybeChunkDecompressor::Some(mut cd) => {
           void.resize(cd.n(), T::default());
           let _ = cd.decompress(&mut void)?;
-          src = cd.into_src();
+          src = cd.into_src()?;
           page_size += measure_bytes_read(src, prev_src_len);
         }
         _ => panic!("unreachable"),
@@ -163,6 +187,11 @@ impl<T: PcoNumberLike> InspectHandler for CoreHandlerImpl<T> {
     let uncompressed_size = <T as NumberLike>::L::BITS as usize / 8 * n;
     let compressed_size = header_size + meta_size + page_size + footer_size;
     let unknown_trailing_bytes = src.len();
+    let metadata = if unknown_trailing_bytes == 0 {
+      fd.metadata(after_header)?
+    } else {
+      None
+    };
 
     let mut chunks = Vec::new();
     for (idx, meta) in metas.iter().enumerate() {
@@ -171,11 +200,24 @@ impl<T: PcoNumberLike> InspectHandler for CoreHandlerImpl<T> {
         let (name, summary) = build_latent_var_summary::<T>(latent_var_idx, meta, latent_var_meta);
         latent_vars.insert(name, summary);
       }
+      let value_bounds = meta.value_bounds::<T>();
+      let mut approx_quantiles = BTreeMap::new();
+      for &q in &opt.quantiles {
+        if let Some(quantile) = meta.approx_quantile::<T>(q)? {
+          approx_quantiles.insert(q.to_string(), quantile.to_string());
+        }
+      }
       chunks.push(ChunkSummary {
         idx,
         n: chunk_ns[idx],
         mode: format!("{:?}", meta.mode),
         delta_order: meta.delta_encoding_order,
+        delta_lag: meta.delta_lag,
+        has_exceptions: meta.has_exceptions,
+        seek_index_interval: meta.seek_index_interval,
+        value_min: value_bounds.map(|(lower, _)| lower.to_string()),
+        value_max: value_bounds.map(|(_, upper)| upper.to_string()),
+        approx_quantiles: (!approx_quantiles.is_empty()).then_some(approx_quantiles),
         latent_vars,
       });
     }
@@ -196,6 +238,7 @@ impl<T: PcoNumberLike> InspectHandler for CoreHandlerImpl<T> {
         footer_size,
         unknown_trailing_bytes,
       },
+      metadata: metadata.map(|metadata| metadata.key_values),
       chunks,
     };
 
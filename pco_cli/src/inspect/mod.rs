@@ -16,17 +16,28 @@ pub mod handler;
 #[derive(Clone, Debug, Parser)]
 pub struct InspectOpt {
   pub path: PathBuf,
+  /// Comma-separated quantiles (e.g. "0.1,0.5,0.9") to estimate for each
+  /// chunk from its metadata, without decompressing.
+  /// Only chunks in classic or dictionary mode without delta encoding or
+  /// exceptions support this.
+  #[arg(long, value_delimiter = ',')]
+  pub quantiles: Vec<f64>,
 }
 
 fn trivial_inspect(opt: &InspectOpt, src: &[u8]) -> Result<()> {
   let start_len = src.len();
-  let (fd, src) = FileDecompressor::new(src)?;
-  let header_size = start_len - src.len();
-  let no_cd = fd.chunk_decompressor::<i32, _>(src)?;
+  let (fd, after_header) = FileDecompressor::new(src)?;
+  let header_size = start_len - after_header.len();
+  let no_cd = fd.chunk_decompressor::<i32, _>(after_header)?;
   let src = match no_cd {
     MaybeChunkDecompressor::Some(_) => unreachable!("file was supposed to be trivial"),
     MaybeChunkDecompressor::EndOfData(src) => src,
   };
+  let metadata = if src.is_empty() {
+    fd.metadata(after_header)?
+  } else {
+    None
+  };
 
   let summary = Output {
     filename: opt.path.to_str().unwrap().to_string(),
@@ -41,9 +52,10 @@ fn trivial_inspect(opt: &InspectOpt, src: &[u8]) -> Result<()> {
       header_size,
       meta_size: 0,
       page_size: 0,
-      footer_size: 1,
+      footer_size: after_header.len() - src.len(),
       unknown_trailing_bytes: src.len(),
     },
+    metadata: metadata.map(|metadata| metadata.key_values),
     chunks: Vec::new(),
   };
   println!("{}", toml::to_string_pretty(&summary)?);
@@ -1,7 +1,23 @@
 use anyhow::anyhow;
-use arrow::datatypes::{DataType, TimeUnit};
+use arrow::datatypes::{DataType, TimeUnit, DECIMAL128_MAX_PRECISION};
 
-use pco::{FloatMultSpec, IntMultSpec};
+use pco::{
+  DecimalSpec, DeltaLagSpec, DictionarySpec, ExceptionSpec, FloatMultSpec, IntMultSpec, LpcSpec,
+  RunLengthSpec, ValueBoundsSpec,
+};
+
+pub fn delta_lag(s: &str) -> anyhow::Result<DeltaLagSpec> {
+  let lowercase = s.to_lowercase();
+  let spec = match lowercase.as_str() {
+    "enabled" => DeltaLagSpec::Enabled,
+    "disabled" => DeltaLagSpec::Disabled,
+    other => match other.parse::<usize>() {
+      Ok(lag) => DeltaLagSpec::Provided(lag),
+      _ => return Err(anyhow!("cannot parse delta lag: {}", other)),
+    },
+  };
+  Ok(spec)
+}
 
 pub fn int_mult(s: &str) -> anyhow::Result<IntMultSpec> {
   let lowercase = s.to_lowercase();
@@ -21,20 +37,100 @@ pub fn float_mult(s: &str) -> anyhow::Result<FloatMultSpec> {
   let spec = match lowercase.as_str() {
     "enabled" => FloatMultSpec::Enabled,
     "disabled" => FloatMultSpec::Disabled,
-    other => match other.parse::<f64>() {
-      Ok(mult) => FloatMultSpec::Provided(mult),
-      _ => return Err(anyhow!("cannot parse float mult: {}", other)),
-    },
+    "lossyenabled" => FloatMultSpec::LossyEnabled,
+    other => {
+      let (lossy, base_str) = match other.strip_prefix("lossy") {
+        Some(base_str) => (true, base_str),
+        None => (false, other),
+      };
+      match (lossy, base_str.parse::<f64>()) {
+        (false, Ok(mult)) => FloatMultSpec::Provided(mult),
+        (true, Ok(mult)) => FloatMultSpec::LossyProvided(mult),
+        _ => return Err(anyhow!("cannot parse float mult: {}", other)),
+      }
+    }
+  };
+  Ok(spec)
+}
+
+pub fn decimal(s: &str) -> anyhow::Result<DecimalSpec> {
+  let lowercase = s.to_lowercase();
+  let spec = match lowercase.as_str() {
+    "enabled" => DecimalSpec::Enabled,
+    "disabled" => DecimalSpec::Disabled,
+    other => return Err(anyhow!("cannot parse decimal: {}", other)),
+  };
+  Ok(spec)
+}
+
+pub fn dictionary(s: &str) -> anyhow::Result<DictionarySpec> {
+  let lowercase = s.to_lowercase();
+  let spec = match lowercase.as_str() {
+    "enabled" => DictionarySpec::Enabled,
+    "disabled" => DictionarySpec::Disabled,
+    other => return Err(anyhow!("cannot parse dictionary: {}", other)),
+  };
+  Ok(spec)
+}
+
+pub fn run_length(s: &str) -> anyhow::Result<RunLengthSpec> {
+  let lowercase = s.to_lowercase();
+  let spec = match lowercase.as_str() {
+    "enabled" => RunLengthSpec::Enabled,
+    "disabled" => RunLengthSpec::Disabled,
+    other => return Err(anyhow!("cannot parse run length: {}", other)),
+  };
+  Ok(spec)
+}
+
+pub fn lpc(s: &str) -> anyhow::Result<LpcSpec> {
+  let lowercase = s.to_lowercase();
+  let spec = match lowercase.as_str() {
+    "enabled" => LpcSpec::Enabled,
+    "disabled" => LpcSpec::Disabled,
+    other => return Err(anyhow!("cannot parse lpc: {}", other)),
+  };
+  Ok(spec)
+}
+
+pub fn exceptions(s: &str) -> anyhow::Result<ExceptionSpec> {
+  let lowercase = s.to_lowercase();
+  let spec = match lowercase.as_str() {
+    "enabled" => ExceptionSpec::Enabled,
+    "disabled" => ExceptionSpec::Disabled,
+    other => return Err(anyhow!("cannot parse exceptions: {}", other)),
+  };
+  Ok(spec)
+}
+
+pub fn value_bounds(s: &str) -> anyhow::Result<ValueBoundsSpec> {
+  let lowercase = s.to_lowercase();
+  let spec = match lowercase.as_str() {
+    "enabled" => ValueBoundsSpec::Enabled,
+    "disabled" => ValueBoundsSpec::Disabled,
+    other => {
+      return Err(anyhow!(
+        "cannot parse value bounds: {}",
+        other
+      ))
+    }
   };
   Ok(spec)
 }
 
 pub fn arrow_dtype(s: &str) -> anyhow::Result<DataType> {
   let name_pairs = [
+    ("f16", DataType::Float16),
     ("f32", DataType::Float32),
     ("f64", DataType::Float64),
+    ("i16", DataType::Int16),
     ("i32", DataType::Int32),
     ("i64", DataType::Int64),
+    (
+      "i128",
+      DataType::Decimal128(DECIMAL128_MAX_PRECISION, 0),
+    ),
+    ("u16", DataType::UInt16),
     ("u32", DataType::UInt32),
     ("u64", DataType::UInt64),
     (
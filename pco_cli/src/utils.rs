@@ -55,6 +55,12 @@ pub fn dtype_name<T: NumberLike>() -> String {
   any::type_name::<T>().split(':').last().unwrap().to_string()
 }
 
+pub fn arrow_to_validity(arrow_array: &ArrayRef) -> Vec<bool> {
+  (0..arrow_array.len())
+    .map(|i| arrow_array.is_valid(i))
+    .collect()
+}
+
 pub fn arrow_to_nums<P: ArrowNumberLike>(arrow_array: ArrayRef) -> Vec<P::Pco> {
   arrow_array
     .as_primitive::<P>()
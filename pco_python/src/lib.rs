@@ -1,21 +1,56 @@
+use half::f16;
 use numpy::PyArrayDyn;
 use pco::data_types::CoreDataType;
-use pco::{ChunkConfig, FloatMultSpec, IntMultSpec, PagingSpec, Progress};
+use pco::{
+  ChunkConfig, DecimalSpec, DeltaLagSpec, DictionarySpec, ExceptionSpec, FloatMultSpec,
+  IntMultSpec, LpcSpec, PagingSpec, Progress, RunLengthSpec, ValueBoundsSpec,
+};
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::{pymodule, FromPyObject, PyModule, PyResult, Python};
 use pyo3::{py_run, pyclass, pymethods, PyErr};
 
 use pco::errors::PcoError;
 
+// Numpy has no 128-bit integer types, so we only support this subset of
+// pco's core data types.
+macro_rules! with_numpy_dtypes {
+  ($inner:ident) => {
+    $inner! {
+      U32(U32) => u32,
+      U64(U64) => u64,
+      I32(U32) => i32,
+      I64(U64) => i64,
+      F32(U32) => f32,
+      F64(U64) => f64,
+      U16(U16) => u16,
+      I16(U16) => i16,
+      F16(U16) => half::f16,
+    }
+  };
+}
+
+macro_rules! with_numpy_latents {
+  ($inner:ident) => {
+    $inner! {
+      U16 => u16,
+      U32 => u32,
+      U64 => u64,
+    }
+  };
+}
+
 pub mod standalone;
 pub mod wrapped;
 
 pub fn core_dtype_from_str(s: &str) -> PyResult<CoreDataType> {
   match s.to_uppercase().as_str() {
+    "F16" => Ok(CoreDataType::F16),
     "F32" => Ok(CoreDataType::F32),
     "F64" => Ok(CoreDataType::F64),
+    "I16" => Ok(CoreDataType::I16),
     "I32" => Ok(CoreDataType::I32),
     "I64" => Ok(CoreDataType::I64),
+    "U16" => Ok(CoreDataType::U16),
     "U32" => Ok(CoreDataType::U32),
     "U64" => Ok(CoreDataType::U64),
     _ => Err(PyRuntimeError::new_err(format!(
@@ -46,12 +81,19 @@ pub fn pco_err_to_py(pco: PcoError) -> PyErr {
   PyRuntimeError::new_err(format!("pco error: {}", pco))
 }
 
+pub fn unsupported_dtype_err(dtype: CoreDataType) -> PyErr {
+  PyRuntimeError::new_err(format!(
+    "data type {:?} is not supported by numpy",
+    dtype,
+  ))
+}
+
 #[pyclass(name = "PagingSpec")]
 #[derive(Clone, Default)]
 pub struct PyPagingSpec(PagingSpec);
 
-/// Determines how pcodec splits a chunk into pages. In
-/// standalone.simple_compress, this instead controls how pcodec splits a file
+/// Determines how pcodec splits a chunk into pages, or, when passed as a
+/// ChunkConfig's chunking_spec, how standalone.simple_compress splits a file
 /// into chunks.
 #[pymethods]
 impl PyPagingSpec {
@@ -77,6 +119,15 @@ pub struct PyChunkConfig {
   int_mult_spec: String,
   float_mult_spec: String,
   paging_spec: PyPagingSpec,
+  dictionary_spec: String,
+  run_length_spec: String,
+  delta_lag_spec: String,
+  lpc_spec: String,
+  decimal_spec: String,
+  exception_spec: String,
+  seek_index_interval: Option<usize>,
+  value_bounds_spec: String,
+  chunking_spec: PyPagingSpec,
 }
 
 #[pymethods]
@@ -91,11 +142,45 @@ impl PyChunkConfig {
   /// :param int_mult_spec: either 'enabled' or 'disabled'. If enabled, pcodec
   /// will consider using int mult mode, which can substantially improve
   /// compression ratio but decrease speed in some cases for integer types.
-  /// :param float_mult_spec: either 'enabled' or 'disabled'. If enabled, pcodec
-  /// will consider using float mult mode, which can substantially improve
-  /// compression ratio but decrease speed in some cases for float types.
+  /// :param float_mult_spec: either 'enabled', 'disabled', or 'lossy_enabled'.
+  /// If enabled, pcodec will consider using float mult mode, which can
+  /// substantially improve compression ratio but decrease speed in some cases
+  /// for float types. If lossy_enabled, pcodec will additionally drop the
+  /// ULPs adjustments when using float mult mode, rounding each float to the
+  /// nearest multiple of the detected base.
   /// :param paging_spec: a PagingSpec describing how many numbers should
   /// go into each page.
+  /// :param dictionary_spec: either 'enabled' or 'disabled'. If enabled,
+  /// pcodec will consider using dictionary mode, which can substantially
+  /// improve compression ratio for data with few distinct values but decrease
+  /// compression speed.
+  /// :param run_length_spec: either 'enabled' or 'disabled'. If enabled,
+  /// pcodec will consider using run length mode, which can substantially
+  /// improve compression ratio for data with long runs of repeated values.
+  /// :param delta_lag_spec: either 'enabled' or 'disabled'. If enabled,
+  /// pcodec will consider delta encoding with a lag, taking differences
+  /// between numbers that are a fixed distance apart. This can substantially
+  /// improve compression ratio for periodic or interleaved data but decrease
+  /// compression speed.
+  /// :param lpc_spec: either 'enabled' or 'disabled'. If enabled, pcodec
+  /// will consider fitting a linear predictor to each chunk, which can
+  /// substantially improve compression ratio for oscillating signals like
+  /// audio but decrease compression speed.
+  /// :param decimal_spec: either 'enabled' or 'disabled'. If enabled, pcodec
+  /// will consider using decimal mode for floats, which can substantially
+  /// improve compression ratio for floats parsed from decimal strings.
+  /// :param exception_spec: either 'enabled' or 'disabled'. If enabled,
+  /// pcodec will consider storing a few extreme outliers separately, which
+  /// can improve compression ratio for data with rare sentinel values.
+  /// :param seek_index_interval: either a count of batches or None. If set,
+  /// each page stores a seek index with an entry every this many batches of
+  /// 256 numbers, letting decompressors skip ahead within a page at the cost
+  /// of some compression ratio.
+  /// :param value_bounds_spec: either 'enabled' or 'disabled'. If enabled,
+  /// each chunk's metadata stores the minimum and maximum of its numbers,
+  /// unless compression is lossy.
+  /// :param chunking_spec: a PagingSpec describing how many numbers
+  /// standalone.simple_compress should put into each chunk.
   ///
   /// :returns: A new ChunkConfig object.
   #[new]
@@ -105,6 +190,15 @@ impl PyChunkConfig {
     int_mult_spec="enabled".to_string(),
     float_mult_spec="enabled".to_string(),
     paging_spec=PyPagingSpec::default(),
+    dictionary_spec="enabled".to_string(),
+    run_length_spec="enabled".to_string(),
+    delta_lag_spec="enabled".to_string(),
+    lpc_spec="enabled".to_string(),
+    decimal_spec="enabled".to_string(),
+    exception_spec="enabled".to_string(),
+    seek_index_interval=None,
+    value_bounds_spec="disabled".to_string(),
+    chunking_spec=PyPagingSpec(PagingSpec::EqualPagesUpTo(pco::DEFAULT_MAX_CHUNK_N)),
   ))]
   fn new(
     compression_level: usize,
@@ -112,6 +206,15 @@ impl PyChunkConfig {
     int_mult_spec: String,
     float_mult_spec: String,
     paging_spec: PyPagingSpec,
+    dictionary_spec: String,
+    run_length_spec: String,
+    delta_lag_spec: String,
+    lpc_spec: String,
+    decimal_spec: String,
+    exception_spec: String,
+    seek_index_interval: Option<usize>,
+    value_bounds_spec: String,
+    chunking_spec: PyPagingSpec,
   ) -> Self {
     Self {
       compression_level,
@@ -119,6 +222,15 @@ impl PyChunkConfig {
       int_mult_spec,
       float_mult_spec,
       paging_spec,
+      dictionary_spec,
+      run_length_spec,
+      delta_lag_spec,
+      lpc_spec,
+      decimal_spec,
+      exception_spec,
+      seek_index_interval,
+      value_bounds_spec,
+      chunking_spec,
     }
   }
 }
@@ -140,6 +252,7 @@ impl TryFrom<&PyChunkConfig> for ChunkConfig {
     let float_mult_spec = match py_config.float_mult_spec.to_lowercase().as_str() {
       "enabled" => FloatMultSpec::Enabled,
       "disabled" => FloatMultSpec::Disabled,
+      "lossy_enabled" => FloatMultSpec::LossyEnabled,
       other => {
         return Err(PyRuntimeError::new_err(format!(
           "unknown float mult spec: {}",
@@ -147,12 +260,91 @@ impl TryFrom<&PyChunkConfig> for ChunkConfig {
         )))
       }
     };
+    let dictionary_spec = match py_config.dictionary_spec.to_lowercase().as_str() {
+      "enabled" => DictionarySpec::Enabled,
+      "disabled" => DictionarySpec::Disabled,
+      other => {
+        return Err(PyRuntimeError::new_err(format!(
+          "unknown dictionary spec: {}",
+          other
+        )))
+      }
+    };
+    let run_length_spec = match py_config.run_length_spec.to_lowercase().as_str() {
+      "enabled" => RunLengthSpec::Enabled,
+      "disabled" => RunLengthSpec::Disabled,
+      other => {
+        return Err(PyRuntimeError::new_err(format!(
+          "unknown run length spec: {}",
+          other
+        )))
+      }
+    };
+    let delta_lag_spec = match py_config.delta_lag_spec.to_lowercase().as_str() {
+      "enabled" => DeltaLagSpec::Enabled,
+      "disabled" => DeltaLagSpec::Disabled,
+      other => {
+        return Err(PyRuntimeError::new_err(format!(
+          "unknown delta lag spec: {}",
+          other
+        )))
+      }
+    };
+    let lpc_spec = match py_config.lpc_spec.to_lowercase().as_str() {
+      "enabled" => LpcSpec::Enabled,
+      "disabled" => LpcSpec::Disabled,
+      other => {
+        return Err(PyRuntimeError::new_err(format!(
+          "unknown lpc spec: {}",
+          other
+        )))
+      }
+    };
+    let decimal_spec = match py_config.decimal_spec.to_lowercase().as_str() {
+      "enabled" => DecimalSpec::Enabled,
+      "disabled" => DecimalSpec::Disabled,
+      other => {
+        return Err(PyRuntimeError::new_err(format!(
+          "unknown decimal spec: {}",
+          other
+        )))
+      }
+    };
+    let exception_spec = match py_config.exception_spec.to_lowercase().as_str() {
+      "enabled" => ExceptionSpec::Enabled,
+      "disabled" => ExceptionSpec::Disabled,
+      other => {
+        return Err(PyRuntimeError::new_err(format!(
+          "unknown exception spec: {}",
+          other
+        )))
+      }
+    };
+    let value_bounds_spec = match py_config.value_bounds_spec.to_lowercase().as_str() {
+      "enabled" => ValueBoundsSpec::Enabled,
+      "disabled" => ValueBoundsSpec::Disabled,
+      other => {
+        return Err(PyRuntimeError::new_err(format!(
+          "unknown value bounds spec: {}",
+          other
+        )))
+      }
+    };
     let res = ChunkConfig::default()
       .with_compression_level(py_config.compression_level)
       .with_delta_encoding_order(py_config.delta_encoding_order)
+      .with_delta_lag_spec(delta_lag_spec)
       .with_int_mult_spec(int_mult_spec)
       .with_float_mult_spec(float_mult_spec)
-      .with_paging_spec(py_config.paging_spec.0.clone());
+      .with_decimal_spec(decimal_spec)
+      .with_dictionary_spec(dictionary_spec)
+      .with_run_length_spec(run_length_spec)
+      .with_lpc_spec(lpc_spec)
+      .with_exception_spec(exception_spec)
+      .with_seek_index_interval(py_config.seek_index_interval)
+      .with_value_bounds_spec(value_bounds_spec)
+      .with_paging_spec(py_config.paging_spec.0.clone())
+      .with_chunking_spec(py_config.chunking_spec.0.clone());
     Ok(res)
   }
 }
@@ -162,10 +354,13 @@ impl TryFrom<&PyChunkConfig> for ChunkConfig {
 // The first dyn refers to dynamic dtype; the second to dynamic shape
 #[derive(Debug, FromPyObject)]
 pub enum DynTypedPyArrayDyn<'py> {
+  F16(&'py PyArrayDyn<f16>),
   F32(&'py PyArrayDyn<f32>),
   F64(&'py PyArrayDyn<f64>),
+  I16(&'py PyArrayDyn<i16>),
   I32(&'py PyArrayDyn<i32>),
   I64(&'py PyArrayDyn<i64>),
+  U16(&'py PyArrayDyn<u16>),
   U32(&'py PyArrayDyn<u32>),
   U64(&'py PyArrayDyn<u64>),
 }
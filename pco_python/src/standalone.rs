@@ -7,9 +7,9 @@ use pyo3::{pyfunction, wrap_pyfunction, PyObject, PyResult, Python};
 
 use pco::data_types::NumberLike;
 use pco::standalone::{FileDecompressor, MaybeChunkDecompressor};
-use pco::{standalone, with_core_dtypes, ChunkConfig};
+use pco::{standalone, ChunkConfig};
 
-use crate::{pco_err_to_py, DynTypedPyArrayDyn, PyChunkConfig, PyProgress};
+use crate::{pco_err_to_py, unsupported_dtype_err, DynTypedPyArrayDyn, PyChunkConfig, PyProgress};
 
 fn decompress_chunks<'py, T: NumberLike + Element>(
   py: Python<'py>,
@@ -31,7 +31,7 @@ fn decompress_chunks<'py, T: NumberLike + Element>(
       .decompress(&mut res[initial_len..])
       .map_err(pco_err_to_py)?;
     assert!(progress.finished);
-    src = chunk_decompressor.into_src();
+    src = chunk_decompressor.into_src().map_err(pco_err_to_py)?;
   }
   let py_array = res.into_pyarray(py);
   Ok(py_array)
@@ -66,7 +66,8 @@ pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
   ///
   /// :param nums: numpy array to compress. This may have any shape.
   /// However, it must be contiguous, and only the following data types are
-  /// supported: float32, float64, int32, int64, uint32, uint64.
+  /// supported: float16, float32, float64, int16, int32, int64, uint16, uint32,
+  /// uint64.
   /// :param config: a ChunkConfig object containing compression level and
   /// other settings.
   ///
@@ -87,7 +88,7 @@ pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
         }
       }
     }
-    with_core_dtypes!(match_py_array)
+    with_numpy_dtypes!(match_py_array)
   }
   m.add_function(wrap_pyfunction!(simple_compress, m)?)?;
 
@@ -113,7 +114,7 @@ pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
         }
       }
     }
-    with_core_dtypes!(match_py_array)
+    with_numpy_dtypes!(match_py_array)
   }
   m.add_function(wrap_pyfunction!(simple_decompress_into, m)?)?;
 
@@ -141,6 +142,7 @@ pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
       {$($name:ident($lname:ident) => $t:ty,)+} => {
         match dtype {
           $(Known($name) => Ok(decompress_chunks::<$t>(py, src, file_decompressor)?.into()),)+
+          Known(other) => Err(unsupported_dtype_err(other)),
           Termination => Ok(PyNone::get(py).into()),
           Unknown(other) => Err(PyRuntimeError::new_err(format!(
             "unrecognized dtype byte {:?}",
@@ -149,7 +151,7 @@ pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
         }
       }
     }
-    with_core_dtypes!(match_dtype)
+    with_numpy_dtypes!(match_dtype)
   }
   m.add_function(wrap_pyfunction!(simple_decompress, m)?)?;
 
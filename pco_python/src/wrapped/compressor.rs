@@ -6,7 +6,7 @@ use pyo3::{pyclass, pymethods, PyObject, PyResult, Python};
 
 use pco::data_types::{Latent, NumberLike};
 use pco::wrapped::{ChunkCompressor, FileCompressor};
-use pco::{with_core_dtypes, with_core_latents, ChunkConfig};
+use pco::ChunkConfig;
 
 use crate::{pco_err_to_py, DynTypedPyArrayDyn, PyChunkConfig};
 
@@ -17,6 +17,7 @@ struct PyFc {
 }
 
 enum DynCc {
+  U16(ChunkCompressor<u16>),
   U32(ChunkCompressor<u32>),
   U64(ChunkCompressor<u64>),
 }
@@ -67,7 +68,8 @@ impl PyFc {
   ///
   /// :param nums: numpy array to compress. This may have any shape.
   /// However, it must be contiguous, and only the following data types are
-  /// supported: float32, float64, int32, int64, uint32, uint64.
+  /// supported: float16, float32, float64, int16, int32, int64, uint16, uint32,
+  /// uint64.
   /// :param config: a ChunkConfig object containing compression level and
   /// other settings.
   ///
@@ -83,7 +85,7 @@ impl PyFc {
         }
       }
     }
-    let dyn_cc = with_core_dtypes!(match_nums);
+    let dyn_cc = with_numpy_dtypes!(match_nums);
     Ok(PyCc(dyn_cc))
   }
 }
@@ -114,7 +116,7 @@ impl PyCc {
         }
       }
     }
-    with_core_latents!(match_cc)
+    with_numpy_latents!(match_cc)
   }
 
   /// :returns: a list containing the count of numbers in each page.
@@ -127,7 +129,7 @@ impl PyCc {
         }
       }
     }
-    with_core_latents!(match_cc)
+    with_numpy_latents!(match_cc)
   }
 
   /// :param page_idx: an int for which page you want to write.
@@ -144,7 +146,7 @@ impl PyCc {
         }
       }
     }
-    with_core_latents!(match_cc)
+    with_numpy_latents!(match_cc)
   }
 }
 
@@ -1,12 +1,14 @@
+use numpy::PyArray1;
 use pco::data_types::CoreDataType;
-use pco::with_core_dtypes;
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::types::{PyBytes, PyModule};
-use pyo3::{pyclass, pymethods, PyResult, Python};
+use pyo3::{pyclass, pymethods, PyObject, PyResult, Python};
 
 use pco::wrapped::{ChunkDecompressor, FileDecompressor};
 
-use crate::{core_dtype_from_str, pco_err_to_py, DynTypedPyArrayDyn, PyProgress};
+use crate::{
+  core_dtype_from_str, pco_err_to_py, unsupported_dtype_err, DynTypedPyArrayDyn, PyProgress,
+};
 
 #[pyclass(name = "FileDecompressor")]
 struct PyFd(FileDecompressor);
@@ -19,7 +21,7 @@ macro_rules! impl_dyn_cd {
     }
   }
 }
-with_core_dtypes!(impl_dyn_cd);
+with_numpy_dtypes!(impl_dyn_cd);
 
 #[pyclass(name = "ChunkDecompressor")]
 struct PyCd {
@@ -71,11 +73,12 @@ impl PyFd {
               .map_err(pco_err_to_py)?;
             (DynCd::$name(generic_cd), rest)
           })+
+          other => return Err(unsupported_dtype_err(other)),
         }
       }
     }
 
-    let (inner, rest) = with_core_dtypes!(match_dtype);
+    let (inner, rest) = with_numpy_dtypes!(match_dtype);
     let res = PyCd { inner, dtype };
     let n_bytes_read = src.len() - rest.len();
     Ok((res, n_bytes_read))
@@ -84,6 +87,25 @@ impl PyFd {
 
 #[pymethods]
 impl PyCd {
+  /// :returns: a numpy array containing a lower and upper bound (inclusive)
+  /// on the chunk's numbers, or None if the chunk metadata doesn't determine
+  /// any. The bounds are exact if the chunk was compressed with
+  /// value_bounds_spec='enabled'.
+  fn value_bounds(&self, py: Python) -> Option<PyObject> {
+    let inner = &self.inner;
+    macro_rules! match_cd {
+      {$($name:ident($lname:ident) => $t:ty,)+} => {
+        match inner {
+          $(DynCd::$name(cd) => cd
+            .meta()
+            .value_bounds::<$t>()
+            .map(|(lower, upper)| PyArray1::from_slice(py, &[lower, upper]).into()),)+
+        }
+      }
+    }
+    with_numpy_dtypes!(match_cd)
+  }
+
   // TODO find a way to reuse docstring content
   /// Decompresses a page into the provided array. If dst is shorter than
   /// page_n, writes as much as possible and leaves the rest
@@ -130,7 +152,7 @@ impl PyCd {
         }
       }
     }
-    let (progress, rest) = with_core_dtypes!(match_cd_and_dst);
+    let (progress, rest) = with_numpy_dtypes!(match_cd_and_dst);
 
     let n_bytes_read = src.len() - rest.len();
     Ok((PyProgress::from(progress), n_bytes_read))
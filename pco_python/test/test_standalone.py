@@ -10,7 +10,7 @@ all_shapes = (
   [9, 100],
 )
 
-all_dtypes = ('f4', 'f8', 'i4', 'i8', 'u4', 'u8')
+all_dtypes = ('f2', 'f4', 'f8', 'i2', 'i4', 'i8', 'u2', 'u4', 'u8')
 
 @pytest.mark.parametrize("shape", all_shapes)
 @pytest.mark.parametrize("dtype", all_dtypes)
@@ -30,7 +30,7 @@ def test_round_trip_decompress_into(shape, dtype):
 @pytest.mark.parametrize("dtype", all_dtypes)
 def test_round_trip_simple_decompress(shape, dtype):
   data = np.random.uniform(0, 1000, size=shape).astype(dtype)
-  compressed = standalone.simple_compress(data, ChunkConfig(paging_spec=PagingSpec.equal_pages_up_to(300)))
+  compressed = standalone.simple_compress(data, ChunkConfig(chunking_spec=PagingSpec.equal_pages_up_to(300)))
   out = standalone.simple_decompress(compressed)
   # data are decompressed into a 1D array; ensure it can be reshaped to the original shape
   out.shape = shape
@@ -99,6 +99,6 @@ def test_compression_options():
       delta_encoding_order=1,
       int_mult_spec='disabled',
       float_mult_spec='DISABLED',
-      paging_spec=PagingSpec.equal_pages_up_to(77),
+      chunking_spec=PagingSpec.equal_pages_up_to(77),
     )
   )) > default_size
@@ -4,7 +4,7 @@ from pcodec.wrapped import FileCompressor, FileDecompressor
 import pytest
 
 np.random.seed(12345)
-all_dtypes = ('f4', 'f8', 'i4', 'i8', 'u4', 'u8')
+all_dtypes = ('f2', 'f4', 'f8', 'i2', 'i4', 'i8', 'u2', 'u4', 'u8')
 
 @pytest.mark.parametrize("dtype", all_dtypes)
 def test_compress(dtype):
@@ -47,3 +47,16 @@ def test_compress(dtype):
   progress, n_bytes_read = cd.read_page_into(page0, 6, dst0)
   np.testing.assert_array_equal(dst0, data[:6])
   assert n_bytes_read == len(page0)
+
+@pytest.mark.parametrize("dtype", all_dtypes)
+def test_value_bounds(dtype):
+  data = np.random.uniform(0, 1000, size=[100]).astype(dtype)
+  pco_dtype = dtype[0].upper() + str(int(dtype[1]) * 8)
+
+  fc = FileCompressor()
+  fd, _ = FileDecompressor.from_header(fc.write_header())
+  cc = fc.chunk_compressor(data, ChunkConfig(value_bounds_spec='enabled'))
+  cd, _ = fd.read_chunk_meta(cc.write_chunk_meta(), pco_dtype)
+  bounds = cd.value_bounds()
+  assert bounds.dtype == data.dtype
+  np.testing.assert_array_equal(bounds, [data.min(), data.max()])